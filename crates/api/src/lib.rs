@@ -1,30 +1,51 @@
-use std::fmt::{self, Display};
+mod schema;
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
 
 use common::{
-    commands::ApiCommand,
-    item::{Item, Name},
+    commands::{ApiCommand, Update},
+    item::{Item, ItemDetail, Name},
     items::Items,
-    list::List,
-    recipes::{Ingredients, Recipe},
-    section::Section,
+    list::{List, ListDiff, ShoppingOrder, Trip, TripId},
+    recipes::{AddRecipeReport, Ingredients, Recipe, RecipeStatus},
+    section::{ItemsCsvReport, Section, SectionMapReport},
 };
-use persistence::store::{Store, StoreDispatch, StoreError, StoreResponse, StoreType};
+use persistence::{
+    dump::DatabaseDump,
+    import_store::MigrationSummary,
+    stats::DbStats,
+    store::{Store, StoreChunk, StoreDispatch, StoreError, StoreResponse},
+};
+
+pub use persistence::store::{StoreConfig, StoreType};
 
-use futures::FutureExt;
+use futures::{stream, FutureExt, Stream, StreamExt};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
 use thiserror::Error;
 use tokio::sync::{
     mpsc::{self, error::SendError},
     oneshot,
 };
 use tracing::{error, info, instrument, trace, warn};
+use url::Url;
 
 #[derive(Error, Debug)]
 pub enum ApiError {
     #[error("API shut down before reply")]
     ApiShutdownRx,
 
+    /// Boxed because it hands back the unsent command, and commands such as
+    /// `FetchRecipe` would otherwise make every `ApiError` that large.
     #[error("API shut down before send: {0}")]
-    ApiShutdownTx(#[from] SendError<ApiSendWithReply>),
+    ApiShutdownTx(#[from] Box<SendError<ApiSendWithReply>>),
+
+    #[error("invalid recipe URL: {0}")]
+    InvalidUrl(Url),
 
     #[error("{0}")]
     RecvError(#[from] oneshot::error::RecvError),
@@ -40,9 +61,14 @@ pub struct Api {
 impl Api {
     pub async fn init(store: StoreType) -> Result<ApiDispatch, ApiError> {
         info!("Initializing API with store type: {store}");
+        Self::init_with_config(StoreConfig::from(store)).await
+    }
 
+    /// Like [`init`](Self::init), with the store set up from `config`, e.g.
+    /// to seed the default sections.
+    pub async fn init_with_config(config: StoreConfig) -> Result<ApiDispatch, ApiError> {
         let api = Api {
-            store: Store::from_store_type(store).await?.init().await?,
+            store: Store::from_config(config).await?.init().await?,
         };
 
         let (tx, mut rx) = mpsc::channel::<ApiSendWithReply>(10);
@@ -52,18 +78,24 @@ impl Api {
             loop {
                 tokio::select! {
                     cmd = rx.recv().fuse() => {
-                        if let Some((command, reply)) = cmd {
-                            let result = api
-                                .execute(command)
-                                .await;
-
-                            reply
-                                .send(result)
-                                .await
-                                .map_err(|e| {
-                                    warn!(?e, "Send reply to API consumer failed");
-                                })
-                                .ok();
+                        match cmd {
+                            Some((command, ApiReply::Response(reply))) => {
+                                let result = api
+                                    .execute(command)
+                                    .await;
+
+                                reply
+                                    .send(result)
+                                    .await
+                                    .map_err(|e| {
+                                        warn!(?e, "Send reply to API consumer failed");
+                                    })
+                                    .ok();
+                            }
+                            Some((command, ApiReply::Stream(reply))) => {
+                                api.execute_stream(command, reply).await;
+                            }
+                            None => {}
                         }
                     }
                     else => break
@@ -76,14 +108,78 @@ impl Api {
 
     #[instrument(level = "debug", skip(self), ret(Debug))]
     async fn execute(&self, command: ApiCommand) -> Result<ApiResponse, ApiError> {
+        match &command {
+            ApiCommand::FetchRecipe { url, .. }
+            | ApiCommand::Update(Update::RefetchRecipe { url, .. }) => validate_recipe_url(url)?,
+            ApiCommand::Schema => return Ok(ApiResponse::Schema(schema::response_schema())),
+            _ => {}
+        }
         let (tx, rx) = oneshot::channel();
         self.store.send((command, tx)).await?;
         let res = rx.await??;
         Ok(res.into())
     }
+
+    /// Like [`execute`](Self::execute), but passes the store's reply on to
+    /// `reply` a chunk at a time as it arrives.
+    async fn execute_stream(
+        &self,
+        command: ApiCommand,
+        reply: mpsc::Sender<Result<ApiResponseChunk, ApiError>>,
+    ) {
+        match &command {
+            ApiCommand::FetchRecipe { url, .. }
+            | ApiCommand::Update(Update::RefetchRecipe { url, .. }) => {
+                if let Err(e) = validate_recipe_url(url) {
+                    reply.send(Err(e)).await.ok();
+                    return;
+                }
+            }
+            ApiCommand::Schema => {
+                let schema = ApiResponse::Schema(schema::response_schema());
+                reply
+                    .send(Ok(ApiResponseChunk::Response(Box::new(schema))))
+                    .await
+                    .ok();
+                return;
+            }
+            _ => {}
+        }
+        let (tx, mut rx) = mpsc::channel(10);
+        if let Err(e) = self.store.send_stream(command, tx).await {
+            reply.send(Err(e.into())).await.ok();
+            return;
+        }
+        tokio::task::spawn(async move {
+            while let Some(chunk) = rx.recv().await {
+                let chunk = chunk.map(ApiResponseChunk::from).map_err(ApiError::from);
+                if reply.send(chunk).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+/// Rejects URLs the recipe fetcher can't use before they reach the store.
+fn validate_recipe_url(url: &Url) -> Result<(), ApiError> {
+    let has_host = url.host_str().is_some_and(|host| !host.is_empty());
+    if matches!(url.scheme(), "http" | "https") && has_host {
+        Ok(())
+    } else {
+        Err(ApiError::InvalidUrl(url.clone()))
+    }
 }
 
-type ApiSendWithReply = (ApiCommand, mpsc::Sender<Result<ApiResponse, ApiError>>);
+type ApiSendWithReply = (ApiCommand, ApiReply);
+
+/// Where the API sends what a command returns: whole, or one
+/// [`ApiResponseChunk`] at a time.
+#[derive(Debug)]
+pub enum ApiReply {
+    Response(mpsc::Sender<Result<ApiResponse, ApiError>>),
+    Stream(mpsc::Sender<Result<ApiResponseChunk, ApiError>>),
+}
 
 #[derive(Debug, Clone)]
 /// A clonable API handle
@@ -97,7 +193,11 @@ impl ApiDispatch {
         let (reply_tx, mut reply_rx) = mpsc::channel(1);
         trace!(?command, "Dispatch command to API");
 
-        self.tx.clone().send((command, reply_tx)).await?;
+        self.tx
+            .clone()
+            .send((command, ApiReply::Response(reply_tx)))
+            .await
+            .map_err(Box::new)?;
 
         let reply = reply_rx.recv().await;
         if let Some(Err(ref error)) = reply {
@@ -106,29 +206,142 @@ impl ApiDispatch {
 
         reply.ok_or(ApiError::ApiShutdownRx)?
     }
+
+    /// Like [`dispatch`](Self::dispatch), but never fails: errors, including
+    /// commands that aren't implemented yet, come back as
+    /// [`ApiResponse::Error`] so an interactive loop can keep going.
+    pub async fn try_execute(&self, command: ApiCommand) -> ApiResponse {
+        self.dispatch(command)
+            .await
+            .unwrap_or_else(|error| ApiResponse::Error(error.to_string()))
+    }
+
+    /// Like [`dispatch`](Self::dispatch), but yields the items or recipes of
+    /// collection responses one at a time. The library's items and recipes
+    /// are streamed from the store as they're read. Other responses are
+    /// yielded whole, as a single chunk.
+    pub async fn execute_stream(
+        &self,
+        command: ApiCommand,
+    ) -> impl Stream<Item = Result<ApiResponseChunk, ApiError>> {
+        let (reply_tx, reply_rx) = mpsc::channel(10);
+        trace!(?command, "Dispatch streamed command to API");
+
+        let sent = self
+            .tx
+            .send((command, ApiReply::Stream(reply_tx)))
+            .await
+            .map_err(|e| ApiError::from(Box::new(e)));
+        let failed = sent.err().map(Err);
+        stream::iter(failed).chain(stream::unfold(reply_rx, |mut reply_rx| async move {
+            reply_rx.recv().await.map(|chunk| (chunk, reply_rx))
+        }))
+    }
 }
 
-#[derive(Debug)]
+/// One piece of an [`ApiResponse`] yielded by [`ApiDispatch::execute_stream`].
+#[derive(Debug, Serialize)]
+pub enum ApiResponseChunk {
+    Item(Item),
+    Recipe(Recipe),
+    Response(Box<ApiResponse>),
+}
+
+impl From<StoreChunk> for ApiResponseChunk {
+    fn from(chunk: StoreChunk) -> Self {
+        match chunk {
+            StoreChunk::Item(item) => Self::Item(item),
+            StoreChunk::Recipe(recipe) => Self::Recipe(recipe),
+            StoreChunk::Response(response) => Self::Response(Box::new((*response).into())),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
 pub enum ApiResponse {
     AddedChecklistItem(Name),
     AddedItem(Name),
     AddedListItem(Name),
     AddedListRecipe(Recipe),
-    AddedRecipe(Recipe),
+    AddedRecipe(Recipe, AddRecipeReport),
+    AddedSubscription(Name),
+    AppliedSectionMap(SectionMapReport),
     Checklist(Vec<Item>),
+    ClearedChecklist,
+    ClearedList,
+    CommonListIngredients(Vec<Name>),
+    CompletedTrip(TripId),
     DeletedRecipe(Recipe),
+    DeletedSections(Vec<Section>),
+    DeletedSubscription(Name),
     DeletedChecklistItem(Name),
+    DeletedItem(Name),
+    DeletedListItem(Name),
+    /// An error, as returned by [`ApiDispatch::try_execute`].
+    Error(String),
+    DuplicateItems(Vec<Vec<Name>>),
+    Dump(DatabaseDump),
     Exported(Vec<Item>, List),
+    ExportedChecklist(String),
+    ExportedList(String),
+    ExportedRecipe(String),
     FetchedRecipe((Recipe, Ingredients)),
+    FrequentlyListed(Vec<(Name, usize)>),
+    Id(Option<i32>),
+    IngredientHistogram(BTreeMap<i64, i64>),
     ItemAlreadyAdded(Name),
+    ItemDetail(ItemDetail),
+    ItemNotFound {
+        query: Name,
+        suggestions: Vec<Name>,
+    },
     Items(Items),
-    ImportToSqlite,
+    ItemsWithSections(Vec<(Item, Option<Section>)>),
+    ImportedChecklist(Vec<Name>),
+    ImportedItemsCsv(ItemsCsvReport),
+    ImportedRecipes(Vec<Recipe>),
+    ImportedShareCode(List),
+    ImportToSqlite(MigrationSummary),
+    IsEmpty(bool),
     List(List),
+    ListDiff(ListDiff),
+    ListRecipeStatus(Vec<RecipeStatus>),
+    MissingFromLibrary(Vec<i32>),
+    MergedRecipes(Recipe, Recipe),
+    MigrationStatus {
+        applied: Vec<String>,
+        pending: Vec<String>,
+    },
+    NormalizedUnit(String, String, usize),
     NothingReturned(ApiCommand),
+    Overlap(Vec<Name>),
+    RecipePreviews(Vec<(Recipe, Vec<Name>)>),
     Recipes(Vec<Recipe>),
     RecipeIngredients(Option<Ingredients>),
+    RecipeCoverage(Vec<(Recipe, i64, i64)>),
+    RecipeIngredientCount(Recipe, i64),
+    RecipeShoppingStatus(Vec<(Name, bool)>),
     RefreshList,
+    RemovedListRecipe(Recipe),
+    RenamedItem(Name, Name),
+    RenamedRecipe(Recipe, Recipe),
+    RenamedSection(Section, Section),
+    Restored,
+    RestoredRecipe(Recipe),
+    /// A JSON Schema describing the serialized shape of every response.
+    Schema(Value),
     Sections(Vec<Section>),
+    ShareCode(String),
+    ShoppingOrder(ShoppingOrder),
+    Stats(DbStats),
+    SubscribedSection(Section, Vec<Name>),
+    SuggestedSection(Name, Option<Section>),
+    Subscriptions(Vec<Item>),
+    SubstitutedIngredient(Name, Name),
+    ToggledListItem(Name, bool),
+    Trips(Vec<Trip>),
+    Units(Vec<String>),
+    UpdatedIngredient(Recipe, Name),
 }
 
 impl Display for ApiResponse {
@@ -141,7 +354,33 @@ impl Display for ApiResponse {
                 writeln!(f, "\nrecipe added:\n{recipe}")?;
                 Ok(())
             }
-            Self::AddedRecipe(name) => writeln!(f, "\nrecipe added: {name}"),
+            Self::AddedRecipe(name, report) => {
+                writeln!(f, "\nrecipe added: {name}")?;
+                if !report.new_items().is_empty() {
+                    writeln!(f, "new items:")?;
+                    for item in report.new_items() {
+                        writeln!(f, "  {item}")?;
+                    }
+                }
+                Ok(())
+            }
+            Self::AddedSubscription(name) => writeln!(f, "\nsubscribed to: {name}"),
+            Self::AppliedSectionMap(report) => {
+                writeln!(f, "\nsections applied: {}", report.applied())?;
+                if !report.missing_items().is_empty() {
+                    writeln!(f, "missing items:")?;
+                    for item in report.missing_items() {
+                        writeln!(f, "  {item}")?;
+                    }
+                }
+                if !report.missing_sections().is_empty() {
+                    writeln!(f, "missing sections:")?;
+                    for section in report.missing_sections() {
+                        writeln!(f, "  {section}")?;
+                    }
+                }
+                Ok(())
+            }
             Self::Checklist(items) => {
                 writeln!(f, "\nchecklist:")?;
                 for item in items {
@@ -149,8 +388,33 @@ impl Display for ApiResponse {
                 }
                 Ok(())
             }
+            Self::ClearedChecklist => writeln!(f, "\nChecklist is now empty"),
+            Self::ClearedList => writeln!(f, "\nList is now empty"),
             Self::DeletedChecklistItem(name) => writeln!(f, "\ndeleted from checklist: \n{name}"),
+            Self::DeletedItem(name) => writeln!(f, "\ndeleted item: \n{name}"),
+            Self::DeletedListItem(name) => writeln!(f, "\ndeleted from list: \n{name}"),
             Self::DeletedRecipe(recipe) => writeln!(f, "\ndeleted recipe: \n{recipe}"),
+            Self::DeletedSections(sections) => {
+                writeln!(f, "\ndeleted sections:")?;
+                for section in sections {
+                    writeln!(f, "{section}")?;
+                }
+                Ok(())
+            }
+            Self::Error(error) => writeln!(f, "\nerror: {error}"),
+            Self::DeletedSubscription(name) => writeln!(f, "\nunsubscribed from: {name}"),
+            Self::DuplicateItems(groups) => {
+                writeln!(f)?;
+                for group in groups {
+                    let names: Vec<String> = group
+                        .iter()
+                        .map(|name| format!("{:?}", name.as_str()))
+                        .collect();
+                    writeln!(f, "{}", names.join(", "))?;
+                }
+                Ok(())
+            }
+            Self::Dump(dump) => writeln!(f, "{}", dump.to_json()),
             Self::Exported(items, list) => {
                 writeln!(f, "\nexported items:")?;
                 for item in items {
@@ -162,6 +426,11 @@ impl Display for ApiResponse {
                 }
                 Ok(())
             }
+            Self::ExportedChecklist(checklist) => writeln!(f, "{checklist}"),
+            Self::ExportedList(list) => write!(f, "{list}"),
+            Self::ExportedRecipe(recipe) => write!(f, "{recipe}"),
+            Self::Id(Some(id)) => writeln!(f, "{id}"),
+            Self::Id(None) => writeln!(f, "\nnot found"),
             Self::FetchedRecipe((recipe, ingredients)) => {
                 writeln!(f, "\n{recipe}:")?;
                 for ingredient in ingredients.iter() {
@@ -170,6 +439,17 @@ impl Display for ApiResponse {
                 Ok(())
             }
             Self::ItemAlreadyAdded(item) => writeln!(f, "\nitem already added: {item}"),
+            Self::ItemDetail(detail) => writeln!(f, "\n{detail}"),
+            Self::ItemNotFound { query, suggestions } => {
+                writeln!(f, "\nno item called {query}")?;
+                if !suggestions.is_empty() {
+                    writeln!(f, "did you mean:")?;
+                    for suggestion in suggestions {
+                        writeln!(f, "  {suggestion}")?;
+                    }
+                }
+                Ok(())
+            }
             Self::Items(items) => {
                 writeln!(f)?;
                 for item in items.collection_iter() {
@@ -177,7 +457,67 @@ impl Display for ApiResponse {
                 }
                 Ok(())
             }
-            Self::ImportToSqlite => writeln!(f, "\nImport successful"),
+            Self::ItemsWithSections(items) => {
+                writeln!(f)?;
+                for (item, section) in items {
+                    match section {
+                        Some(section) => writeln!(f, "{item}: {section}")?,
+                        None => writeln!(f, "{item}")?,
+                    }
+                }
+                Ok(())
+            }
+            Self::ImportedItemsCsv(report) => {
+                writeln!(f, "\nitems imported: {}", report.imported())?;
+                if !report.malformed_rows().is_empty() {
+                    writeln!(f, "malformed rows:")?;
+                    for row in report.malformed_rows() {
+                        writeln!(f, "  {row}")?;
+                    }
+                }
+                Ok(())
+            }
+            Self::ImportedRecipes(recipes) => {
+                writeln!(f, "\nimported recipes:")?;
+                for recipe in recipes {
+                    writeln!(f, "{recipe}")?;
+                }
+                Ok(())
+            }
+            Self::ImportedChecklist(names) => {
+                writeln!(f, "\nimported checklist:")?;
+                for name in names {
+                    writeln!(f, "  {name}")?;
+                }
+                Ok(())
+            }
+            Self::ImportedShareCode(list) => {
+                writeln!(f, "\nimported list:")?;
+                for item in list.items() {
+                    writeln!(f, "  {item}")?;
+                }
+                Ok(())
+            }
+            Self::FrequentlyListed(items) => {
+                writeln!(f)?;
+                for (item, times) in items {
+                    writeln!(f, "{item} ({times})")?;
+                }
+                Ok(())
+            }
+            Self::IngredientHistogram(histogram) => {
+                writeln!(f)?;
+                for (ingredients, recipes) in histogram {
+                    writeln!(f, "{ingredients} ingredients: {recipes} recipes")?;
+                }
+                Ok(())
+            }
+            Self::ImportToSqlite(summary) => writeln!(f, "\nImport successful\n{summary}"),
+            Self::IsEmpty(true) => writeln!(
+                f,
+                "\nyour library is empty, import or add some items to get started"
+            ),
+            Self::IsEmpty(false) => writeln!(f, "\nyour library has items"),
             Self::List(list) => {
                 writeln!(f)?;
                 for item in list.items() {
@@ -185,7 +525,73 @@ impl Display for ApiResponse {
                 }
                 Ok(())
             }
+            Self::ListDiff(diff) => {
+                writeln!(f, "\nadded:")?;
+                for name in diff.added() {
+                    writeln!(f, "{name}")?;
+                }
+                writeln!(f, "\nremoved:")?;
+                for name in diff.removed() {
+                    writeln!(f, "{name}")?;
+                }
+                Ok(())
+            }
+            Self::ListRecipeStatus(statuses) => {
+                writeln!(f)?;
+                for status in statuses {
+                    writeln!(f, "{status}")?;
+                }
+                Ok(())
+            }
+            Self::MissingFromLibrary(ids) if ids.is_empty() => {
+                writeln!(f, "\nevery item on the list is in the library")
+            }
+            Self::MissingFromLibrary(ids) => {
+                writeln!(f, "\nlist ids missing from the library:")?;
+                for id in ids {
+                    writeln!(f, "{id}")?;
+                }
+                Ok(())
+            }
+            Self::MergedRecipes(keep, remove) => writeln!(f, "\nmerged {remove} into {keep}"),
+            Self::NormalizedUnit(from, to, updated) => {
+                writeln!(f, "\n{from} is now {to} in {updated} recipe ingredients")
+            }
+            Self::MigrationStatus { applied, pending } => {
+                writeln!(f, "\napplied migrations:")?;
+                for version in applied {
+                    writeln!(f, "  {version}")?;
+                }
+                writeln!(f, "pending migrations:")?;
+                for version in pending {
+                    writeln!(f, "  {version}")?;
+                }
+                Ok(())
+            }
             Self::NothingReturned(cmd) => writeln!(f, "\nnothing returned for command: {cmd:?}."),
+            Self::CommonListIngredients(names) => {
+                writeln!(f, "\nin every recipe on the list:")?;
+                for name in names {
+                    writeln!(f, "{name}")?;
+                }
+                Ok(())
+            }
+            Self::CompletedTrip(id) => writeln!(f, "\ncompleted trip {id}, list is now empty"),
+            Self::Overlap(names) => {
+                writeln!(f, "\non both the list and the checklist:")?;
+                for name in names {
+                    writeln!(f, "{name}")?;
+                }
+                Ok(())
+            }
+            Self::RecipePreviews(recipes) => {
+                writeln!(f)?;
+                for (recipe, preview) in recipes {
+                    let preview: Vec<&str> = preview.iter().map(Name::as_str).collect();
+                    writeln!(f, "{recipe}: {}", preview.join(", "))?;
+                }
+                Ok(())
+            }
             Self::Recipes(recipes) => {
                 writeln!(f)?;
                 for recipe in recipes {
@@ -193,6 +599,24 @@ impl Display for ApiResponse {
                 }
                 Ok(())
             }
+            Self::RecipeCoverage(coverage) => {
+                writeln!(f)?;
+                for (recipe, have, total) in coverage {
+                    writeln!(f, "{recipe}: {have}/{total} on the list")?;
+                }
+                Ok(())
+            }
+            Self::RecipeIngredientCount(recipe, count) => {
+                writeln!(f, "\n{recipe}: {count} ingredients")
+            }
+            Self::RecipeShoppingStatus(ingredients) => {
+                writeln!(f)?;
+                for (ingredient, on_list) in ingredients {
+                    let mark = if *on_list { "x" } else { " " };
+                    writeln!(f, "[{mark}] {ingredient}")?;
+                }
+                Ok(())
+            }
             Self::RecipeIngredients(ingredients) => {
                 if let Some(ingredients) = ingredients {
                     writeln!(f)?;
@@ -204,6 +628,16 @@ impl Display for ApiResponse {
             }
 
             Self::RefreshList => writeln!(f, "\nList is now empty"),
+            Self::RemovedListRecipe(recipe) => writeln!(f, "\nremoved from list: {recipe}"),
+            Self::RenamedItem(item, new_name) => writeln!(f, "\nrenamed {item} to {new_name}"),
+            Self::RenamedRecipe(recipe, new_name) => {
+                writeln!(f, "\nrenamed recipe {recipe} to {new_name}")
+            }
+            Self::RenamedSection(section, new_name) => {
+                writeln!(f, "\nrenamed section {section} to {new_name}")
+            }
+            Self::Restored => writeln!(f, "\ndatabase restored"),
+            Self::RestoredRecipe(recipe) => writeln!(f, "\nrestored recipe: \n{recipe}"),
             Self::Sections(sections) => {
                 writeln!(f)?;
                 for section in sections {
@@ -211,6 +645,57 @@ impl Display for ApiResponse {
                 }
                 Ok(())
             }
+            Self::Schema(schema) => match serde_json::to_string_pretty(schema) {
+                Ok(schema) => writeln!(f, "{schema}"),
+                Err(_) => Err(fmt::Error),
+            },
+            Self::ShareCode(code) => writeln!(f, "\nshare code:\n{code}"),
+            Self::ShoppingOrder(order) => write!(f, "\n{order}"),
+            Self::Stats(stats) => writeln!(f, "\n{stats}"),
+            Self::SubscribedSection(section, staples) => {
+                writeln!(f, "\nsubscribed to {} items in {section}:", staples.len())?;
+                for item in staples {
+                    writeln!(f, "{item}")?;
+                }
+                Ok(())
+            }
+            Self::SuggestedSection(item, Some(section)) => {
+                writeln!(f, "\nsuggested section for {item}: {section}")
+            }
+            Self::SuggestedSection(item, None) => {
+                writeln!(f, "\nno section to suggest for {item}")
+            }
+            Self::Subscriptions(items) => {
+                writeln!(f)?;
+                for item in items {
+                    writeln!(f, "{item}")?;
+                }
+                Ok(())
+            }
+            Self::SubstitutedIngredient(from, to) => {
+                writeln!(f, "\nrecipes now use {to} instead of {from}")
+            }
+            Self::ToggledListItem(name, true) => writeln!(f, "\nitem added to list: {name}"),
+            Self::ToggledListItem(name, false) => {
+                writeln!(f, "\nitem removed from list: {name}")
+            }
+            Self::Trips(trips) => {
+                writeln!(f)?;
+                for trip in trips {
+                    write!(f, "{trip}")?;
+                }
+                Ok(())
+            }
+            Self::Units(units) => {
+                writeln!(f)?;
+                for unit in units {
+                    writeln!(f, "{unit}")?;
+                }
+                Ok(())
+            }
+            Self::UpdatedIngredient(recipe, item) => {
+                writeln!(f, "\n{item} updated in {recipe}")
+            }
         }
     }
 }
@@ -222,31 +707,205 @@ impl From<StoreResponse> for ApiResponse {
             StoreResponse::AddedItem(item) => Self::AddedItem(item),
             StoreResponse::AddedListItem(item) => Self::AddedListItem(item),
             StoreResponse::AddedListRecipe(item) => Self::AddedListRecipe(item),
-            StoreResponse::AddedRecipe(item) => Self::AddedRecipe(item),
+            StoreResponse::AddedRecipe(recipe, report) => Self::AddedRecipe(recipe, report),
+            StoreResponse::AddedSubscription(item) => Self::AddedSubscription(item),
+            StoreResponse::AppliedSectionMap(report) => Self::AppliedSectionMap(report),
             StoreResponse::Checklist(item) => Self::Checklist(item),
+            StoreResponse::ClearedChecklist => Self::ClearedChecklist,
+            StoreResponse::ClearedList => Self::ClearedList,
+            StoreResponse::CommonListIngredients(names) => Self::CommonListIngredients(names),
+            StoreResponse::CompletedTrip(id) => Self::CompletedTrip(id),
             StoreResponse::DeletedRecipe(item) => Self::DeletedRecipe(item),
+            StoreResponse::DeletedSections(sections) => Self::DeletedSections(sections),
+            StoreResponse::DeletedSubscription(item) => Self::DeletedSubscription(item),
             StoreResponse::DeletedChecklistItem(item) => Self::DeletedChecklistItem(item),
+            StoreResponse::DeletedItem(item) => Self::DeletedItem(item),
+            StoreResponse::DeletedListItem(item) => Self::DeletedListItem(item),
+            StoreResponse::DuplicateItems(groups) => Self::DuplicateItems(groups),
+            StoreResponse::Dump(dump) => Self::Dump(dump),
             StoreResponse::Exported(items, list) => Self::Exported(items, list),
+            StoreResponse::ExportedChecklist(checklist) => Self::ExportedChecklist(checklist),
+            StoreResponse::ExportedList(list) => Self::ExportedList(list),
+            StoreResponse::ExportedRecipe(recipe) => Self::ExportedRecipe(recipe),
+            StoreResponse::Id(id) => Self::Id(id),
             StoreResponse::FetchedRecipe(item) => Self::FetchedRecipe(item),
+            StoreResponse::FrequentlyListed(items) => Self::FrequentlyListed(items),
             StoreResponse::ItemAlreadyAdded(item) => Self::ItemAlreadyAdded(item),
+            StoreResponse::ItemDetail(detail) => Self::ItemDetail(detail),
+            StoreResponse::ItemNotFound { query, suggestions } => {
+                Self::ItemNotFound { query, suggestions }
+            }
             StoreResponse::Items(item) => Self::Items(item),
-            StoreResponse::ImportToSqlite => Self::ImportToSqlite,
+            StoreResponse::ItemsWithSections(items) => Self::ItemsWithSections(items),
+            StoreResponse::ImportedChecklist(names) => Self::ImportedChecklist(names),
+            StoreResponse::ImportedItemsCsv(report) => Self::ImportedItemsCsv(report),
+            StoreResponse::ImportedRecipes(recipes) => Self::ImportedRecipes(recipes),
+            StoreResponse::ImportedShareCode(list) => Self::ImportedShareCode(list),
+            StoreResponse::ImportToSqlite(summary) => Self::ImportToSqlite(summary),
+            StoreResponse::IngredientHistogram(histogram) => Self::IngredientHistogram(histogram),
+            StoreResponse::IsEmpty(empty) => Self::IsEmpty(empty),
             StoreResponse::List(item) => Self::List(item),
+            StoreResponse::ListDiff(diff) => Self::ListDiff(diff),
+            StoreResponse::ListRecipeStatus(item) => Self::ListRecipeStatus(item),
+            StoreResponse::MissingFromLibrary(ids) => Self::MissingFromLibrary(ids),
+            StoreResponse::MergedRecipes(keep, remove) => Self::MergedRecipes(keep, remove),
+            StoreResponse::NormalizedUnit(from, to, updated) => {
+                Self::NormalizedUnit(from, to, updated)
+            }
+            StoreResponse::MigrationStatus { applied, pending } => {
+                Self::MigrationStatus { applied, pending }
+            }
             StoreResponse::NothingReturned(item) => Self::NothingReturned(item),
+            StoreResponse::Overlap(names) => Self::Overlap(names),
+            StoreResponse::RecipePreviews(recipes) => Self::RecipePreviews(recipes),
             StoreResponse::Recipes(item) => Self::Recipes(item),
             StoreResponse::RecipeIngredients(item) => Self::RecipeIngredients(item),
+            StoreResponse::RecipeCoverage(coverage) => Self::RecipeCoverage(coverage),
+            StoreResponse::RecipeIngredientCount(recipe, count) => {
+                Self::RecipeIngredientCount(recipe, count)
+            }
+            StoreResponse::RecipeShoppingStatus(ingredients) => {
+                Self::RecipeShoppingStatus(ingredients)
+            }
             StoreResponse::RefreshList => Self::RefreshList,
+            StoreResponse::RemovedListRecipe(recipe) => Self::RemovedListRecipe(recipe),
+            StoreResponse::RenamedItem(item, new_name) => Self::RenamedItem(item, new_name),
+            StoreResponse::RenamedRecipe(recipe, new_name) => Self::RenamedRecipe(recipe, new_name),
+            StoreResponse::RenamedSection(section, new_name) => {
+                Self::RenamedSection(section, new_name)
+            }
+            StoreResponse::Restored => Self::Restored,
+            StoreResponse::RestoredRecipe(recipe) => Self::RestoredRecipe(recipe),
             StoreResponse::Sections(item) => Self::Sections(item),
+            StoreResponse::SuggestedSection(item, section) => Self::SuggestedSection(item, section),
+            StoreResponse::Subscriptions(items) => Self::Subscriptions(items),
+            StoreResponse::ShareCode(code) => Self::ShareCode(code),
+            StoreResponse::ShoppingOrder(order) => Self::ShoppingOrder(order),
+            StoreResponse::Stats(stats) => Self::Stats(stats),
+            StoreResponse::SubscribedSection(section, staples) => {
+                Self::SubscribedSection(section, staples)
+            }
+            StoreResponse::SubstitutedIngredient(from, to) => Self::SubstitutedIngredient(from, to),
+            StoreResponse::ToggledListItem(name, on_list) => Self::ToggledListItem(name, on_list),
+            StoreResponse::Trips(trips) => Self::Trips(trips),
+            StoreResponse::Units(units) => Self::Units(units),
+            StoreResponse::UpdatedIngredient(recipe, item) => Self::UpdatedIngredient(recipe, item),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use common::commands::{Add, Delete, Read};
+    use common::commands::{Add, Delete, FetchOptions, Read, Update};
 
     use super::*;
 
+    #[test]
+    fn validate_https_recipe_url() {
+        let url =
+            Url::parse("https://www.bbc.co.uk/food/recipes/scrambledeggandtoast_75736").unwrap();
+        assert!(validate_recipe_url(&url).is_ok());
+    }
+
+    #[tokio::test]
+    async fn fetch_recipe_rejects_file_url() {
+        let api = Api::init(StoreType::SqliteInMem).await.unwrap();
+
+        let url = Url::parse("file:///etc/passwd").unwrap();
+        let result = api
+            .dispatch(ApiCommand::FetchRecipe {
+                url,
+                default_section: None,
+                options: FetchOptions::default(),
+            })
+            .await;
+
+        assert!(matches!(result, Err(ApiError::InvalidUrl(_))));
+    }
+
+    #[tokio::test]
+    async fn try_execute_turns_errors_into_responses() {
+        let api = Api::init(StoreType::SqliteInMem).await.unwrap();
+
+        let response = api
+            .try_execute(ApiCommand::FetchRecipe {
+                url: Url::parse("file:///etc/passwd").unwrap(),
+                default_section: None,
+                options: FetchOptions::default(),
+            })
+            .await;
+        insta::assert_display_snapshot!(response.to_string().trim(), @"error: invalid recipe URL: file:///etc/passwd");
+
+        let response = api
+            .try_execute(ApiCommand::Update(Update::recipe_from_name(Recipe::from(
+                "pancakes",
+            ))))
+            .await;
+        insta::assert_display_snapshot!(response.to_string().trim(), @"error: store error: not supported yet: editing a recipe's ingredients");
+
+        let response = api
+            .try_execute(ApiCommand::Delete(Delete::item_from_name(Name::from(
+                "eggs",
+            ))))
+            .await;
+        insta::assert_display_snapshot!(response.to_string().trim(), @"error: store error: item not found: eggs");
+
+        let response = api
+            .try_execute(ApiCommand::Add(Add::item_from_name_and_section(
+                Name::from("eggs"),
+                None,
+            )))
+            .await;
+        assert!(matches!(response, ApiResponse::AddedItem(_)));
+    }
+
+    #[tokio::test]
+    async fn execute_stream_matches_dispatch() {
+        use futures::StreamExt;
+
+        let api = Api::init(StoreType::SqliteInMem).await.unwrap();
+        for name in ["eggs", "milk"] {
+            api.dispatch(ApiCommand::Add(Add::item_from_name_and_section(
+                Name::from(name),
+                None,
+            )))
+            .await
+            .unwrap();
+        }
+
+        let ApiResponse::Items(items) = api.dispatch(ApiCommand::Read(Read::All)).await.unwrap()
+        else {
+            panic!("expected items");
+        };
+        let streamed = api
+            .execute_stream(ApiCommand::Read(Read::All))
+            .await
+            .map(|chunk| match chunk.unwrap() {
+                ApiResponseChunk::Item(item) => item,
+                chunk => panic!("expected an item, got {chunk:?}"),
+            })
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(streamed, items.collection());
+
+        let streamed = api
+            .execute_stream(ApiCommand::Read(Read::Recipes))
+            .await
+            .collect::<Vec<_>>()
+            .await;
+        assert!(streamed.is_empty());
+
+        let chunks = api
+            .execute_stream(ApiCommand::Read(Read::List))
+            .await
+            .collect::<Vec<_>>()
+            .await;
+        assert!(matches!(
+            &chunks[..],
+            [Ok(ApiResponseChunk::Response(response))] if matches!(**response, ApiResponse::List(_))
+        ));
+    }
+
     #[tokio::test]
     async fn serve_api() {
         let api = Api::init(StoreType::SqliteInMem).await.unwrap();
@@ -257,8 +916,17 @@ mod tests {
             .unwrap();
 
         insta::assert_display_snapshot!(response.to_string().trim(), @r###"
-
         recipe added: fluffy american pancakes
+        new items:
+          135g/4¾oz plain flour
+          1 tsp baking powder
+          ½ tsp salt
+          2 tbsp caster sugar
+          130ml/4½fl oz milk
+          1 large egg
+          lightly beaten
+          2 tbsp melted butter (allowed to cool slightly)
+          plus extra for cooking
         "###);
 
         let response = api.dispatch(ApiCommand::Read(Read::Recipes)).await.unwrap();
@@ -274,15 +942,15 @@ mod tests {
 
         insta::assert_display_snapshot!(response.to_string().trim(), @r###"
 
-        135g/4¾oz plain flour
+        1 large egg
         1 tsp baking powder
-        ½ tsp salt
-        2 tbsp caster sugar
         130ml/4½fl oz milk
-        1 large egg
-        lightly beaten
+        135g/4¾oz plain flour
+        2 tbsp caster sugar
         2 tbsp melted butter (allowed to cool slightly)
+        lightly beaten
         plus extra for cooking
+        ½ tsp salt
         "###);
 
         let response = api.dispatch(ApiCommand::Read(Read::All)).await.unwrap();
@@ -315,8 +983,26 @@ mod tests {
 
         insta::assert_display_snapshot!(response.to_string().trim(), @"");
 
+        let response = api
+            .dispatch(ApiCommand::Read(Read::DeletedRecipes))
+            .await
+            .unwrap();
+
+        insta::assert_display_snapshot!(response.to_string().trim(), @"fluffy american pancakes");
+
+        // Deleted recipes keep their ingredients in the library so they can be restored
         let response = api.dispatch(ApiCommand::Read(Read::All)).await.unwrap();
 
-        insta::assert_display_snapshot!(response.to_string().trim(), @"");
+        insta::assert_display_snapshot!(response.to_string().trim(), @r###"
+        135g/4¾oz plain flour
+        1 tsp baking powder
+        ½ tsp salt
+        2 tbsp caster sugar
+        130ml/4½fl oz milk
+        1 large egg
+        lightly beaten
+        2 tbsp melted butter (allowed to cool slightly)
+        plus extra for cooking
+        "###);
     }
 }
@@ -0,0 +1,52 @@
+//! A JSON Schema describing the serialized shape of [`ApiResponse`].
+//!
+//! The schema is derived from the same types serde serializes, so it follows
+//! them as they change. Responses use serde's default externally tagged enum
+//! representation: unit variants are plain strings and every other variant is
+//! an object with the variant's name as its only key.
+
+use schemars::schema_for;
+use serde_json::Value;
+
+use crate::ApiResponse;
+
+/// Builds the JSON Schema for `ApiResponse`.
+pub fn response_schema() -> Value {
+    serde_json::to_value(schema_for!(ApiResponse)).expect("a schema serializes to JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use common::list::List;
+
+    use super::*;
+
+    #[test]
+    fn test_response_schema_defines_list_and_items() {
+        let schema = response_schema();
+        let definitions = &schema["definitions"];
+
+        assert_eq!(definitions["Items"]["type"], "array");
+        assert_eq!(definitions["Items"]["items"]["$ref"], "#/definitions/Item");
+
+        let list = serde_json::to_value(List::new()).unwrap();
+        let mut fields: Vec<&String> = list.as_object().unwrap().keys().collect();
+        fields.sort();
+        let mut properties: Vec<&String> = definitions["List"]["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect();
+        properties.sort();
+        assert_eq!(fields, properties);
+
+        let variants: Vec<&str> = schema["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|variant| variant["required"][0].as_str())
+            .collect();
+        assert!(variants.contains(&"List"));
+        assert!(variants.contains(&"Items"));
+    }
+}
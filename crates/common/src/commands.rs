@@ -1,35 +1,81 @@
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
+use serde::Serialize;
 use url::Url;
 
 use crate::{
+    export::Format,
     item::Name,
-    recipes::{Ingredients, Recipe},
+    list::TripId,
+    recipes::{Ingredients, Recipe, RecipeSort},
     section::Section,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub enum ApiCommand {
     Add(Add),
+    ApplySectionMap(PathBuf),
     Delete(Delete),
+    Dump,
     Export,
-    FetchRecipe(Url),
-    ImportFromJson,
+    ExportChecklist,
+    ExportList(Format),
+    ExportRecipe(Recipe),
+    ExportShareCode,
+    FetchRecipe {
+        url: Url,
+        default_section: Option<Section>,
+        options: FetchOptions,
+    },
+    ImportChecklist(PathBuf),
+    ImportFromJson {
+        fresh: bool,
+    },
+    ImportItemsCsv(PathBuf),
+    ImportPaprika(PathBuf),
+    ImportShareCode(String),
+    MigrationStatus,
     Read(Read),
+    Restore(PathBuf),
+    Schema,
     Update(Update),
 }
 
-#[derive(Debug)]
+/// How a recipe fetch treats the cache, empty pages and new ingredients.
+#[derive(Debug, Default, Clone, Copy, Serialize, JsonSchema)]
+pub struct FetchOptions {
+    /// Fetches the page even if the URL was fetched recently or a recipe in
+    /// the library came from it.
+    pub force: bool,
+    /// Accepts a page without any ingredients.
+    pub allow_empty: bool,
+    /// Puts each ingredient new to the library in its suggested section.
+    pub auto_section: bool,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub enum Add {
     ChecklistItem(Name),
+    ExistingChecklistItem(Name),
     Item {
         name: Name,
         section: Option<Section>,
     },
     ListItem(Name),
-    ListRecipe(Recipe),
+    ListRecipe {
+        recipe: Recipe,
+        refresh: bool,
+    },
     Recipe {
         recipe: Recipe,
         ingredients: Ingredients,
     },
+    RecipeFromText {
+        recipe: Recipe,
+        text: String,
+    },
+    Subscription(Name),
 }
 
 impl Add {
@@ -37,6 +83,10 @@ impl Add {
         Self::ChecklistItem(name)
     }
 
+    pub fn existing_checklist_item_from_name(name: Name) -> Self {
+        Self::ExistingChecklistItem(name)
+    }
+
     pub fn item_from_name_and_section(name: Name, section: Option<Section>) -> Self {
         Self::Item { name, section }
     }
@@ -45,8 +95,8 @@ impl Add {
         Self::ListItem(name)
     }
 
-    pub fn list_recipe_from_name(name: Recipe) -> Self {
-        Self::ListRecipe(name)
+    pub fn list_recipe_from_name(recipe: Recipe, refresh: bool) -> Self {
+        Self::ListRecipe { recipe, refresh }
     }
 
     pub fn recipe_from_name_and_ingredients(recipe: Recipe, ingredients: Ingredients) -> Self {
@@ -55,16 +105,30 @@ impl Add {
             ingredients,
         }
     }
+
+    pub fn recipe_from_name_and_text(recipe: Recipe, text: impl Into<String>) -> Self {
+        Self::RecipeFromText {
+            recipe,
+            text: text.into(),
+        }
+    }
+
+    pub fn subscription_from_name(name: Name) -> Self {
+        Self::Subscription(name)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub enum Delete {
     ChecklistItem(Name),
     ClearChecklist,
     ClearList,
+    EmptySections,
     Item(Name),
     ListItem(Name),
+    ListRecipe { recipe: Recipe, with_items: bool },
     Recipe(Recipe),
+    Subscription(Name),
 }
 
 impl Delete {
@@ -77,16 +141,62 @@ impl Delete {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub enum Read {
     All,
+    AvailableRecipes,
     Checklist,
+    CombinedIngredients(Vec<Recipe>),
+    CommonListIngredients,
+    DeletedRecipes,
+    DuplicateItems,
+    EmptyRecipes,
+    EmptySections,
+    FrequentlyListed(usize),
+    IngredientHistogram,
+    IsEmpty,
     Item(Name),
+    ItemDetail(Name),
+    ItemId(Name),
+    ItemsByPrefix(String),
+    ItemsWithSections,
     List,
+    ListDiff(PathBuf),
+    ListItemsFromRecipe(Recipe),
+    ListItemsOnly,
+    ListMissingFromLibrary,
     ListRecipes,
+    ListRecipeStatus,
+    Overlap,
+    RecentFetches(usize),
     Recipe(Recipe),
+    RecipeAlreadyPlanned(Recipe),
+    RecipeBySection(Recipe),
+    RecipeCoverage,
+    RecipeId(Recipe),
+    RecipeIngredientCount(Recipe),
+    RecipeStatus(Recipe),
     Recipes,
+    RefreshPreview {
+        subscriptions: bool,
+    },
+    /// With `preview`, each recipe comes with a few of its ingredients.
+    RecipesPage {
+        offset: usize,
+        limit: usize,
+        sort: RecipeSort,
+        preview: bool,
+    },
+    RecipesUsing(String),
+    Section(Section),
     Sections,
+    ShoppingOrder,
+    Stats,
+    SuggestSection(Name),
+    Subscriptions,
+    Trip(TripId),
+    Trips,
+    Units,
 }
 
 impl Read {
@@ -97,21 +207,90 @@ impl Read {
     pub fn recipe_from_name(name: Recipe) -> Self {
         Self::Recipe(name)
     }
+
+    pub fn items_in_section(section: Section) -> Self {
+        Self::Section(section)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, JsonSchema)]
 pub enum Update {
-    Item(Name),
-    RefreshList,
+    CompleteTrip,
+    IngredientAmount {
+        recipe: Recipe,
+        item: Name,
+        quantity: i32,
+        unit: Option<String>,
+    },
+    ListAllItems,
+    RenameItem {
+        item: Name,
+        new_name: Name,
+    },
+    RenameItemStrict {
+        item: Name,
+        new_name: Name,
+    },
+    RefreshList {
+        subscriptions: bool,
+    },
+    RefreshKeeping(Vec<Name>),
+    MergeRecipes {
+        keep: Recipe,
+        remove: Recipe,
+    },
+    NormalizeUnit {
+        from: String,
+        to: String,
+    },
     Recipe(Recipe),
+    RefetchRecipe {
+        recipe: Recipe,
+        url: Url,
+    },
+    RenameRecipe {
+        recipe: Recipe,
+        new_name: Recipe,
+    },
+    RenameSection {
+        section: Section,
+        new_name: Section,
+    },
+    RestoreRecipe(Recipe),
+    RestoreTrip(TripId),
+    SetList(Vec<Name>),
+    StapleSection(Section),
+    Substitute {
+        from: Name,
+        to: Name,
+    },
+    ToggleListItem(Name),
 }
 
 impl Update {
-    pub fn refresh_list() -> Self {
-        Self::RefreshList
+    pub fn rename_item(item: Name, new_name: Name) -> Self {
+        Self::RenameItem { item, new_name }
+    }
+
+    /// Empties the list, putting subscribed items back on it if
+    /// `subscriptions` is set.
+    pub fn refresh_list(subscriptions: bool) -> Self {
+        Self::RefreshList { subscriptions }
     }
 
     pub fn recipe_from_name(name: Recipe) -> Self {
         Self::Recipe(name)
     }
+
+    pub fn rename_recipe(recipe: Recipe, new_name: Recipe) -> Self {
+        Self::RenameRecipe { recipe, new_name }
+    }
+
+    pub fn rename_section(section: Section, new_name: Section) -> Self {
+        Self::RenameSection { section, new_name }
+    }
+
+    pub fn restore_recipe_from_name(name: Recipe) -> Self {
+        Self::RestoreRecipe(name)
+    }
 }
@@ -1,6 +1,7 @@
 use std::{fs::File, path::Path};
 
-use serde::Serialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{item::Item, list::List};
@@ -8,6 +9,18 @@ use crate::{item::Item, list::List};
 pub const ITEMS_YAML_PATH: &str = "items.yaml";
 pub const LIST_YAML_PATH: &str = "list.yaml";
 
+/// Heading for items without a section when grouping the list by section.
+pub const UNCATEGORIZED_LABEL: &str = "other";
+
+/// Alternative formats for exporting the list.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub enum Format {
+    /// Item names only, one per line
+    Plain,
+    /// Item names grouped under their sections
+    BySection { uncategorized_label: String },
+}
+
 #[derive(Error, Debug)]
 pub enum ExportError {
     #[error("file error: {0}")]
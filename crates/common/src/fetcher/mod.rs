@@ -1,5 +1,15 @@
+pub mod paprika;
+
+#[cfg(feature = "fetch-limiter")]
+use std::{
+    future::Future,
+    sync::{Arc, OnceLock},
+};
+
 use scraper::{Html, Selector};
 use thiserror::Error;
+#[cfg(feature = "fetch-limiter")]
+use tokio::sync::Semaphore;
 use url::Url;
 
 use crate::recipes::{Ingredients, Recipe};
@@ -8,15 +18,73 @@ use crate::recipes::{Ingredients, Recipe};
 pub enum FetchError {
     #[error("CSS selector failed to select anything")]
     CSS,
+    #[error("no ingredients found for recipe '{0}'")]
+    NoIngredients(Recipe),
     #[error("reqwest error: {0}")]
     Reqwest(#[from] reqwest::Error),
     #[error("Selector Error: {0}")]
     SelectorError(String),
 }
 
+/// How many fetches may be in flight at once by default.
+#[cfg(feature = "fetch-limiter")]
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 4;
+
+/// Bounds how many fetches run at the same time, so a burst of requests
+/// doesn't hammer a recipe site. Clones share the same bound.
+#[cfg(feature = "fetch-limiter")]
+#[derive(Clone, Debug)]
+pub struct FetchLimiter {
+    semaphore: Arc<Semaphore>,
+    max_in_flight: usize,
+}
+
+#[cfg(feature = "fetch-limiter")]
+impl FetchLimiter {
+    pub fn new(max_in_flight: usize) -> Self {
+        let max_in_flight = max_in_flight.max(1);
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_in_flight)),
+            max_in_flight,
+        }
+    }
+
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight
+    }
+
+    /// The limiter fetchers use unless given another one.
+    pub fn shared() -> Self {
+        static SHARED: OnceLock<FetchLimiter> = OnceLock::new();
+        SHARED
+            .get_or_init(|| Self::new(DEFAULT_MAX_IN_FLIGHT))
+            .clone()
+    }
+
+    /// Runs `fetch` once a slot is free.
+    pub async fn run<F: Future>(&self, fetch: F) -> F::Output {
+        let _permit = self
+            .semaphore
+            .acquire()
+            .await
+            .expect("the semaphore is never closed");
+        fetch.await
+    }
+}
+
+#[cfg(feature = "fetch-limiter")]
+impl Default for FetchLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_IN_FLIGHT)
+    }
+}
+
 pub struct Fetcher {
     site: Site,
     url: Url,
+    #[cfg(feature = "fetch-limiter")]
+    limiter: FetchLimiter,
+    allow_empty: bool,
 }
 
 #[allow(dead_code, clippy::upper_case_acronyms)]
@@ -38,18 +106,49 @@ impl From<Url> for Fetcher {
 
 impl Fetcher {
     fn new(site: Site, url: Url) -> Self {
-        Self { site, url }
+        Self {
+            site,
+            url,
+            #[cfg(feature = "fetch-limiter")]
+            limiter: FetchLimiter::shared(),
+            allow_empty: false,
+        }
+    }
+
+    /// Accepts recipes without any ingredients instead of failing with
+    /// [`FetchError::NoIngredients`].
+    pub fn allow_empty(mut self, allow_empty: bool) -> Self {
+        self.allow_empty = allow_empty;
+        self
+    }
+
+    /// Waits for a slot in `limiter` instead of the shared one.
+    #[cfg(feature = "fetch-limiter")]
+    pub fn with_limiter(mut self, limiter: FetchLimiter) -> Self {
+        self.limiter = limiter;
+        self
     }
 
     pub async fn fetch_recipe(&self) -> Result<(Recipe, Ingredients), FetchError> {
+        #[cfg(feature = "fetch-limiter")]
+        let document = self.limiter.run(self.fetch_html()).await?;
+        #[cfg(not(feature = "fetch-limiter"))]
         let document = self.fetch_html().await?;
-        Ok((
-            self.fetch_recipe_name(&document)?.trim().into(),
-            self.fetch_recipe_ingredients(&document)?
-                .into_iter()
-                .map(|i| i.trim().into())
-                .collect(),
-        ))
+        self.parse_recipe(&document)
+    }
+
+    fn parse_recipe(&self, document: &Html) -> Result<(Recipe, Ingredients), FetchError> {
+        let recipe: Recipe = self.fetch_recipe_name(document)?.trim().into();
+        let ingredients: Ingredients = self
+            .fetch_recipe_ingredients(document)?
+            .into_iter()
+            .map(|i| i.trim().into())
+            .collect();
+        // An empty ingredient list usually means the site's markup changed
+        if ingredients.iter().next().is_none() && !self.allow_empty {
+            return Err(FetchError::NoIngredients(recipe));
+        }
+        Ok((recipe, ingredients))
     }
 
     async fn fetch_html(&self) -> Result<Html, reqwest::Error> {
@@ -98,9 +197,111 @@ impl Fetcher {
 
 #[cfg(test)]
 mod tests {
+    use scraper::Html;
     use url::Url;
 
-    use crate::fetcher::Fetcher;
+    use crate::fetcher::{FetchError, Fetcher};
+
+    const NO_INGREDIENTS: &str = r#"
+        <html><body>
+            <h1 class="gel-trafalgar">Mystery Stew</h1>
+            <ul class="recipe-ingredients__list"></ul>
+        </body></html>
+    "#;
+
+    #[test]
+    fn test_parse_recipe_without_ingredients() {
+        let document = Html::parse_document(NO_INGREDIENTS);
+
+        let fetcher: Fetcher = url().into();
+        assert!(matches!(
+            fetcher.parse_recipe(&document),
+            Err(FetchError::NoIngredients(recipe)) if recipe.as_str() == "mystery stew"
+        ));
+
+        let (recipe, ingredients) = Fetcher::from(url())
+            .allow_empty(true)
+            .parse_recipe(&document)
+            .unwrap();
+        assert_eq!(recipe.as_str(), "mystery stew");
+        assert!(ingredients.iter().next().is_none());
+    }
+
+    #[cfg(feature = "fetch-limiter")]
+    #[tokio::test]
+    async fn test_fetch_limiter_bounds_in_flight() {
+        use std::{
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc,
+            },
+            time::Duration,
+        };
+
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpListener,
+        };
+
+        use crate::fetcher::{FetchLimiter, Site};
+
+        const PAGE: &str = r#"
+            <html><body>
+                <h1 class="gel-trafalgar">Boiled Egg</h1>
+                <ul class="recipe-ingredients__list"><li>egg</li></ul>
+            </body></html>
+        "#;
+
+        // A fixture server that answers every request slowly, counting how
+        // many it's answering at once
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let server = {
+            let in_flight = Arc::clone(&in_flight);
+            let peak = Arc::clone(&peak);
+            tokio::spawn(async move {
+                loop {
+                    let (mut socket, _) = listener.accept().await.unwrap();
+                    let in_flight = Arc::clone(&in_flight);
+                    let peak = Arc::clone(&peak);
+                    tokio::spawn(async move {
+                        // A GET has no body, so the request ends with its headers
+                        let mut request = Vec::new();
+                        let mut buf = [0; 1024];
+                        while !request.ends_with(b"\r\n\r\n") {
+                            let read = socket.read(&mut buf).await.unwrap();
+                            request.extend_from_slice(&buf[..read]);
+                        }
+                        let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(20)).await;
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{PAGE}",
+                            PAGE.len()
+                        );
+                        socket.write_all(response.as_bytes()).await.unwrap();
+                    });
+                }
+            })
+        };
+
+        let limiter = FetchLimiter::new(2);
+        let fetches = (0..8).map(|_| {
+            let fetcher = Fetcher::new(Site::BBC, url.clone()).with_limiter(limiter.clone());
+            tokio::spawn(async move { fetcher.fetch_recipe().await })
+        });
+        for fetch in fetches.collect::<Vec<_>>() {
+            let (recipe, _) = fetch.await.unwrap().unwrap();
+            assert_eq!(recipe.as_str(), "boiled egg");
+        }
+        server.abort();
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(in_flight.load(Ordering::SeqCst), 0);
+    }
 
     fn url() -> Url {
         Url::parse("https://www.bbc.co.uk/food/recipes/scrambledeggandtoast_75736").unwrap()
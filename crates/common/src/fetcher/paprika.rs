@@ -0,0 +1,236 @@
+use serde::Deserialize;
+
+use crate::{item::Name, recipes::Recipe};
+
+/// Units read from the front of an ingredient line, after its quantity.
+const UNITS: &[&str] = &[
+    "bunch",
+    "c",
+    "can",
+    "clove",
+    "cup",
+    "g",
+    "gram",
+    "head",
+    "kg",
+    "l",
+    "lb",
+    "ml",
+    "oz",
+    "package",
+    "pinch",
+    "pound",
+    "slice",
+    "sprig",
+    "tbsp",
+    "teaspoon",
+    "tablespoon",
+    "tin",
+    "tsp",
+];
+
+/// The item an ingredient line names and how much of it the recipe needs.
+/// Quantities are whole numbers, so fractions round up: `1 1/2 cups flour`
+/// is 2 cups.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ingredient {
+    name: Name,
+    quantity: i32,
+    unit: Option<String>,
+}
+
+impl Ingredient {
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    pub fn quantity(&self) -> i32 {
+        self.quantity
+    }
+
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
+}
+
+/// A recipe as Paprika exports it. Only the fields used here are read; its
+/// ingredients are one newline-separated block of text.
+#[derive(Deserialize, Debug)]
+struct PaprikaRecipe {
+    name: String,
+    #[serde(default)]
+    ingredients: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum PaprikaExport {
+    Many(Vec<PaprikaRecipe>),
+    One(PaprikaRecipe),
+}
+
+/// Reads recipes from Paprika's JSON, either a single recipe or an array of
+/// them as found in an unpacked `.paprikarecipes` export.
+pub fn parse_export(json: &str) -> Result<Vec<(Recipe, Vec<Ingredient>)>, serde_json::Error> {
+    let recipes = match serde_json::from_str(json)? {
+        PaprikaExport::Many(recipes) => recipes,
+        PaprikaExport::One(recipe) => vec![recipe],
+    };
+    Ok(recipes
+        .into_iter()
+        .map(|recipe| {
+            let ingredients = recipe.ingredients.lines().filter_map(ingredient).collect();
+            (Recipe::from(recipe.name), ingredients)
+        })
+        .collect())
+}
+
+/// Reads an ingredient line like `2 tbsp olive oil, divided`, splitting off
+/// the quantity and unit and dropping any preparation notes after a comma.
+/// A line without a quantity needs one of the item. Blank lines and section
+/// headings such as `For the sauce:` give `None`.
+pub fn ingredient(line: &str) -> Option<Ingredient> {
+    let line = line.trim().to_lowercase();
+    if line.is_empty() || line.ends_with(':') {
+        return None;
+    }
+    let line = line.split(',').next().unwrap_or_default();
+
+    let mut words = line.split_whitespace().peekable();
+    let mut amount = 0.0;
+    while let Some(word) = words.next_if(|word| is_quantity(word)) {
+        amount += quantity(word);
+    }
+    let unit = words.peek().and_then(|word| unit(word));
+    if unit.is_some() {
+        words.next();
+    }
+    words.next_if_eq(&"of");
+
+    let name = words.collect::<Vec<_>>().join(" ");
+    (!name.is_empty()).then(|| Ingredient {
+        name: Name::from(name.as_str()),
+        quantity: (amount.ceil() as i32).max(1),
+        unit: unit.map(String::from),
+    })
+}
+
+fn is_quantity(word: &str) -> bool {
+    word.chars()
+        .all(|c| c.is_ascii_digit() || "/.-½⅓⅔¼¾⅛".contains(c))
+}
+
+/// The amount a quantity word stands for, taking the top of a range like
+/// `2-3`.
+fn quantity(word: &str) -> f64 {
+    let word = word.rsplit('-').next().unwrap_or(word);
+    let (number, fractions): (String, String) = word
+        .chars()
+        .partition(|c| c.is_ascii_digit() || "./".contains(*c));
+    let number = match number.split_once('/') {
+        Some((numerator, denominator)) => {
+            match (numerator.parse::<f64>(), denominator.parse::<f64>()) {
+                (Ok(numerator), Ok(denominator)) if denominator > 0.0 => numerator / denominator,
+                _ => 0.0,
+            }
+        }
+        None => number.parse().unwrap_or_default(),
+    };
+    number
+        + fractions
+            .chars()
+            .map(|c| match c {
+                '½' => 1.0 / 2.0,
+                '⅓' => 1.0 / 3.0,
+                '⅔' => 2.0 / 3.0,
+                '¼' => 1.0 / 4.0,
+                '¾' => 3.0 / 4.0,
+                '⅛' => 1.0 / 8.0,
+                _ => 0.0,
+            })
+            .sum::<f64>()
+}
+
+/// The unit a word names, as it's spelled in `UNITS`.
+fn unit(word: &str) -> Option<&'static str> {
+    let word = word.trim_end_matches('.');
+    [Some(word), word.strip_suffix('s'), word.strip_suffix("es")]
+        .into_iter()
+        .flatten()
+        .find_map(|word| UNITS.iter().copied().find(|unit| *unit == word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ingredient_lines() {
+        let ingredients = [
+            "2 eggs",
+            "1 1/2 cups plain flour",
+            "2 tbsp. olive oil, divided",
+            "½ tsp salt",
+            "3 cloves garlic",
+            "1 head lettuce",
+            "2-3 bunches coriander",
+            "pinch of nutmeg",
+            "For the sauce:",
+            "  ",
+        ]
+        .into_iter()
+        .map(|line| {
+            ingredient(line).map(|ingredient| {
+                (
+                    ingredient.name().to_string(),
+                    ingredient.quantity(),
+                    ingredient.unit().map(String::from),
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+        assert_eq!(
+            ingredients,
+            [
+                Some(("eggs", 2, None)),
+                Some(("plain flour", 2, Some("cup"))),
+                Some(("olive oil", 2, Some("tbsp"))),
+                Some(("salt", 1, Some("tsp"))),
+                Some(("garlic", 3, Some("clove"))),
+                Some(("lettuce", 1, Some("head"))),
+                Some(("coriander", 3, Some("bunch"))),
+                Some(("nutmeg", 1, Some("pinch"))),
+                None,
+                None,
+            ]
+            .map(|ingredient| {
+                ingredient.map(|(name, quantity, unit)| {
+                    (name.to_string(), quantity, unit.map(String::from))
+                })
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_export_single_recipe() {
+        let recipes =
+            parse_export(r#"{"name": "Toast", "ingredients": "2 slices bread\nbutter"}"#).unwrap();
+        assert_eq!(recipes.len(), 1);
+        assert_eq!(recipes[0].0, Recipe::from("toast"));
+        assert_eq!(
+            recipes[0].1,
+            vec![
+                Ingredient {
+                    name: Name::from("bread"),
+                    quantity: 2,
+                    unit: Some("slice".to_string()),
+                },
+                Ingredient {
+                    name: Name::from("butter"),
+                    quantity: 1,
+                    unit: None,
+                },
+            ]
+        );
+    }
+}
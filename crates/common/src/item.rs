@@ -1,3 +1,4 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -10,11 +11,14 @@ use crate::{recipes::Recipe, section::Section};
 /// * `name` - name of the item
 /// * `section` - section in which item is found ("fresh", "frozen", etc.)
 /// * `recipes` - list of recipes of which the item is an ingredient
-#[derive(Default, Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// * `quantity` - how many are needed, when the item is on the list
+#[derive(Default, Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct Item {
     name: Name,
     section: Option<Section>,
     recipes: Option<Vec<Recipe>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    quantity: Option<usize>,
 }
 
 impl Item {
@@ -37,6 +41,10 @@ impl Item {
         self.recipes.as_ref()
     }
 
+    pub fn quantity(&self) -> Option<usize> {
+        self.quantity
+    }
+
     pub fn delete_recipe(&mut self, name: &str) {
         if let Some(vec) = self.recipes.as_mut() {
             vec.retain(|x| x.as_str() != name)
@@ -52,11 +60,19 @@ impl Item {
         self.recipes = Some(recipes.to_vec());
         self
     }
+
+    pub fn with_quantity(mut self, quantity: usize) -> Self {
+        self.quantity = Some(quantity);
+        self
+    }
 }
 
 impl fmt::Display for Item {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.name)
+        match self.quantity {
+            Some(quantity) if quantity > 1 => write!(f, "{} (x{quantity})", self.name),
+            _ => write!(f, "{}", self.name),
+        }
     }
 }
 
@@ -66,7 +82,71 @@ impl From<&Name> for Item {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+/// What looking an item up by name found: the item, or the names of items
+/// the query may have meant.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ItemLookup {
+    Found(Item),
+    NotFound { query: Name, suggestions: Vec<Name> },
+}
+
+/// An item together with whether it's on the list and the checklist. The
+/// item carries its section and the recipes using it.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct ItemDetail {
+    item: Item,
+    on_list: bool,
+    on_checklist: bool,
+}
+
+impl ItemDetail {
+    pub fn new(item: Item, on_list: bool, on_checklist: bool) -> Self {
+        Self {
+            item,
+            on_list,
+            on_checklist,
+        }
+    }
+
+    pub fn item(&self) -> &Item {
+        &self.item
+    }
+
+    pub fn on_list(&self) -> bool {
+        self.on_list
+    }
+
+    pub fn on_checklist(&self) -> bool {
+        self.on_checklist
+    }
+}
+
+impl fmt::Display for ItemDetail {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.item.name)?;
+        match &self.item.section {
+            Some(section) => writeln!(f, "section: {section}")?,
+            None => writeln!(f, "section: none")?,
+        }
+        match self.item.recipes.as_deref() {
+            Some(recipes) if !recipes.is_empty() => {
+                writeln!(f, "recipes:")?;
+                for recipe in recipes {
+                    writeln!(f, "  {recipe}")?;
+                }
+            }
+            _ => writeln!(f, "recipes: none")?,
+        }
+        writeln!(f, "on list: {}", if self.on_list { "yes" } else { "no" })?;
+        write!(
+            f,
+            "on checklist: {}",
+            if self.on_checklist { "yes" } else { "no" }
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
 pub struct Name(String);
 
 impl std::fmt::Display for Name {
@@ -82,6 +162,12 @@ impl From<&str> for Name {
 }
 
 impl Name {
+    /// A name exactly as given, without trimming or lowercasing, e.g. one
+    /// stored before names were normalized.
+    pub fn new_unchecked(s: impl Into<String>) -> Self {
+        Self(s.into())
+    }
+
     pub fn as_str(&self) -> &str {
         &self.0
     }
@@ -1,7 +1,36 @@
-use crate::{item::Item, load::Load, recipes::Recipe};
+use std::{collections::HashSet, fmt};
+
+use crate::{
+    export::UNCATEGORIZED_LABEL,
+    item::{Item, Name},
+    load::Load,
+    recipes::Recipe,
+    section::Section,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Version prefix of share codes produced by [`List::to_share_code`].
+pub const SHARE_CODE_VERSION: &str = "v1";
+
+#[derive(Error, Debug)]
+pub enum ShareError {
+    #[error("invalid base64 in share code: {0}")]
+    Base64(#[from] base64::DecodeError),
+
+    #[error("share code is missing a version prefix")]
+    MissingVersion,
+
+    #[error("invalid list in share code: {0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+
+    #[error("unsupported share code version: {0}")]
+    UnsupportedVersion(String),
+}
 
-#[derive(Default, Serialize, Deserialize, Debug, Clone)]
+#[derive(Default, Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 pub struct List {
     checklist: Vec<Item>,
     recipes: Vec<Recipe>,
@@ -12,6 +41,104 @@ impl Load for List {
     type T = List;
 }
 
+pub type TripId = i32;
+
+/// A completed shopping trip: when it was finished and what was on the list.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct Trip {
+    id: TripId,
+    completed_at: String,
+    items: Vec<Name>,
+}
+
+impl Trip {
+    pub fn new(id: TripId, completed_at: impl Into<String>, items: Vec<Name>) -> Self {
+        Self {
+            id,
+            completed_at: completed_at.into(),
+            items,
+        }
+    }
+
+    pub fn id(&self) -> TripId {
+        self.id
+    }
+
+    pub fn completed_at(&self) -> &str {
+        &self.completed_at
+    }
+
+    pub fn items(&self) -> &[Name] {
+        &self.items
+    }
+}
+
+impl fmt::Display for Trip {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "trip {} ({}):", self.id, self.completed_at)?;
+        for item in &self.items {
+            writeln!(f, "  {item}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What changed on a list since an earlier snapshot of it, by item name.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct ListDiff {
+    added: Vec<Name>,
+    removed: Vec<Name>,
+}
+
+impl ListDiff {
+    /// Items on the list that weren't in the snapshot.
+    pub fn added(&self) -> &[Name] {
+        &self.added
+    }
+
+    /// Items in the snapshot that are no longer on the list.
+    pub fn removed(&self) -> &[Name] {
+        &self.removed
+    }
+}
+
+/// The list as it's walked through the store: a group of items per section,
+/// in section order, with unsectioned items last.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct ShoppingOrder {
+    sections: Vec<(Option<Section>, Vec<Item>)>,
+}
+
+impl ShoppingOrder {
+    pub fn new(sections: Vec<(Option<Section>, Vec<Item>)>) -> Self {
+        Self { sections }
+    }
+
+    pub fn sections(&self) -> &[(Option<Section>, Vec<Item>)] {
+        &self.sections
+    }
+}
+
+impl fmt::Display for ShoppingOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (section, items) in &self.sections {
+            match section {
+                Some(section) => writeln!(f, "{section}:")?,
+                None => writeln!(f, "{UNCATEGORIZED_LABEL}:")?,
+            }
+            for item in items {
+                write!(f, "  {item}")?;
+                if let Some(recipes) = item.recipes() {
+                    let recipes: Vec<&str> = recipes.iter().map(Recipe::as_str).collect();
+                    write!(f, " [{}]", recipes.join(", "))?;
+                }
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl FromIterator<Item> for List {
     fn from_iter<I: IntoIterator<Item = Item>>(iter: I) -> Self {
         let mut c = List::new();
@@ -28,12 +155,17 @@ impl List {
         Self::default()
     }
 
-    pub fn with_checklist(mut self, checklist: Vec<Item>) -> Self {
+    pub fn with_items(mut self, items: impl IntoIterator<Item = Item>) -> Self {
+        self.items.extend(items);
+        self
+    }
+
+    pub fn with_checklist(mut self, checklist: impl IntoIterator<Item = Item>) -> Self {
         self.checklist.extend(checklist);
         self
     }
 
-    pub fn with_recipes(mut self, recipes: Vec<Recipe>) -> Self {
+    pub fn with_recipes(mut self, recipes: impl IntoIterator<Item = Recipe>) -> Self {
         self.recipes.extend(recipes);
         self
     }
@@ -42,7 +174,279 @@ impl List {
         &self.items
     }
 
+    pub fn checklist(&self) -> &Vec<Item> {
+        &self.checklist
+    }
+
+    pub fn recipes(&self) -> &Vec<Recipe> {
+        &self.recipes
+    }
+
     pub fn add_item(&mut self, item: Item) {
         self.items.push(item);
     }
+
+    /// Compares this list's items against `other`, an earlier snapshot.
+    pub fn diff(&self, other: &List) -> ListDiff {
+        let names = |list: &List| -> HashSet<String> {
+            list.items
+                .iter()
+                .map(|item| item.name().to_string())
+                .collect()
+        };
+        let (current, previous) = (names(self), names(other));
+        let only_in = |list: &List, names: &HashSet<String>| {
+            list.items
+                .iter()
+                .filter(|item| !names.contains(item.name().as_str()))
+                .map(|item| item.name().clone())
+                .collect()
+        };
+
+        ListDiff {
+            added: only_in(self, &previous),
+            removed: only_in(other, &current),
+        }
+    }
+
+    /// Item names only, one per line, e.g. for pasting into a notes app.
+    pub fn to_plain(&self) -> String {
+        self.items
+            .iter()
+            .map(|item| item.name().as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Item names under a heading for each section, with unsectioned items
+    /// last under `uncategorized_label`. Sections come in the order their
+    /// first item appears on the list, which the store sorts by section
+    /// position.
+    pub fn to_plain_by_section(&self, uncategorized_label: &str) -> String {
+        let mut sections: Vec<(&str, Vec<&str>)> = Vec::new();
+        let mut uncategorized = Vec::new();
+        for item in &self.items {
+            let name = item.name().as_str();
+            match item.section() {
+                Some(section) => match sections
+                    .iter_mut()
+                    .find(|(heading, _)| *heading == section.as_str())
+                {
+                    Some((_, names)) => names.push(name),
+                    None => sections.push((section.as_str(), vec![name])),
+                },
+                None => uncategorized.push(name),
+            }
+        }
+
+        sections
+            .into_iter()
+            .chain((!uncategorized.is_empty()).then_some((uncategorized_label, uncategorized)))
+            .map(|(heading, names)| {
+                let names = names
+                    .iter()
+                    .map(|name| format!("  {name}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!("{heading}:\n{names}")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Encodes the list as a URL-safe string, e.g. for sharing as a link.
+    ///
+    /// The code is the list's JSON, base64url-encoded and prefixed with
+    /// [`SHARE_CODE_VERSION`] so that format changes can be detected.
+    pub fn to_share_code(&self) -> String {
+        let json = serde_json::to_vec(self).expect("a list always serializes to JSON");
+        format!("{SHARE_CODE_VERSION}.{}", URL_SAFE_NO_PAD.encode(json))
+    }
+
+    /// Decodes a list from a code produced by [`List::to_share_code`].
+    pub fn from_share_code(s: &str) -> Result<List, ShareError> {
+        let (version, payload) = s.trim().split_once('.').ok_or(ShareError::MissingVersion)?;
+        if version != SHARE_CODE_VERSION {
+            return Err(ShareError::UnsupportedVersion(version.to_string()));
+        }
+        let json = URL_SAFE_NO_PAD.decode(payload)?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list() -> List {
+        List::new()
+            .with_items(vec![Item::new("eggs"), Item::new("sour cream")])
+            .with_checklist(vec![Item::new("milk")])
+            .with_recipes(vec![Recipe::new("pancakes")])
+    }
+
+    #[test]
+    fn test_diff() {
+        let previous = List::from_iter(vec![Item::new("eggs"), Item::new("milk")]);
+        let current = List::from_iter(vec![Item::new("milk"), Item::new("bread")]);
+
+        let diff = current.diff(&previous);
+
+        assert_eq!(diff.added(), &[Name::from("bread")]);
+        assert_eq!(diff.removed(), &[Name::from("eggs")]);
+    }
+
+    #[test]
+    fn test_diff_unchanged() {
+        let diff = list().diff(&list());
+
+        assert!(diff.added().is_empty());
+        assert!(diff.removed().is_empty());
+    }
+
+    #[test]
+    fn test_builder() {
+        let list = List::new()
+            .with_items(["eggs", "flour"].into_iter().map(Item::new))
+            .with_recipes(std::iter::once(Recipe::new("pancakes")))
+            .with_checklist(vec![Item::new("milk")])
+            .with_items([Item::new("sugar")]);
+
+        insta::assert_debug_snapshot!(list, @r###"
+        List {
+            checklist: [
+                Item {
+                    name: Name(
+                        "milk",
+                    ),
+                    section: None,
+                    recipes: None,
+                    quantity: None,
+                },
+            ],
+            recipes: [
+                Recipe(
+                    "pancakes",
+                ),
+            ],
+            items: [
+                Item {
+                    name: Name(
+                        "eggs",
+                    ),
+                    section: None,
+                    recipes: None,
+                    quantity: None,
+                },
+                Item {
+                    name: Name(
+                        "flour",
+                    ),
+                    section: None,
+                    recipes: None,
+                    quantity: None,
+                },
+                Item {
+                    name: Name(
+                        "sugar",
+                    ),
+                    section: None,
+                    recipes: None,
+                    quantity: None,
+                },
+            ],
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_builder_matches_collect() {
+        let items = vec![Item::new("eggs"), Item::new("milk")];
+        assert_eq!(
+            List::new().with_items(items.clone()),
+            items.into_iter().collect::<List>()
+        );
+        assert_eq!(List::new().with_items(Vec::new()), List::default());
+    }
+
+    #[test]
+    fn test_to_plain() {
+        let list = List::new()
+            .with_items(vec![
+                Item::new("eggs"),
+                Item::new("sour cream"),
+                Item::new("milk"),
+            ])
+            .with_checklist(vec![Item::new("salt")])
+            .with_recipes(vec![Recipe::new("pancakes")]);
+        assert_eq!(list.to_plain(), "eggs\nsour cream\nmilk");
+        assert_eq!(List::new().to_plain(), "");
+    }
+
+    #[test]
+    fn test_to_plain_by_section() {
+        let list = List::new().with_items(vec![
+            Item::new("eggs"),
+            Item::new("milk").with_section("dairy"),
+            Item::new("carrots").with_section("fresh"),
+            Item::new("butter").with_section("dairy"),
+        ]);
+        assert_eq!(
+            list.to_plain_by_section("misc"),
+            "dairy:\n  milk\n  butter\nfresh:\n  carrots\nmisc:\n  eggs"
+        );
+        assert_eq!(
+            List::new()
+                .with_items(vec![Item::new("milk").with_section("dairy")])
+                .to_plain_by_section("misc"),
+            "dairy:\n  milk"
+        );
+        // the list's order is kept, not the sections' names
+        assert_eq!(
+            List::new()
+                .with_items(vec![
+                    Item::new("carrots").with_section("fresh"),
+                    Item::new("milk").with_section("dairy"),
+                ])
+                .to_plain_by_section("misc"),
+            "fresh:\n  carrots\ndairy:\n  milk"
+        );
+    }
+
+    #[test]
+    fn test_share_code_round_trip() {
+        let list = list();
+        let code = list.to_share_code();
+        assert!(code.starts_with("v1."));
+        assert!(code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')));
+        assert_eq!(List::from_share_code(&code).unwrap(), list);
+    }
+
+    #[test]
+    fn test_share_code_empty_list_round_trip() {
+        let code = List::new().to_share_code();
+        assert_eq!(List::from_share_code(&code).unwrap(), List::new());
+    }
+
+    #[test]
+    fn test_share_code_malformed() {
+        assert!(matches!(
+            List::from_share_code("no-version-here"),
+            Err(ShareError::MissingVersion)
+        ));
+        assert!(matches!(
+            List::from_share_code("v0.e30"),
+            Err(ShareError::UnsupportedVersion(v)) if v == "v0"
+        ));
+        assert!(matches!(
+            List::from_share_code("v1.not base64!"),
+            Err(ShareError::Base64(_))
+        ));
+        assert!(matches!(
+            List::from_share_code(&format!("v1.{}", URL_SAFE_NO_PAD.encode("[1, 2]"))),
+            Err(ShareError::SerdeJsonError(_))
+        ));
+    }
 }
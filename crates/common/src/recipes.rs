@@ -1,10 +1,11 @@
 use std::{fmt, ops::Deref};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::item::Name;
 
-#[derive(Serialize, Deserialize, Clone, Debug, Default, Hash, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default, Hash, Eq, PartialEq)]
 pub struct Recipe(String);
 
 impl fmt::Display for Recipe {
@@ -43,7 +44,123 @@ impl From<String> for Recipe {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+/// Progress of a recipe on the list: how many of its ingredients are already
+/// on the list and how many are still missing.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, PartialEq)]
+pub struct RecipeStatus {
+    recipe: Recipe,
+    on_list: usize,
+    missing: usize,
+}
+
+impl RecipeStatus {
+    pub fn new(recipe: Recipe, on_list: usize, missing: usize) -> Self {
+        Self {
+            recipe,
+            on_list,
+            missing,
+        }
+    }
+
+    pub fn recipe(&self) -> &Recipe {
+        &self.recipe
+    }
+
+    pub fn on_list(&self) -> usize {
+        self.on_list
+    }
+
+    pub fn missing(&self) -> usize {
+        self.missing
+    }
+}
+
+impl fmt::Display for RecipeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}/{} on list ({} missing)",
+            self.recipe,
+            self.on_list,
+            self.on_list + self.missing,
+            self.missing
+        )
+    }
+}
+
+/// A recipe with its ingredients, in the JSON shape used to share a single
+/// recipe: `{ "recipe": ..., "ingredients": [...] }`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RecipeExport {
+    recipe: Recipe,
+    ingredients: Ingredients,
+}
+
+impl RecipeExport {
+    pub fn new(recipe: Recipe, ingredients: Ingredients) -> Self {
+        Self {
+            recipe,
+            ingredients,
+        }
+    }
+
+    pub fn recipe(&self) -> &Recipe {
+        &self.recipe
+    }
+
+    pub fn ingredients(&self) -> &Ingredients {
+        &self.ingredients
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a recipe always serializes to JSON")
+    }
+
+    pub fn from_json_str(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+}
+
+/// What adding a recipe did to the library: which ingredients it created
+/// and which were already there.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default, PartialEq)]
+pub struct AddRecipeReport {
+    recipe_id: i32,
+    new_items: Vec<Name>,
+    existing_items: Vec<Name>,
+}
+
+impl AddRecipeReport {
+    pub fn new(recipe_id: i32, new_items: Vec<Name>, existing_items: Vec<Name>) -> Self {
+        Self {
+            recipe_id,
+            new_items,
+            existing_items,
+        }
+    }
+
+    pub fn recipe_id(&self) -> i32 {
+        self.recipe_id
+    }
+
+    pub fn new_items(&self) -> &[Name] {
+        &self.new_items
+    }
+
+    pub fn existing_items(&self) -> &[Name] {
+        &self.existing_items
+    }
+}
+
+/// The order recipes are listed in when read a page at a time.
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq)]
+pub enum RecipeSort {
+    #[default]
+    NameAsc,
+    NameDesc,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug, Default, PartialEq)]
 pub struct Ingredients(Vec<Name>);
 
 impl Ingredients {
@@ -58,6 +175,16 @@ impl Ingredients {
     pub fn from_input_string(s: &str) -> Self {
         Self::from(s)
     }
+
+    /// Builds ingredients from a pasted block of text, one ingredient per line.
+    /// Surrounding whitespace is trimmed and blank lines are skipped.
+    pub fn from_lines(text: &str) -> Self {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(Name::from)
+            .collect()
+    }
 }
 
 impl FromIterator<Name> for Ingredients {
@@ -84,3 +211,39 @@ impl Deref for Ingredients {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ingredients_from_lines() {
+        let text = "2 eggs\n1 tbsp milk  \n\n   \n  salt and pepper\n";
+        let ingredients = Ingredients::from_lines(text);
+        insta::assert_debug_snapshot!(ingredients, @r###"
+        Ingredients(
+            [
+                Name(
+                    "2 eggs",
+                ),
+                Name(
+                    "1 tbsp milk",
+                ),
+                Name(
+                    "salt and pepper",
+                ),
+            ],
+        )
+        "###);
+    }
+
+    #[test]
+    fn test_ingredients_from_lines_crlf_and_empty() {
+        let ingredients = Ingredients::from_lines("Butter\r\n\r\nFlour\r\n");
+        assert_eq!(
+            ingredients,
+            Ingredients::from_iter(vec![Name::from("butter"), Name::from("flour")])
+        );
+        assert!(Ingredients::from_lines("\n  \n").is_empty());
+    }
+}
@@ -1,10 +1,14 @@
 use core::fmt;
+use std::collections::BTreeMap;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::{item::Name, load::Load};
+
 pub const SECTIONS: [&str; 5] = ["fresh", "pantry", "protein", "dairy", "freezer"];
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
 pub struct Section(String);
 
 impl Section {
@@ -30,3 +34,170 @@ impl fmt::Display for Section {
         write!(f, "{}", self.0)
     }
 }
+
+/// A mapping of item names to the section each belongs in, e.g.
+/// `{ "milk": "dairy", "spinach": "fresh" }`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SectionMap(BTreeMap<String, String>);
+
+impl Load for SectionMap {
+    type T = SectionMap;
+}
+
+impl FromIterator<(Name, Section)> for SectionMap {
+    fn from_iter<I: IntoIterator<Item = (Name, Section)>>(iter: I) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(name, section)| (name.to_string(), section.to_string()))
+                .collect(),
+        )
+    }
+}
+
+impl SectionMap {
+    pub fn iter(&self) -> impl Iterator<Item = (Name, Section)> + '_ {
+        self.0
+            .iter()
+            .map(|(name, section)| (Name::from(name.as_str()), Section::from(section.as_str())))
+    }
+}
+
+/// The outcome of applying a [`SectionMap`].
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct SectionMapReport {
+    applied: usize,
+    missing_items: Vec<Name>,
+    missing_sections: Vec<Section>,
+}
+
+impl SectionMapReport {
+    pub fn new(applied: usize, missing_items: Vec<Name>, missing_sections: Vec<Section>) -> Self {
+        Self {
+            applied,
+            missing_items,
+            missing_sections,
+        }
+    }
+
+    pub fn applied(&self) -> usize {
+        self.applied
+    }
+
+    pub fn missing_items(&self) -> &[Name] {
+        &self.missing_items
+    }
+
+    pub fn missing_sections(&self) -> &[Section] {
+        &self.missing_sections
+    }
+}
+
+/// Items and their sections read from `name,section` CSV rows. Fields may be
+/// quoted, e.g. to hold a comma. A `name,section` header is skipped, and
+/// blank lines are ignored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ItemSectionsCsv {
+    rows: Vec<(Name, Section)>,
+    malformed: Vec<usize>,
+}
+
+impl ItemSectionsCsv {
+    pub fn parse(csv: &str) -> Self {
+        let mut parsed = Self::default();
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(csv.as_bytes());
+        // The reader doesn't count the blank lines it skips, and a record's
+        // position can be at the start of the blank lines before it
+        let line_of = |position: &csv::Position| {
+            let start = position.byte() as usize;
+            let start = start
+                + csv.as_bytes()[start..]
+                    .iter()
+                    .take_while(|byte| matches!(byte, b'\r' | b'\n'))
+                    .count();
+            csv[..start].matches('\n').count() + 1
+        };
+        for record in reader.records() {
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    if let Some(position) = e.position() {
+                        parsed.malformed.push(line_of(position));
+                    }
+                    continue;
+                }
+            };
+            let line = record.position().map_or(0, line_of);
+            match record.iter().collect::<Vec<_>>()[..] {
+                [name, section]
+                    if line == 1
+                        && name.eq_ignore_ascii_case("name")
+                        && section.eq_ignore_ascii_case("section") => {}
+                [name, section] if !name.is_empty() && !section.is_empty() => {
+                    parsed.rows.push((Name::from(name), Section::from(section)))
+                }
+                _ => parsed.malformed.push(line),
+            }
+        }
+        parsed
+    }
+
+    pub fn rows(&self) -> &[(Name, Section)] {
+        &self.rows
+    }
+
+    /// Line numbers, starting at 1, of rows that couldn't be read.
+    pub fn malformed(&self) -> &[usize] {
+        &self.malformed
+    }
+}
+
+/// The outcome of importing an [`ItemSectionsCsv`].
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct ItemsCsvReport {
+    imported: usize,
+    malformed_rows: Vec<usize>,
+}
+
+impl ItemsCsvReport {
+    pub fn new(imported: usize, malformed_rows: Vec<usize>) -> Self {
+        Self {
+            imported,
+            malformed_rows,
+        }
+    }
+
+    pub fn imported(&self) -> usize {
+        self.imported
+    }
+
+    pub fn malformed_rows(&self) -> &[usize] {
+        &self.malformed_rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_item_sections_csv() {
+        let csv =
+            ItemSectionsCsv::parse("name,section\nMilk, Dairy\n\nno section\na,b,c\n,fresh\n");
+        assert_eq!(csv.rows(), [(Name::from("milk"), Section::from("dairy"))]);
+        assert_eq!(csv.malformed(), [4, 5, 6]);
+
+        let csv = ItemSectionsCsv::parse("\"cheese, aged\",dairy\n\"crisps\",\"snacks, salty\"\n");
+        assert_eq!(
+            csv.rows(),
+            [
+                (Name::from("cheese, aged"), Section::from("dairy")),
+                (Name::from("crisps"), Section::from("snacks, salty")),
+            ]
+        );
+        assert!(csv.malformed().is_empty());
+    }
+}
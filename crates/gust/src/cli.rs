@@ -1,5 +1,6 @@
 use api::ApiError;
-use clap::{builder::NonEmptyStringValueParser, Arg, Command, ValueHint};
+use clap::{builder::NonEmptyStringValueParser, Arg, ArgAction, ArgGroup, Command, ValueHint};
+use common::export::UNCATEGORIZED_LABEL;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,6 +11,12 @@ pub enum CliError {
     #[error("invalid input: {0}")]
     ParseInputError(String),
 
+    #[error("JSON error: {0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+
+    #[error("error writing output: {0}")]
+    WriteError(#[from] std::io::Error),
+
     #[error("URL parse error: {0}")]
     UrlParseError(#[from] url::ParseError),
 }
@@ -49,6 +56,14 @@ fn item() -> Arg {
         .help("item name")
 }
 
+fn new_name() -> Arg {
+    Arg::new("name")
+        .long("name")
+        .required(true)
+        .value_hint(ValueHint::Unknown)
+        .value_parser(NonEmptyStringValueParser::new())
+}
+
 fn recipe() -> Arg {
     Arg::new("recipe")
         .long("recipe")
@@ -82,16 +97,54 @@ fn clear_checklist() -> Command {
         .about("delete everything from checklist")
 }
 
+fn clear_list() -> Command {
+    Command::new("clear")
+        .subcommand_required(false)
+        .about("delete everything from the list, leaving the checklist alone")
+}
+
 fn refresh_list() -> Command {
     Command::new("clear")
         .subcommand_required(false)
         .about("refresh list")
+        .arg(
+            Arg::new("subscriptions")
+                .long("subscriptions")
+                .action(ArgAction::SetTrue)
+                .help("put subscribed items back on the list"),
+        )
+        .arg(
+            item()
+                .long("keep")
+                .action(ArgAction::Append)
+                .conflicts_with("subscriptions")
+                .help("leave this item on the list"),
+        )
+}
+
+fn subscription() -> Command {
+    Command::new("subscription")
+        .about("work with items that go back on the list when it's refreshed")
+        .arg(item().required(true))
 }
 
 fn read_all_items() -> Command {
     Command::new("all")
         .subcommand_required(false)
         .about("read all items from library")
+        .arg(
+            Arg::new("with-sections")
+                .long("with-sections")
+                .action(ArgAction::SetTrue)
+                .help("show each item's section"),
+        )
+        .arg(
+            Arg::new("prefix")
+                .long("prefix")
+                .num_args(1)
+                .conflicts_with("with-sections")
+                .help("only items whose names start with this"),
+        )
 }
 
 fn sections() -> Command {
@@ -105,7 +158,43 @@ fn checklist() -> Command {
 }
 
 fn read_list() -> Command {
-    Command::new("list").about("read the list")
+    Command::new("list")
+        .about("read the list")
+        .arg(
+            Arg::new("items-only")
+                .long("items-only")
+                .action(ArgAction::SetTrue)
+                .help("read only the list's items"),
+        )
+        .arg(
+            Arg::new("check")
+                .long("check")
+                .action(ArgAction::SetTrue)
+                .help("report list entries that aren't items in the library"),
+        )
+        .arg(
+            Arg::new("diff")
+                .long("diff")
+                .num_args(1)
+                .value_hint(ValueHint::FilePath)
+                .value_parser(NonEmptyStringValueParser::new())
+                .help("show what's changed since a list saved as JSON, e.g. 'list.json'"),
+        )
+        .arg(recipe().help("read only the list items called for by this recipe"))
+        .arg(
+            Arg::new("frequent")
+                .long("frequent")
+                .num_args(1)
+                .value_parser(clap::value_parser!(usize))
+                .help("read this many of the items most often put on the list"),
+        )
+        .arg(
+            Arg::new("common")
+                .long("common")
+                .action(ArgAction::SetTrue)
+                .exclusive(true)
+                .help("read the ingredients every recipe on the list calls for"),
+        )
 }
 
 fn list() -> Command {
@@ -121,7 +210,24 @@ fn add() -> Command {
         .arg(recipe())
         .arg(ingredients())
         .arg(checklist_item())
-        .subcommand(list().arg(item()).arg(recipe()))
+        .subcommand(
+            checklist().arg(
+                Arg::new("existing")
+                    .long("existing")
+                    .action(ArgAction::SetTrue)
+                    .help("only add the item if it's already in the library"),
+            ),
+        )
+        .subcommand(
+            list().arg(item()).arg(recipe()).arg(
+                Arg::new("refresh")
+                    .long("refresh")
+                    .action(ArgAction::SetTrue)
+                    .requires("recipe")
+                    .help("replace the recipe's items on the list with its current ingredients"),
+            ),
+        )
+        .subcommand(subscription())
 }
 
 fn delete() -> Command {
@@ -136,7 +242,27 @@ fn delete() -> Command {
         )
         .arg(recipe())
         .arg(item())
-        .subcommand(list().arg(recipe()).arg(item()))
+        .subcommand(
+            list()
+                .subcommand(clear_list())
+                .arg(recipe())
+                .arg(item())
+                .arg(
+                    Arg::new("with-items")
+                        .long("with-items")
+                        .action(ArgAction::SetTrue)
+                        .requires("recipe")
+                        .help("also remove the recipe's items, unless added by hand or needed by another recipe"),
+                )
+                .group(
+                    ArgGroup::new("entry")
+                        .args(["recipe", "item"])
+                        .required(true),
+                )
+                .subcommand_negates_reqs(true),
+        )
+        .subcommand(subscription())
+        .subcommand(Command::new("empty-sections").about("delete sections without any items"))
 }
 
 fn fetch() -> Command {
@@ -144,6 +270,29 @@ fn fetch() -> Command {
         .subcommand_required(false)
         .about("fetch recipes from a URL")
         .arg(url())
+        .arg(
+            section()
+                .default_value("uncategorized")
+                .help("section for ingredients new to the library"),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .action(ArgAction::SetTrue)
+                .help("fetch the recipe again even if it was fetched recently"),
+        )
+        .arg(
+            Arg::new("allow-empty")
+                .long("allow-empty")
+                .action(ArgAction::SetTrue)
+                .help("save the recipe even if no ingredients were found"),
+        )
+        .arg(
+            Arg::new("auto-section")
+                .long("auto-section")
+                .action(ArgAction::SetTrue)
+                .help("put new items in the section suggested by the recipe's other items"),
+        )
 }
 
 fn read() -> Command {
@@ -152,15 +301,170 @@ fn read() -> Command {
         .about("read stuff")
         .arg(item())
         .arg(recipe())
+        .arg(
+            Arg::new("by-section")
+                .long("by-section")
+                .action(ArgAction::SetTrue)
+                .requires("recipe")
+                .help("group the recipe's ingredients by section"),
+        )
+        .arg(
+            Arg::new("id")
+                .long("id")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("by-section")
+                .help("read the store's id for the item or recipe"),
+        )
+        .arg(
+            Arg::new("status")
+                .long("status")
+                .action(ArgAction::SetTrue)
+                .requires("recipe")
+                .conflicts_with_all(["id", "by-section"])
+                .help("check off the recipe's ingredients that are already on the list"),
+        )
+        .arg(
+            Arg::new("planned")
+                .long("planned")
+                .action(ArgAction::SetTrue)
+                .requires("recipe")
+                .conflicts_with_all(["id", "by-section", "status"])
+                .help("read only the recipe's ingredients that are already on the list"),
+        )
+        .arg(
+            Arg::new("suggest-section")
+                .long("suggest-section")
+                .action(ArgAction::SetTrue)
+                .requires("item")
+                .conflicts_with("id")
+                .help("suggest a section for the item from the items it shares recipes with"),
+        )
+        .arg(
+            Arg::new("detail")
+                .long("detail")
+                .action(ArgAction::SetTrue)
+                .requires("item")
+                .conflicts_with_all(["id", "suggest-section"])
+                .help("show the item's section and recipes, and whether it's on the list or checklist"),
+        )
+        .arg(section())
         .subcommand(read_list())
         .subcommand(checklist())
         .subcommand(read_all_items())
         .subcommand(
             Command::new("recipes")
                 .subcommand_required(false)
-                .about("read all recipes"),
+                .about("read all recipes")
+                .arg(ingredient().help("only recipes with an ingredient matching this name"))
+                .arg(
+                    Arg::new("empty")
+                        .long("empty")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("ingredient")
+                        .help("only recipes without any ingredients"),
+                )
+                .arg(
+                    Arg::new("available")
+                        .long("available")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["ingredient", "empty"])
+                        .help("only recipes that aren't on the list yet"),
+                )
+                .arg(
+                    Arg::new("offset")
+                        .long("offset")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(usize))
+                        .conflicts_with_all(["ingredient", "empty"])
+                        .help("skip this many recipes"),
+                )
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(usize))
+                        .conflicts_with_all(["ingredient", "empty"])
+                        .help("read at most this many recipes"),
+                )
+                .arg(
+                    Arg::new("desc")
+                        .long("desc")
+                        .action(ArgAction::SetTrue)
+                        .help("with --offset, --limit or --preview, list recipes from Z to A"),
+                )
+                .arg(
+                    Arg::new("recent")
+                        .long("recent")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(usize))
+                        .conflicts_with_all(["ingredient", "empty", "available", "offset", "limit"])
+                        .help("read this many of the recipes most recently fetched from a URL"),
+                )
+                .arg(
+                    Arg::new("coverage")
+                        .long("coverage")
+                        .action(ArgAction::SetTrue)
+                        .exclusive(true)
+                        .help("rank recipes by how many of their ingredients are on the list"),
+                )
+                .arg(
+                    Arg::new("histogram")
+                        .long("histogram")
+                        .action(ArgAction::SetTrue)
+                        .exclusive(true)
+                        .help("count how many recipes have each number of ingredients"),
+                )
+                .arg(
+                    Arg::new("preview")
+                        .long("preview")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["ingredient", "empty", "available"])
+                        .help("show each recipe with a few of its ingredients"),
+                ),
+        )
+        .subcommand(Command::new("deleted-recipes").about("read recently deleted recipes"))
+        .subcommand(
+            Command::new("duplicates")
+                .about("read items whose names differ only by whitespace or case"),
+        )
+        .subcommand(
+            Command::new("overlap").about("read items that are on both the list and the checklist"),
+        )
+        .subcommand(Command::new("is-empty").about("check whether the library has any items"))
+        .subcommand(
+            Command::new("ingredients")
+                .about("read the ingredients needed across several recipes")
+                .arg(recipe().action(ArgAction::Append).required(true)),
+        )
+        .subcommand(
+            sections().arg(
+                Arg::new("empty")
+                    .long("empty")
+                    .action(ArgAction::SetTrue)
+                    .help("only sections without any items"),
+            ),
+        )
+        .subcommand(
+            Command::new("shopping")
+                .about("read the list in store order, with quantities and recipes"),
+        )
+        .subcommand(
+            Command::new("stats")
+                .about("read how many rows each table has and the database's size"),
+        )
+        .subcommand(Command::new("subscriptions").about("read subscribed items"))
+        .subcommand(Command::new("units").about("read the units recipes measure ingredients in"))
+        .subcommand(
+            Command::new("trips")
+                .about("read completed shopping trips, most recent first")
+                .arg(
+                    Arg::new("trip")
+                        .long("trip")
+                        .num_args(1)
+                        .value_parser(clap::value_parser!(i32))
+                        .help("read only the trip with this id"),
+                ),
         )
-        .subcommand(sections())
 }
 
 fn update() -> Command {
@@ -183,21 +487,245 @@ fn update() -> Command {
                     Command::new("edit-ingredient")
                         .about("edits an ingredient in a recipe")
                         .arg(ingredient()),
+                )
+                .subcommand(
+                    Command::new("rename")
+                        .about("rename a recipe")
+                        .arg(new_name().help("the recipe's new name")),
+                )
+                .subcommand(
+                    Command::new("merge")
+                        .about("merge another recipe into this one, then delete it")
+                        .arg(new_name().help("the recipe to merge in")),
+                )
+                .subcommand(
+                    Command::new("refetch")
+                        .about("replace the recipe's ingredients with a fresh fetch of a URL")
+                        .arg(url()),
+                )
+                .subcommand(Command::new("restore").about("restore a deleted recipe"))
+                .subcommand(
+                    Command::new("amount")
+                        .about("set how much of an ingredient the recipe calls for")
+                        .arg(ingredient().required(true))
+                        .arg(
+                            Arg::new("quantity")
+                                .long("quantity")
+                                .required(true)
+                                .num_args(1)
+                                .value_parser(clap::value_parser!(i32).range(1..))
+                                .help("how many of the unit, or of the item without one"),
+                        )
+                        .arg(
+                            Arg::new("unit")
+                                .long("unit")
+                                .num_args(1)
+                                .value_parser(NonEmptyStringValueParser::new())
+                                .help("the unit measured in, e.g. 'g' or 'tbsp'"),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("item")
+                .about("rename an item, merging it into any item that already has the new name")
+                .arg(item().required(true))
+                .arg(new_name().help("the item's new name"))
+                .arg(
+                    Arg::new("strict")
+                        .long("strict")
+                        .action(ArgAction::SetTrue)
+                        .help("fail instead of merging if an item already has the new name"),
+                ),
+        )
+        .subcommand(
+            Command::new("section")
+                .about(
+                    "rename a section, merging it into any section that already has the new name",
+                )
+                .arg(section().required(true))
+                .arg(
+                    new_name()
+                        .required_unless_present("staples")
+                        .help("the section's new name"),
+                )
+                .arg(
+                    Arg::new("staples")
+                        .long("staples")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("name")
+                        .help("subscribe to every item in the section instead of renaming it"),
+                ),
+        )
+        .subcommand(
+            Command::new("substitute")
+                .about("use a different ingredient in place of an item in every recipe")
+                .arg(item().required(true))
+                .arg(new_name().help("the ingredient to use instead")),
+        )
+        .subcommand(
+            Command::new("unit")
+                .about("rename a unit in every recipe, e.g. 'tbsp' to 'tablespoon'")
+                .arg(
+                    Arg::new("unit")
+                        .long("unit")
+                        .required(true)
+                        .value_parser(NonEmptyStringValueParser::new())
+                        .help("the unit to rename"),
+                )
+                .arg(new_name().help("the unit's new name")),
+        )
+        .subcommand(
+            list()
+                .subcommand(refresh_list())
+                .subcommand(Command::new("all").about("put every item in the library on the list"))
+                .subcommand(
+                    Command::new("complete")
+                        .about("archive the list as a completed shopping trip, then clear it"),
+                )
+                .subcommand(
+                    Command::new("restore")
+                        .about("replace the list with the items from a completed trip")
+                        .arg(
+                            Arg::new("trip")
+                                .long("trip")
+                                .required(true)
+                                .num_args(1)
+                                .value_parser(clap::value_parser!(i32))
+                                .help("the id of the trip, as shown by 'read trips'"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("set")
+                        .about("replace everything on the list with these items")
+                        .arg(item().action(ArgAction::Append).required(true)),
+                )
+                .subcommand(
+                    Command::new("toggle")
+                        .about("put an item on the list, or take it off if it's already there")
+                        .arg(item().required(true)),
+                ),
+        )
+        .subcommand(
+            Command::new("section-map")
+                .about("assign items to sections from a JSON file, e.g. '{ \"milk\": \"dairy\" }'")
+                .arg(
+                    Arg::new("path")
+                        .required(true)
+                        .value_hint(ValueHint::FilePath)
+                        .value_parser(NonEmptyStringValueParser::new())
+                        .help("path to the JSON file"),
                 ),
         )
-        .subcommand(list().subcommand(refresh_list()))
 }
 
 fn import() -> Command {
     Command::new("import")
         .subcommand_required(false)
         .about("import from 'items.json' and 'list.json' files")
+        .arg(
+            Arg::new("share-code")
+                .long("share-code")
+                .num_args(1)
+                .value_hint(ValueHint::Unknown)
+                .value_parser(NonEmptyStringValueParser::new())
+                .help("import a list from a share code instead"),
+        )
+        .arg(
+            Arg::new("fresh")
+                .long("fresh")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("share-code")
+                .help("clear existing data before importing"),
+        )
+        .arg(
+            Arg::new("csv")
+                .long("csv")
+                .num_args(1)
+                .value_hint(ValueHint::FilePath)
+                .value_parser(NonEmptyStringValueParser::new())
+                .conflicts_with_all(["share-code", "fresh"])
+                .help("import items with their sections from 'name,section' CSV rows instead"),
+        )
+        .arg(
+            Arg::new("dump")
+                .long("dump")
+                .num_args(1)
+                .value_hint(ValueHint::FilePath)
+                .value_parser(NonEmptyStringValueParser::new())
+                .conflicts_with_all(["share-code", "fresh", "csv"])
+                .help("replace the whole database with a JSON dump instead, e.g. 'dump.json'"),
+        )
+        .arg(
+            Arg::new("checklist")
+                .long("checklist")
+                .num_args(1)
+                .value_hint(ValueHint::FilePath)
+                .value_parser(NonEmptyStringValueParser::new())
+                .conflicts_with_all(["share-code", "fresh", "csv", "dump"])
+                .help("add the items in a saved checklist to the checklist instead"),
+        )
+        .arg(
+            Arg::new("paprika")
+                .long("paprika")
+                .num_args(1)
+                .value_hint(ValueHint::FilePath)
+                .value_parser(NonEmptyStringValueParser::new())
+                .conflicts_with_all(["share-code", "fresh", "csv", "dump", "checklist"])
+                .help("add the recipes in a Paprika JSON export instead"),
+        )
+}
+
+fn migrations() -> Command {
+    Command::new("migrations").about("show which database migrations have been applied")
+}
+
+fn schema() -> Command {
+    Command::new("schema").about("print a JSON Schema describing the API's responses")
 }
 
 fn export() -> Command {
     Command::new("export")
         .subcommand_required(false)
         .about("export items to 'items.yaml' and list to 'list.yaml' files")
+        .arg(
+            Arg::new("share-code")
+                .long("share-code")
+                .action(ArgAction::SetTrue)
+                .help("print the list as a shareable code instead"),
+        )
+        .arg(
+            Arg::new("dump")
+                .long("dump")
+                .action(ArgAction::SetTrue)
+                .help("print the whole database as JSON instead"),
+        )
+        .arg(
+            Arg::new("checklist")
+                .long("checklist")
+                .action(ArgAction::SetTrue)
+                .help("print the checklist as JSON, to import again later, instead"),
+        )
+        .arg(
+            Arg::new("plain")
+                .long("plain")
+                .action(ArgAction::SetTrue)
+                .help("print the list's item names, one per line, instead"),
+        )
+        .arg(
+            Arg::new("by-section")
+                .long("by-section")
+                .action(ArgAction::SetTrue)
+                .help("print the list's item names grouped by section instead"),
+        )
+        .arg(
+            Arg::new("uncategorized-label")
+                .long("uncategorized-label")
+                .num_args(1)
+                .value_parser(NonEmptyStringValueParser::new())
+                .default_value(UNCATEGORIZED_LABEL)
+                .help("heading for items without a section"),
+        )
+        .arg(recipe().help("print a single recipe and its ingredients as JSON instead"))
 }
 
 fn store() -> Arg {
@@ -209,6 +737,22 @@ fn store() -> Arg {
         .help("which database to use")
 }
 
+fn seed_sections() -> Arg {
+    Arg::new("seed-sections")
+        .long("seed-sections")
+        .action(ArgAction::SetTrue)
+        .help("add any of the default sections that are missing")
+}
+
+fn output() -> Arg {
+    Arg::new("output")
+        .long("output")
+        .num_args(1)
+        .value_parser(["text", "json"])
+        .default_value("text")
+        .help("how to print responses")
+}
+
 pub fn cli() -> Command {
     Command::new("gust")
         .about("gust: rust-powered grocery list creator")
@@ -221,5 +765,9 @@ pub fn cli() -> Command {
         .subcommand(update())
         .subcommand(import())
         .subcommand(export())
+        .subcommand(migrations())
+        .subcommand(schema())
         .arg(store())
+        .arg(seed_sections())
+        .arg(output())
 }
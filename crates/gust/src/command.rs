@@ -1,22 +1,43 @@
 use common::{
-    commands::{Add, ApiCommand, Delete, Read, Update},
+    commands::{Add, ApiCommand, Delete, FetchOptions, Read, Update},
+    export::Format,
     item::Name,
-    recipes::{Ingredients, Recipe},
+    recipes::{Ingredients, Recipe, RecipeSort},
     section::Section,
 };
 
 use clap::ArgMatches;
+use std::path::PathBuf;
 use url::Url;
 
 use crate::CliError;
 
 pub enum UserCommand {
     Add(Add),
+    ApplySectionMap(PathBuf),
     Delete(Delete),
+    Dump,
     Export,
-    FetchRecipe(Url),
-    ImportFromJson,
+    ExportChecklist,
+    ExportList(Format),
+    ExportRecipe(Recipe),
+    ExportShareCode,
+    FetchRecipe {
+        url: Url,
+        default_section: Option<Section>,
+        options: FetchOptions,
+    },
+    ImportChecklist(PathBuf),
+    ImportFromJson {
+        fresh: bool,
+    },
+    ImportItemsCsv(PathBuf),
+    ImportPaprika(PathBuf),
+    ImportShareCode(String),
+    MigrationStatus,
     Read(Read),
+    Restore(PathBuf),
+    Schema,
     Update(Update),
 }
 
@@ -45,21 +66,37 @@ impl TryFrom<ArgMatches> for UserCommand {
                     Add::checklist_item_from_name(Name::from(item.as_str()))
                 } else {
                     match matches.subcommand() {
-                        Some(("checklist", matches)) => Add::checklist_item_from_name(Name::from(
-                            matches
-                                .get_one::<String>("item")
-                                .expect("item required")
-                                .as_str(),
-                        )),
+                        Some(("checklist", matches)) => {
+                            let name = Name::from(
+                                matches
+                                    .get_one::<String>("item")
+                                    .expect("item required")
+                                    .as_str(),
+                            );
+                            if matches.get_flag("existing") {
+                                Add::existing_checklist_item_from_name(name)
+                            } else {
+                                Add::checklist_item_from_name(name)
+                            }
+                        }
                         Some(("list", matches)) => {
                             if let Some(name) = matches.get_one::<String>("recipe") {
-                                Add::list_recipe_from_name(name.as_str().into())
+                                Add::list_recipe_from_name(
+                                    name.as_str().into(),
+                                    matches.get_flag("refresh"),
+                                )
                             } else if let Some(name) = matches.get_one::<String>("item") {
                                 Add::list_item_from_name(Name::from(name.as_str()))
                             } else {
                                 unimplemented!()
                             }
                         }
+                        Some(("subscription", matches)) => Add::subscription_from_name(Name::from(
+                            matches
+                                .get_one::<String>("item")
+                                .expect("item required")
+                                .as_str(),
+                        )),
                         _ => unreachable!(),
                     }
                 },
@@ -77,6 +114,30 @@ impl TryFrom<ArgMatches> for UserCommand {
                             };
                             Delete::ChecklistItem(Name::from(name.as_str()))
                         }
+                        Some(("list", matches)) => match matches.subcommand() {
+                            Some(("clear", _)) => Delete::ClearList,
+                            _ => match matches.get_one::<String>("recipe") {
+                                Some(name) => Delete::ListRecipe {
+                                    recipe: name.as_str().into(),
+                                    with_items: matches.get_flag("with-items"),
+                                },
+                                None => match matches.get_one::<String>("item") {
+                                    Some(name) => Delete::ListItem(Name::from(name.as_str())),
+                                    None => {
+                                        return Err(CliError::ParseInputError(
+                                            "delete list needs a --recipe or an --item".to_string(),
+                                        ))
+                                    }
+                                },
+                            },
+                        },
+                        Some(("empty-sections", _)) => Delete::EmptySections,
+                        Some(("subscription", matches)) => Delete::Subscription(Name::from(
+                            matches
+                                .get_one::<String>("item")
+                                .expect("item required")
+                                .as_str(),
+                        )),
                         _ => unimplemented!(),
                     }
                 },
@@ -86,41 +147,310 @@ impl TryFrom<ArgMatches> for UserCommand {
                     unreachable!("Providing a URL is required")
                 };
                 let url: Url = Url::parse(url)?;
-                Ok(UserCommand::FetchRecipe(url))
+                Ok(UserCommand::FetchRecipe {
+                    url,
+                    default_section: matches
+                        .get_one::<String>("section")
+                        .map(|section| Section::from(section.trim())),
+                    options: FetchOptions {
+                        force: matches.get_flag("force"),
+                        allow_empty: matches.get_flag("allow-empty"),
+                        auto_section: matches.get_flag("auto-section"),
+                    },
+                })
             }
             Some(("read", matches)) => Ok(UserCommand::Read(
                 if let Some(name) = matches.get_one::<String>("recipe") {
-                    Read::recipe_from_name(name.as_str().into())
+                    if matches.get_flag("id") {
+                        Read::RecipeId(name.as_str().into())
+                    } else if matches.get_flag("status") {
+                        Read::RecipeStatus(name.as_str().into())
+                    } else if matches.get_flag("planned") {
+                        Read::RecipeAlreadyPlanned(name.as_str().into())
+                    } else if matches.get_flag("by-section") {
+                        Read::RecipeBySection(name.as_str().into())
+                    } else {
+                        Read::recipe_from_name(name.as_str().into())
+                    }
                 } else if let Some(name) = matches.get_one::<String>("item") {
-                    Read::item_from_name(Name::from(name.as_str()))
+                    if matches.get_flag("id") {
+                        Read::ItemId(Name::from(name.as_str()))
+                    } else if matches.get_flag("detail") {
+                        Read::ItemDetail(Name::from(name.as_str()))
+                    } else if matches.get_flag("suggest-section") {
+                        Read::SuggestSection(Name::from(name.as_str()))
+                    } else {
+                        Read::item_from_name(Name::from(name.as_str()))
+                    }
+                } else if let Some(section) = matches.get_one::<String>("section") {
+                    Read::items_in_section(Section::from(section.as_str()))
                 } else {
                     match matches.subcommand() {
+                        Some(("all", matches)) if matches.get_flag("with-sections") => {
+                            Read::ItemsWithSections
+                        }
+                        Some(("all", matches)) if matches.contains_id("prefix") => {
+                            Read::ItemsByPrefix(
+                                matches
+                                    .get_one::<String>("prefix")
+                                    .expect("prefix has a value")
+                                    .to_string(),
+                            )
+                        }
                         Some(("checklist", _matches)) => Read::Checklist,
-                        Some(("list", _matches)) => Read::List,
+                        Some(("deleted-recipes", _matches)) => Read::DeletedRecipes,
+                        Some(("duplicates", _matches)) => Read::DuplicateItems,
+                        Some(("ingredients", matches)) => Read::CombinedIngredients(
+                            matches
+                                .get_many::<String>("recipe")
+                                .expect("recipe required")
+                                .map(|name| name.as_str().into())
+                                .collect(),
+                        ),
+                        Some(("list", matches)) => {
+                            if matches.get_flag("common") {
+                                Read::CommonListIngredients
+                            } else if let Some(path) = matches.get_one::<String>("diff") {
+                                Read::ListDiff(PathBuf::from(path))
+                            } else if let Some(name) = matches.get_one::<String>("recipe") {
+                                Read::ListItemsFromRecipe(name.as_str().into())
+                            } else if let Some(&limit) = matches.get_one::<usize>("frequent") {
+                                Read::FrequentlyListed(limit)
+                            } else if matches.get_flag("check") {
+                                Read::ListMissingFromLibrary
+                            } else if matches.get_flag("items-only") {
+                                Read::ListItemsOnly
+                            } else {
+                                Read::List
+                            }
+                        }
+                        Some(("is-empty", _matches)) => Read::IsEmpty,
                         Some(("library", _matches)) => Read::All,
-                        Some(("recipes", _matches)) => Read::Recipes,
+                        Some(("overlap", _matches)) => Read::Overlap,
+                        Some(("recipes", matches)) if matches.get_flag("empty") => {
+                            Read::EmptyRecipes
+                        }
+                        Some(("recipes", matches)) if matches.get_flag("coverage") => {
+                            Read::RecipeCoverage
+                        }
+                        Some(("recipes", matches)) if matches.get_flag("histogram") => {
+                            Read::IngredientHistogram
+                        }
+                        Some(("recipes", matches)) if matches.get_flag("available") => {
+                            Read::AvailableRecipes
+                        }
+                        Some(("recipes", matches)) if matches.contains_id("recent") => {
+                            Read::RecentFetches(
+                                *matches
+                                    .get_one::<usize>("recent")
+                                    .expect("recent has a value"),
+                            )
+                        }
+                        Some(("recipes", matches))
+                            if matches.get_flag("preview")
+                                || matches.contains_id("offset")
+                                || matches.contains_id("limit") =>
+                        {
+                            Read::RecipesPage {
+                                offset: matches.get_one::<usize>("offset").copied().unwrap_or(0),
+                                limit: matches
+                                    .get_one::<usize>("limit")
+                                    .copied()
+                                    .unwrap_or(usize::MAX),
+                                sort: if matches.get_flag("desc") {
+                                    RecipeSort::NameDesc
+                                } else {
+                                    RecipeSort::NameAsc
+                                },
+                                preview: matches.get_flag("preview"),
+                            }
+                        }
+                        Some(("recipes", matches)) => match matches.get_one::<String>("ingredient")
+                        {
+                            Some(ingredient) => Read::RecipesUsing(ingredient.to_string()),
+                            None => Read::Recipes,
+                        },
+                        Some(("sections", matches)) if matches.get_flag("empty") => {
+                            Read::EmptySections
+                        }
                         Some(("sections", _matches)) => Read::Sections,
+                        Some(("shopping", _matches)) => Read::ShoppingOrder,
+                        Some(("stats", _matches)) => Read::Stats,
+                        Some(("subscriptions", _matches)) => Read::Subscriptions,
+                        Some(("units", _matches)) => Read::Units,
+                        Some(("trips", matches)) => match matches.get_one::<i32>("trip") {
+                            Some(&id) => Read::Trip(id),
+                            None => Read::Trips,
+                        },
                         _ => Read::All,
                     }
                 },
             )),
-            Some(("update", matches)) => Ok(UserCommand::Update(match matches.subcommand() {
+            Some(("update", matches)) => match matches.subcommand() {
+                Some(("item", matches)) => {
+                    let item = matches.get_one::<String>("item").expect("item is required");
+                    let name = matches.get_one::<String>("name").expect("name is required");
+                    let (item, new_name) = (Name::from(item.as_str()), Name::from(name.as_str()));
+                    Ok(UserCommand::Update(if matches.get_flag("strict") {
+                        Update::RenameItemStrict { item, new_name }
+                    } else {
+                        Update::rename_item(item, new_name)
+                    }))
+                }
+                Some(("section", matches)) => {
+                    let section = matches
+                        .get_one::<String>("section")
+                        .expect("section is required");
+                    if matches.get_flag("staples") {
+                        return Ok(UserCommand::Update(Update::StapleSection(Section::from(
+                            section.trim(),
+                        ))));
+                    }
+                    let name = matches.get_one::<String>("name").expect("name is required");
+                    Ok(UserCommand::Update(Update::rename_section(
+                        Section::from(section.trim()),
+                        Section::from(name.trim()),
+                    )))
+                }
+                Some(("substitute", matches)) => {
+                    let item = matches.get_one::<String>("item").expect("item is required");
+                    let name = matches.get_one::<String>("name").expect("name is required");
+                    Ok(UserCommand::Update(Update::Substitute {
+                        from: Name::from(item.as_str()),
+                        to: Name::from(name.as_str()),
+                    }))
+                }
+                Some(("unit", matches)) => {
+                    let unit = matches.get_one::<String>("unit").expect("unit is required");
+                    let name = matches.get_one::<String>("name").expect("name is required");
+                    Ok(UserCommand::Update(Update::NormalizeUnit {
+                        from: unit.to_string(),
+                        to: name.to_string(),
+                    }))
+                }
+                Some(("section-map", matches)) => {
+                    let path = matches.get_one::<String>("path").expect("path is required");
+                    Ok(UserCommand::ApplySectionMap(PathBuf::from(path)))
+                }
                 Some(("recipe", matches)) => {
                     let Some(name) = matches.get_one::<String>("recipe") else {
                         todo!()
                     };
-                    Update::recipe_from_name(name.as_str().into())
-                }
-                Some(("list", matches)) => {
-                    let Some(("clear", _)) = matches.subcommand() else {
-                        unimplemented!()
-                    };
-                    Update::RefreshList
+                    Ok(UserCommand::Update(match matches.subcommand() {
+                        Some(("rename", matches)) => Update::rename_recipe(
+                            name.as_str().into(),
+                            matches
+                                .get_one::<String>("name")
+                                .expect("name is required")
+                                .as_str()
+                                .into(),
+                        ),
+                        Some(("merge", matches)) => Update::MergeRecipes {
+                            keep: name.as_str().into(),
+                            remove: matches
+                                .get_one::<String>("name")
+                                .expect("name is required")
+                                .as_str()
+                                .into(),
+                        },
+                        Some(("refetch", matches)) => Update::RefetchRecipe {
+                            recipe: name.as_str().into(),
+                            url: Url::parse(
+                                matches.get_one::<String>("url").expect("url is required"),
+                            )?,
+                        },
+                        Some(("restore", _)) => {
+                            Update::restore_recipe_from_name(name.as_str().into())
+                        }
+                        Some(("amount", matches)) => Update::IngredientAmount {
+                            recipe: name.as_str().into(),
+                            item: matches
+                                .get_one::<String>("ingredient")
+                                .expect("ingredient is required")
+                                .as_str()
+                                .into(),
+                            quantity: *matches
+                                .get_one::<i32>("quantity")
+                                .expect("quantity is required"),
+                            unit: matches.get_one::<String>("unit").cloned(),
+                        },
+                        _ => Update::recipe_from_name(name.as_str().into()),
+                    }))
                 }
+                Some(("list", matches)) => match matches.subcommand() {
+                    Some(("clear", matches)) => Ok(UserCommand::Update(
+                        match matches.get_many::<String>("item") {
+                            Some(keep) => Update::RefreshKeeping(
+                                keep.map(|name| Name::from(name.as_str())).collect(),
+                            ),
+                            None => Update::refresh_list(matches.get_flag("subscriptions")),
+                        },
+                    )),
+                    Some(("all", _)) => Ok(UserCommand::Update(Update::ListAllItems)),
+                    Some(("complete", _)) => Ok(UserCommand::Update(Update::CompleteTrip)),
+                    Some(("restore", matches)) => Ok(UserCommand::Update(Update::RestoreTrip(
+                        *matches.get_one::<i32>("trip").expect("trip is required"),
+                    ))),
+                    Some(("set", matches)) => Ok(UserCommand::Update(Update::SetList(
+                        matches
+                            .get_many::<String>("item")
+                            .expect("item required")
+                            .map(|name| Name::from(name.as_str()))
+                            .collect(),
+                    ))),
+                    Some(("toggle", matches)) => {
+                        Ok(UserCommand::Update(Update::ToggleListItem(Name::from(
+                            matches
+                                .get_one::<String>("item")
+                                .expect("item required")
+                                .as_str(),
+                        ))))
+                    }
+                    _ => unimplemented!(),
+                },
                 _ => unimplemented!(),
-            })),
-            Some(("import", _)) => Ok(UserCommand::ImportFromJson),
-            Some(("export", _)) => Ok(UserCommand::Export),
+            },
+            Some(("import", matches)) => Ok(
+                if let Some(code) = matches.get_one::<String>("share-code") {
+                    UserCommand::ImportShareCode(code.to_string())
+                } else if let Some(path) = matches.get_one::<String>("csv") {
+                    UserCommand::ImportItemsCsv(PathBuf::from(path))
+                } else if let Some(path) = matches.get_one::<String>("dump") {
+                    UserCommand::Restore(PathBuf::from(path))
+                } else if let Some(path) = matches.get_one::<String>("checklist") {
+                    UserCommand::ImportChecklist(PathBuf::from(path))
+                } else if let Some(path) = matches.get_one::<String>("paprika") {
+                    UserCommand::ImportPaprika(PathBuf::from(path))
+                } else {
+                    UserCommand::ImportFromJson {
+                        fresh: matches.get_flag("fresh"),
+                    }
+                },
+            ),
+            Some(("export", matches)) => {
+                Ok(if let Some(name) = matches.get_one::<String>("recipe") {
+                    UserCommand::ExportRecipe(name.as_str().into())
+                } else if matches.get_flag("share-code") {
+                    UserCommand::ExportShareCode
+                } else if matches.get_flag("dump") {
+                    UserCommand::Dump
+                } else if matches.get_flag("checklist") {
+                    UserCommand::ExportChecklist
+                } else if matches.get_flag("plain") {
+                    UserCommand::ExportList(Format::Plain)
+                } else if matches.get_flag("by-section") {
+                    UserCommand::ExportList(Format::BySection {
+                        uncategorized_label: matches
+                            .get_one::<String>("uncategorized-label")
+                            .expect("label has a default")
+                            .to_string(),
+                    })
+                } else {
+                    UserCommand::Export
+                })
+            }
+            Some(("migrations", _)) => Ok(UserCommand::MigrationStatus),
+            Some(("schema", _)) => Ok(UserCommand::Schema),
             _ => unreachable!(),
         }
     }
@@ -130,11 +460,32 @@ impl From<UserCommand> for ApiCommand {
     fn from(command: UserCommand) -> Self {
         match command {
             UserCommand::Add(cmd) => Self::Add(cmd),
+            UserCommand::ApplySectionMap(path) => Self::ApplySectionMap(path),
             UserCommand::Delete(cmd) => Self::Delete(cmd),
+            UserCommand::Dump => Self::Dump,
             UserCommand::Export => Self::Export,
-            UserCommand::FetchRecipe(cmd) => Self::FetchRecipe(cmd),
-            UserCommand::ImportFromJson => Self::ImportFromJson,
+            UserCommand::ExportChecklist => Self::ExportChecklist,
+            UserCommand::ExportList(format) => Self::ExportList(format),
+            UserCommand::ExportRecipe(recipe) => Self::ExportRecipe(recipe),
+            UserCommand::ExportShareCode => Self::ExportShareCode,
+            UserCommand::FetchRecipe {
+                url,
+                default_section,
+                options,
+            } => Self::FetchRecipe {
+                url,
+                default_section,
+                options,
+            },
+            UserCommand::ImportChecklist(path) => Self::ImportChecklist(path),
+            UserCommand::ImportFromJson { fresh } => Self::ImportFromJson { fresh },
+            UserCommand::ImportItemsCsv(path) => Self::ImportItemsCsv(path),
+            UserCommand::ImportPaprika(path) => Self::ImportPaprika(path),
+            UserCommand::ImportShareCode(code) => Self::ImportShareCode(code),
+            UserCommand::MigrationStatus => Self::MigrationStatus,
             UserCommand::Read(cmd) => Self::Read(cmd),
+            UserCommand::Restore(path) => Self::Restore(path),
+            UserCommand::Schema => Self::Schema,
             UserCommand::Update(cmd) => Self::Update(cmd),
         }
     }
@@ -0,0 +1,95 @@
+use std::str::FromStr;
+
+use api::ApiResponse;
+
+use crate::CliError;
+
+/// How responses are printed by the CLI.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Json,
+    #[default]
+    Text,
+}
+
+impl FromStr for OutputFormat {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "text" => Ok(Self::Text),
+            _ => Err(CliError::ParseInputError(format!(
+                "unknown output format '{s}', expected 'text' or 'json'"
+            ))),
+        }
+    }
+}
+
+impl OutputFormat {
+    pub fn format(&self, response: &ApiResponse) -> Result<String, CliError> {
+        match self {
+            Self::Json => Ok(serde_json::to_string(response)?),
+            Self::Text => Ok(response.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{item::Item, list::List, recipes::Recipe};
+
+    use super::*;
+
+    fn list_response() -> ApiResponse {
+        ApiResponse::List(
+            List::from_iter(vec![Item::new("eggs"), Item::new("milk")])
+                .with_recipes(vec![Recipe::new("pancakes")]),
+        )
+    }
+
+    #[test]
+    fn test_format_json() {
+        let output = OutputFormat::Json.format(&list_response()).unwrap();
+
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        insta::assert_json_snapshot!(value, @r###"
+        {
+          "List": {
+            "checklist": [],
+            "items": [
+              {
+                "name": "eggs",
+                "recipes": null,
+                "section": null
+              },
+              {
+                "name": "milk",
+                "recipes": null,
+                "section": null
+              }
+            ],
+            "recipes": [
+              "pancakes"
+            ]
+          }
+        }
+        "###);
+    }
+
+    #[test]
+    fn test_format_text() {
+        let output = OutputFormat::default().format(&list_response()).unwrap();
+        insta::assert_display_snapshot!(output.trim(), @r###"
+        eggs
+        milk
+        "###);
+    }
+
+    #[test]
+    fn test_parse_output_format() {
+        assert_eq!("json".parse::<OutputFormat>().unwrap(), OutputFormat::Json);
+        assert_eq!("text".parse::<OutputFormat>().unwrap(), OutputFormat::Text);
+        assert!("yaml".parse::<OutputFormat>().is_err());
+    }
+}
@@ -1,25 +1,143 @@
-use crate::{cli, command::UserCommand, CliError};
-use api::{Api, ApiError};
+use std::io::Write;
+
+use crate::{cli, command::UserCommand, output::OutputFormat, CliError};
+use api::{Api, ApiDispatch, ApiError, StoreConfig, StoreType};
+use clap::ArgMatches;
 use tracing::instrument;
 
 #[instrument]
 pub async fn run() -> Result<(), CliError> {
-    let matches = cli().get_matches();
-
-    let api = Api::init(
-        matches
-            .get_one::<String>("database")
-            .expect("'database' has a default setting")
-            .parse()
-            .map_err(ApiError::from)?,
-    )
+    run_with(cli().get_matches(), &mut std::io::stdout()).await
+}
+
+/// Runs the command parsed into `matches`, writing the response to `out`.
+#[instrument(skip(out))]
+pub async fn run_with(matches: ArgMatches, out: &mut impl Write) -> Result<(), CliError> {
+    let store: StoreType = matches
+        .get_one::<String>("database")
+        .expect("'database' has a default setting")
+        .parse()
+        .map_err(ApiError::from)?;
+    let api = Api::init_with_config(StoreConfig {
+        seed_sections: matches.get_flag("seed-sections"),
+        ..StoreConfig::from(store)
+    })
     .await?;
 
+    run_on(&api, matches, out).await
+}
+
+/// Runs the command parsed into `matches` against `api`, writing the
+/// response to `out`.
+async fn run_on(
+    api: &ApiDispatch,
+    matches: ArgMatches,
+    out: &mut impl Write,
+) -> Result<(), CliError> {
+    let output: OutputFormat = matches
+        .get_one::<String>("output")
+        .expect("'output' has a default setting")
+        .parse()?;
+
     let command: UserCommand = matches.try_into()?;
 
     let response = api.dispatch(command.into()).await?;
 
-    println!("{response}");
+    writeln!(out, "{}", output.format(&response)?)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_with_captures_output() {
+        let matches = cli()
+            .try_get_matches_from(["gust", "--database", "sqlite-inmem", "read", "is-empty"])
+            .unwrap();
+        let mut out = Vec::new();
+
+        run_with(matches, &mut out).await.unwrap();
+
+        insta::assert_display_snapshot!(String::from_utf8(out).unwrap().trim(), @"your library is empty, import or add some items to get started");
+    }
+
+    #[tokio::test]
+    async fn test_run_on_captures_seeded_list() {
+        let api = Api::init(StoreType::SqliteInMem).await.unwrap();
+        let seed: [&[&str]; 4] = [
+            &["gust", "add", "--item", "milk", "--section", "dairy"],
+            &["gust", "add", "--item", "bread", "--section", "pantry"],
+            &["gust", "add", "list", "--item", "milk"],
+            &["gust", "add", "list", "--item", "bread"],
+        ];
+        for args in seed {
+            let matches = cli().try_get_matches_from(args).unwrap();
+            run_on(&api, matches, &mut std::io::sink()).await.unwrap();
+        }
+        let matches = cli()
+            .try_get_matches_from(["gust", "read", "list"])
+            .unwrap();
+        let mut out = Vec::new();
+
+        run_on(&api, matches, &mut out).await.unwrap();
+
+        insta::assert_display_snapshot!(String::from_utf8(out).unwrap().trim(), @r###"
+        milk
+        bread
+        "###);
+    }
+
+    #[tokio::test]
+    async fn test_seed_sections() {
+        let matches = cli()
+            .try_get_matches_from([
+                "gust",
+                "--database",
+                "sqlite-inmem",
+                "--seed-sections",
+                "read",
+                "sections",
+            ])
+            .unwrap();
+        let mut out = Vec::new();
+
+        run_with(matches, &mut out).await.unwrap();
+
+        insta::assert_display_snapshot!(String::from_utf8(out).unwrap().trim(), @r###"
+        fresh
+        pantry
+        protein
+        dairy
+        freezer
+        "###);
+    }
+
+    #[tokio::test]
+    async fn test_delete_list_item() {
+        let matches = cli()
+            .try_get_matches_from([
+                "gust",
+                "--database",
+                "sqlite-inmem",
+                "delete",
+                "list",
+                "--item",
+                "milk",
+            ])
+            .unwrap();
+        let mut out = Vec::new();
+
+        run_with(matches, &mut out).await.unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap().trim(),
+            "deleted from list: \nmilk"
+        );
+        assert!(cli()
+            .try_get_matches_from(["gust", "delete", "list"])
+            .is_err());
+    }
+}
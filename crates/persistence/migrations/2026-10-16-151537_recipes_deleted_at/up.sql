@@ -0,0 +1,2 @@
+-- Soft-deleted recipes are kept in a recycle bin until restored
+ALTER TABLE recipes ADD COLUMN deleted_at TIMESTAMP;
@@ -0,0 +1,6 @@
+-- Recipes fetched from the web, so a recent fetch of the same URL can be reused
+CREATE TABLE fetched_recipes (
+    url TEXT PRIMARY KEY NOT NULL,
+    recipe TEXT NOT NULL,
+    fetched_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,8 @@
+-- Where a section comes in a walk through the store, so the list can follow it
+ALTER TABLE sections ADD COLUMN position INTEGER;
+
+UPDATE sections SET position = 1 WHERE name = 'fresh';
+UPDATE sections SET position = 2 WHERE name = 'pantry';
+UPDATE sections SET position = 3 WHERE name = 'protein';
+UPDATE sections SET position = 4 WHERE name = 'dairy';
+UPDATE sections SET position = 5 WHERE name = 'freezer';
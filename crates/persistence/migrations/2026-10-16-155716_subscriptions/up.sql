@@ -0,0 +1,6 @@
+-- Items that go back on the list every time it's refreshed
+CREATE TABLE subscriptions (
+    id INTEGER NOT NULL,
+    PRIMARY KEY (id),
+    FOREIGN KEY (id) REFERENCES items (id)
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE list DROP COLUMN manual;
+ALTER TABLE items_recipes DROP COLUMN quantity;
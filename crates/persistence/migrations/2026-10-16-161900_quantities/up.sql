@@ -0,0 +1,5 @@
+-- How many of an item a recipe calls for
+ALTER TABLE items_recipes ADD COLUMN quantity INTEGER NOT NULL DEFAULT 1;
+
+-- Whether an item was put on the list by hand, as opposed to by a recipe
+ALTER TABLE list ADD COLUMN manual BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- Where a fetched recipe was imported from, so it isn't imported twice
+ALTER TABLE recipes ADD COLUMN source_url TEXT;
@@ -0,0 +1,2 @@
+-- How many times an item has been put on the list
+ALTER TABLE items ADD COLUMN times_listed INTEGER NOT NULL DEFAULT 0;
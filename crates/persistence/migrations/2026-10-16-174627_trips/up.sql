@@ -0,0 +1,14 @@
+-- Completed shopping trips, each with the items that were on the list
+CREATE TABLE trips (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    completed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Items are kept by name, so a trip reads the same after items are renamed
+-- or deleted
+CREATE TABLE trip_items (
+    trip_id INTEGER NOT NULL,
+    item TEXT NOT NULL,
+    PRIMARY KEY (trip_id, item),
+    FOREIGN KEY (trip_id) REFERENCES trips (id)
+);
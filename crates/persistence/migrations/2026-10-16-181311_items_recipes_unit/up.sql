@@ -0,0 +1,2 @@
+-- The unit a recipe's quantity of an item is measured in, e.g. 'tbsp'
+ALTER TABLE items_recipes ADD COLUMN unit TEXT;
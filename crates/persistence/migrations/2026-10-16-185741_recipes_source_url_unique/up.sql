@@ -0,0 +1,9 @@
+-- A URL belongs to one recipe at most, so looking a recipe up by URL is
+-- unambiguous. Where two recipes share a URL, the newest keeps it.
+UPDATE recipes SET source_url = NULL
+WHERE source_url IS NOT NULL
+  AND id NOT IN (
+    SELECT MAX(id) FROM recipes WHERE source_url IS NOT NULL GROUP BY source_url
+  );
+
+CREATE UNIQUE INDEX recipes_source_url ON recipes (source_url);
@@ -0,0 +1,15 @@
+-- Which list recipe put each item on the list, so refreshing or removing a
+-- recipe takes off only the items it added
+CREATE TABLE list_items_recipes (
+    item_id INTEGER NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    PRIMARY KEY (item_id, recipe_id),
+    FOREIGN KEY (item_id) REFERENCES items (id),
+    FOREIGN KEY (recipe_id) REFERENCES recipes (id)
+);
+
+INSERT INTO list_items_recipes (item_id, recipe_id)
+SELECT items_recipes.item_id, items_recipes.recipe_id
+FROM items_recipes
+JOIN list ON list.id = items_recipes.item_id
+JOIN list_recipes ON list_recipes.id = items_recipes.recipe_id;
@@ -0,0 +1,15 @@
+-- This file should undo anything in `up.sql`
+CREATE TABLE fetched_recipes_by_name (
+    url TEXT PRIMARY KEY NOT NULL,
+    recipe TEXT NOT NULL,
+    fetched_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO fetched_recipes_by_name (url, recipe, fetched_at)
+SELECT fetched_recipes.url, recipes.name, fetched_recipes.fetched_at
+FROM fetched_recipes
+JOIN recipes ON recipes.id = fetched_recipes.recipe_id
+ORDER BY fetched_recipes.rowid;
+
+DROP TABLE fetched_recipes;
+ALTER TABLE fetched_recipes_by_name RENAME TO fetched_recipes;
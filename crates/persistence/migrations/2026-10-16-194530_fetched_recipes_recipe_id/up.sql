@@ -0,0 +1,26 @@
+-- Cached fetches refer to their recipe by id, so renaming the recipe keeps
+-- them, and hold the ingredients the fetch found
+CREATE TABLE fetched_recipes_by_id (
+    url TEXT PRIMARY KEY NOT NULL,
+    recipe_id INTEGER NOT NULL,
+    ingredients TEXT NOT NULL,
+    fetched_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (recipe_id) REFERENCES recipes (id)
+);
+
+INSERT INTO fetched_recipes_by_id (url, recipe_id, ingredients, fetched_at)
+SELECT fetched_recipes.url,
+    recipes.id,
+    (
+        SELECT json_group_array(items.name)
+        FROM items_recipes
+        JOIN items ON items.id = items_recipes.item_id
+        WHERE items_recipes.recipe_id = recipes.id
+    ),
+    fetched_recipes.fetched_at
+FROM fetched_recipes
+JOIN recipes ON recipes.name = fetched_recipes.recipe
+ORDER BY fetched_recipes.rowid;
+
+DROP TABLE fetched_recipes;
+ALTER TABLE fetched_recipes_by_id RENAME TO fetched_recipes;
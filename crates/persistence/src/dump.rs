@@ -0,0 +1,142 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use diesel::prelude::*;
+
+use crate::schema::{
+    fetched_recipes, items, items_recipes, items_sections, list, list_items_recipes, recipes,
+    sections, trip_items, trips,
+};
+
+/// Every row of every table, ids included, for backing up a database and
+/// restoring it exactly as it was.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct DatabaseDump {
+    pub items: Vec<ItemRow>,
+    pub recipes: Vec<RecipeRow>,
+    pub sections: Vec<SectionRow>,
+    pub items_recipes: Vec<ItemRecipeRow>,
+    pub items_sections: Vec<ItemSectionRow>,
+    pub list: Vec<ListRow>,
+    pub list_recipes: Vec<i32>,
+    pub checklist: Vec<i32>,
+    pub subscriptions: Vec<i32>,
+    pub fetched_recipes: Vec<FetchedRecipeRow>,
+    // Dumps taken before trips were archived have neither
+    #[serde(default)]
+    pub trips: Vec<TripRow>,
+    #[serde(default)]
+    pub trip_items: Vec<TripItemRow>,
+    // Nor do those taken before the list recorded which recipe added an item
+    #[serde(default)]
+    pub list_items_recipes: Vec<ListItemRecipeRow>,
+}
+
+impl DatabaseDump {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a database dump always serializes to JSON")
+    }
+
+    pub fn from_json_str(s: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, JsonSchema, Queryable, Selectable, Insertable, Debug, Clone, PartialEq,
+)]
+#[diesel(table_name = items)]
+pub struct ItemRow {
+    pub id: i32,
+    pub name: String,
+    pub times_listed: i32,
+}
+
+#[derive(
+    Serialize, Deserialize, JsonSchema, Queryable, Selectable, Insertable, Debug, Clone, PartialEq,
+)]
+#[diesel(table_name = recipes)]
+pub struct RecipeRow {
+    pub id: i32,
+    pub name: String,
+    pub deleted_at: Option<String>,
+    pub source_url: Option<String>,
+}
+
+#[derive(
+    Serialize, Deserialize, JsonSchema, Queryable, Selectable, Insertable, Debug, Clone, PartialEq,
+)]
+#[diesel(table_name = sections)]
+pub struct SectionRow {
+    pub id: i32,
+    pub name: String,
+    pub position: Option<i32>,
+}
+
+#[derive(
+    Serialize, Deserialize, JsonSchema, Queryable, Selectable, Insertable, Debug, Clone, PartialEq,
+)]
+#[diesel(table_name = items_recipes)]
+pub struct ItemRecipeRow {
+    pub item_id: i32,
+    pub recipe_id: i32,
+    pub quantity: i32,
+    #[serde(default)]
+    pub unit: Option<String>,
+}
+
+#[derive(
+    Serialize, Deserialize, JsonSchema, Queryable, Selectable, Insertable, Debug, Clone, PartialEq,
+)]
+#[diesel(table_name = items_sections)]
+pub struct ItemSectionRow {
+    pub item_id: i32,
+    pub section_id: i32,
+}
+
+#[derive(
+    Serialize, Deserialize, JsonSchema, Queryable, Selectable, Insertable, Debug, Clone, PartialEq,
+)]
+#[diesel(table_name = list)]
+pub struct ListRow {
+    pub id: i32,
+    pub manual: bool,
+}
+
+#[derive(
+    Serialize, Deserialize, JsonSchema, Queryable, Selectable, Insertable, Debug, Clone, PartialEq,
+)]
+#[diesel(table_name = list_items_recipes)]
+pub struct ListItemRecipeRow {
+    pub item_id: i32,
+    pub recipe_id: i32,
+}
+
+#[derive(
+    Serialize, Deserialize, JsonSchema, Queryable, Selectable, Insertable, Debug, Clone, PartialEq,
+)]
+#[diesel(table_name = fetched_recipes)]
+pub struct FetchedRecipeRow {
+    pub url: String,
+    pub recipe_id: i32,
+    pub ingredients: String,
+    pub fetched_at: String,
+}
+
+#[derive(
+    Serialize, Deserialize, JsonSchema, Queryable, Selectable, Insertable, Debug, Clone, PartialEq,
+)]
+#[diesel(table_name = trips)]
+pub struct TripRow {
+    pub id: i32,
+    pub completed_at: String,
+}
+
+#[derive(
+    Serialize, Deserialize, JsonSchema, Queryable, Selectable, Insertable, Debug, Clone, PartialEq,
+)]
+#[diesel(table_name = trip_items)]
+pub struct TripItemRow {
+    pub trip_id: i32,
+    pub item: String,
+}
@@ -1,9 +1,12 @@
 use std::{
+    fmt,
     fs::{self},
     path::PathBuf,
 };
 
 use common::{items::Items, list::List, load::Load};
+use schemars::JsonSchema;
+use serde::Serialize;
 
 use crate::store::StoreError;
 
@@ -26,6 +29,13 @@ impl Default for ImportStore {
 }
 
 impl ImportStore {
+    pub fn new(items: impl Into<PathBuf>, list: impl Into<PathBuf>) -> Self {
+        Self {
+            items: items.into(),
+            list: list.into(),
+        }
+    }
+
     pub fn items(&self) -> Result<Items, StoreError> {
         Ok(Items::from_json(&self.items)?)
     }
@@ -44,3 +54,45 @@ impl ImportStore {
         Ok(fs::write(&self.list, s)?)
     }
 }
+
+/// How many rows an import added to each table.
+#[derive(Serialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct MigrationSummary {
+    pub(crate) items: usize,
+    pub(crate) recipes: usize,
+    pub(crate) sections: usize,
+    pub(crate) item_recipe_links: usize,
+    pub(crate) item_section_links: usize,
+}
+
+impl MigrationSummary {
+    pub fn items(&self) -> usize {
+        self.items
+    }
+
+    pub fn recipes(&self) -> usize {
+        self.recipes
+    }
+
+    pub fn sections(&self) -> usize {
+        self.sections
+    }
+
+    pub fn item_recipe_links(&self) -> usize {
+        self.item_recipe_links
+    }
+
+    pub fn item_section_links(&self) -> usize {
+        self.item_section_links
+    }
+}
+
+impl fmt::Display for MigrationSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "items: {}", self.items)?;
+        writeln!(f, "recipes: {}", self.recipes)?;
+        writeln!(f, "sections: {}", self.sections)?;
+        writeln!(f, "recipe ingredients: {}", self.item_recipe_links)?;
+        write!(f, "item sections: {}", self.item_section_links)
+    }
+}
@@ -1,5 +1,7 @@
+pub mod dump;
 pub mod import_store;
 pub mod models;
 pub mod schema;
 pub mod sqlite;
+pub mod stats;
 pub mod store;
@@ -1,5 +1,6 @@
 use crate::schema::{
     checklist, items, items_recipes, items_sections, list, list_recipes, recipes, sections,
+    subscriptions,
 };
 use common::recipes::Recipe;
 use diesel::prelude::*;
@@ -8,7 +9,7 @@ pub trait ItemInfo {
     fn name(&self) -> &str;
 }
 
-#[derive(Queryable)]
+#[derive(Queryable, Selectable)]
 #[diesel(table_name = items)]
 pub struct Item {
     pub id: i32,
@@ -146,3 +147,9 @@ pub struct ItemSection {
     pub item_id: i32,
     pub section_id: i32,
 }
+
+#[derive(Insertable)]
+#[diesel(table_name = subscriptions)]
+pub struct NewSubscription {
+    pub id: i32,
+}
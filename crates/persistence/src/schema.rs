@@ -6,10 +6,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    fetched_recipes (url) {
+        url -> Text,
+        recipe_id -> Integer,
+        ingredients -> Text,
+        fetched_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     items (id) {
         id -> Integer,
         name -> Text,
+        times_listed -> Integer,
     }
 }
 
@@ -17,6 +27,8 @@ diesel::table! {
     items_recipes (item_id, recipe_id) {
         item_id -> Integer,
         recipe_id -> Integer,
+        quantity -> Integer,
+        unit -> Nullable<Text>,
     }
 }
 
@@ -30,6 +42,14 @@ diesel::table! {
 diesel::table! {
     list (id) {
         id -> Integer,
+        manual -> Bool,
+    }
+}
+
+diesel::table! {
+    list_items_recipes (item_id, recipe_id) {
+        item_id -> Integer,
+        recipe_id -> Integer,
     }
 }
 
@@ -43,6 +63,8 @@ diesel::table! {
     recipes (id) {
         id -> Integer,
         name -> Text,
+        deleted_at -> Nullable<Timestamp>,
+        source_url -> Nullable<Text>,
     }
 }
 
@@ -50,24 +72,55 @@ diesel::table! {
     sections (id) {
         id -> Integer,
         name -> Text,
+        position -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    subscriptions (id) {
+        id -> Integer,
+    }
+}
+
+diesel::table! {
+    trip_items (trip_id, item) {
+        trip_id -> Integer,
+        item -> Text,
+    }
+}
+
+diesel::table! {
+    trips (id) {
+        id -> Integer,
+        completed_at -> Timestamp,
     }
 }
 
 diesel::joinable!(checklist -> items (id));
+diesel::joinable!(fetched_recipes -> recipes (recipe_id));
 diesel::joinable!(items_recipes -> items (item_id));
 diesel::joinable!(items_recipes -> recipes (recipe_id));
 diesel::joinable!(items_sections -> items (item_id));
 diesel::joinable!(items_sections -> sections (section_id));
 diesel::joinable!(list -> items (id));
+diesel::joinable!(list_items_recipes -> items (item_id));
+diesel::joinable!(list_items_recipes -> recipes (recipe_id));
 diesel::joinable!(list_recipes -> recipes (id));
+diesel::joinable!(subscriptions -> items (id));
+diesel::joinable!(trip_items -> trips (trip_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     checklist,
+    fetched_recipes,
     items,
     items_recipes,
     items_sections,
     list,
+    list_items_recipes,
     list_recipes,
     recipes,
     sections,
+    subscriptions,
+    trip_items,
+    trips,
 );
@@ -1,9 +1,13 @@
 use std::{env, ops::Deref};
 
-use diesel::{r2d2::ConnectionManager, SqliteConnection};
+use diesel::{
+    connection::{AnsiTransactionManager, TransactionManager},
+    r2d2::ConnectionManager,
+    SqliteConnection,
+};
 use r2d2::Pool;
 
-use crate::store::StoreError;
+use crate::store::{StoreError, BUSY_BACKOFF, BUSY_RETRIES};
 
 pub struct DbUri(String);
 
@@ -68,3 +72,51 @@ impl Connection for DatabaseConnector {
         )
     }
 }
+
+/// Write transactions that wait out another writer's lock.
+pub(crate) trait WriteTransaction {
+    /// Runs `f` in an immediate transaction, running it again in a fresh
+    /// transaction, after a growing backoff, while another connection holds
+    /// the lock. Gives up with [`StoreError::Busy`] after [`BUSY_RETRIES`]
+    /// retries.
+    fn write_transaction<T, F>(&mut self, f: F) -> Result<T, StoreError>
+    where
+        F: FnMut(&mut SqliteConnection) -> Result<T, StoreError>;
+}
+
+impl WriteTransaction for SqliteConnection {
+    fn write_transaction<T, F>(&mut self, mut f: F) -> Result<T, StoreError>
+    where
+        F: FnMut(&mut SqliteConnection) -> Result<T, StoreError>,
+    {
+        let mut backoff = BUSY_BACKOFF;
+        for _ in 0..BUSY_RETRIES {
+            match self.immediate_transaction(&mut f) {
+                Err(e) if e.is_locked() => {
+                    roll_back_open_transaction(self)?;
+                    std::thread::sleep(backoff);
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+        match self.immediate_transaction(f) {
+            Err(e) if e.is_locked() => {
+                roll_back_open_transaction(self)?;
+                Err(StoreError::Busy)
+            }
+            result => result,
+        }
+    }
+}
+
+/// A `COMMIT` turned away while a reader holds the lock leaves the
+/// transaction open, so it's rolled back before trying again.
+fn roll_back_open_transaction(connection: &mut SqliteConnection) -> Result<(), StoreError> {
+    let depth =
+        AnsiTransactionManager::transaction_manager_status_mut(connection).transaction_depth()?;
+    if depth.is_some() {
+        AnsiTransactionManager::rollback_transaction(connection)?;
+    }
+    Ok(())
+}
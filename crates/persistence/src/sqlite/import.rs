@@ -1,113 +1,156 @@
+use std::collections::{BTreeSet, HashMap};
+
 use common::{items::Items, section::SECTIONS};
-use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper, SqliteConnection};
 
 use crate::{
-    models::{self, NewItem, NewItemRecipe, NewItemSection, NewRecipe, NewSection},
+    import_store::MigrationSummary,
+    models::{NewItem, NewItemRecipe, NewItemSection, NewRecipe, Section},
     schema,
     store::StoreError,
 };
 
-pub fn import_sections(connection: &mut SqliteConnection) -> Result<(), StoreError> {
+/// Empties every table, so an import that follows leaves only imported data.
+pub fn clear_tables(connection: &mut SqliteConnection) -> Result<(), StoreError> {
+    diesel::delete(schema::items_recipes::table).execute(connection)?;
+    diesel::delete(schema::items_sections::table).execute(connection)?;
+    diesel::delete(schema::list::table).execute(connection)?;
+    diesel::delete(schema::list_items_recipes::table).execute(connection)?;
+    diesel::delete(schema::list_recipes::table).execute(connection)?;
+    diesel::delete(schema::checklist::table).execute(connection)?;
+    diesel::delete(schema::items::table).execute(connection)?;
+    diesel::delete(schema::recipes::table).execute(connection)?;
+    diesel::delete(schema::sections::table).execute(connection)?;
+    Ok(())
+}
+
+/// Adds the default sections, returning how many were new.
+pub fn import_sections(connection: &mut SqliteConnection) -> Result<usize, StoreError> {
     use crate::schema::sections;
 
-    let sections = SECTIONS;
+    // The default sections are listed in the order they're walked past
+    let new_sections: Vec<_> = (1..)
+        .zip(SECTIONS)
+        .map(|(position, name)| (sections::name.eq(name), sections::position.eq(position)))
+        .collect();
+    Ok(diesel::insert_or_ignore_into(sections::table)
+        .values(&new_sections)
+        .execute(connection)?)
+}
+
+/// Rows per multi-row insert, keeping each statement well under SQLite's
+/// limit on bound parameters.
+pub(super) const BATCH_SIZE: usize = 400;
 
-    for name in sections {
-        let section = NewSection { name };
+/// Adds any section the items refer to that isn't in the sections table yet,
+/// so every item's section resolves to an id. Returns how many were added.
+fn repair_sections(connection: &mut SqliteConnection, items: &Items) -> Result<usize, StoreError> {
+    use crate::schema::sections;
 
-        diesel::insert_into(sections::table)
-            .values(&section)
-            .on_conflict_do_nothing()
+    let names: BTreeSet<&str> = items
+        .collection_iter()
+        .filter_map(|item| item.section())
+        .map(|section| section.as_str())
+        .collect();
+    let new_sections: Vec<_> = names
+        .into_iter()
+        .map(|name| sections::name.eq(name))
+        .collect();
+    let mut added = 0;
+    for chunk in new_sections.chunks(BATCH_SIZE) {
+        added += diesel::insert_or_ignore_into(sections::table)
+            .values(chunk)
             .execute(connection)?;
     }
-
-    Ok(())
+    Ok(added)
 }
 
-pub fn import_items(connection: &mut SqliteConnection, items: Items) -> Result<(), StoreError> {
-    let items_table = schema::items::table;
-    let recipes_table = schema::recipes::table;
-    let sections_table = schema::sections::table;
-
-    for item in items.collection_iter() {
-        // add the item to the item table
-        let new_item = NewItem {
+/// Imports the items with a handful of multi-row inserts: one pass each for
+/// items, recipes, and the links between them and their sections.
+///
+/// The summary counts the rows that were new; its `sections` counts only the
+/// sections added because an item named one that didn't exist yet.
+pub fn import_items(
+    connection: &mut SqliteConnection,
+    items: Items,
+) -> Result<MigrationSummary, StoreError> {
+    use crate::schema::{items, recipes, sections};
+
+    let mut summary = MigrationSummary::default();
+
+    let new_items: Vec<NewItem> = items
+        .collection_iter()
+        .map(|item| NewItem {
             name: item.name().as_str(),
-        };
-
-        diesel::insert_into(items_table)
-            .values(&new_item)
-            .on_conflict_do_nothing()
+        })
+        .collect();
+    for chunk in new_items.chunks(BATCH_SIZE) {
+        summary.items += diesel::insert_or_ignore_into(items::table)
+            .values(chunk)
             .execute(connection)?;
+    }
 
-        // get the item's item_id
-        let results = items_table
-            .filter(schema::items::dsl::name.eq(item.name().to_string()))
-            .load::<models::Item>(connection)?;
-
-        assert_eq!(results.len(), 1);
-
-        let item_id = results[0].id;
-
-        if let Some(item_recipes) = item.recipes() {
-            // log the item_id in items_recipes
-            for recipe in item_recipes {
-                let new_recipe = NewRecipe {
-                    name: &recipe.to_string(),
-                };
-
-                diesel::insert_into(schema::recipes::table)
-                    .values(&new_recipe)
-                    .on_conflict_do_nothing()
-                    .execute(connection)
-                    .unwrap_or_else(|_| panic!("Error inserting recipe {recipe}"));
-
-                let results = recipes_table
-                    .filter(schema::recipes::dsl::name.eq(recipe.to_string()))
-                    .load::<models::RecipeModel>(connection)?;
-
-                assert_eq!(results.len(), 1);
-
-                let recipe_id = results[0].id;
+    let recipe_names: BTreeSet<String> = items
+        .collection_iter()
+        .filter_map(|item| item.recipes())
+        .flatten()
+        .map(|recipe| recipe.to_string())
+        .collect();
+    let new_recipes: Vec<NewRecipe> = recipe_names.iter().map(|name| NewRecipe { name }).collect();
+    for chunk in new_recipes.chunks(BATCH_SIZE) {
+        summary.recipes += diesel::insert_or_ignore_into(recipes::table)
+            .values(chunk)
+            .execute(connection)?;
+    }
 
-                let new_item_recipe = NewItemRecipe { item_id, recipe_id };
+    summary.sections = repair_sections(connection, &items)?;
+
+    let item_ids: HashMap<String, i32> = items::table
+        .select((items::name, items::id))
+        .load(connection)?
+        .into_iter()
+        .collect();
+    let recipe_ids: HashMap<String, i32> = recipes::table
+        .select((recipes::name, recipes::id))
+        .load(connection)?
+        .into_iter()
+        .collect();
+    let section_ids: HashMap<String, i32> = sections::table
+        .select(Section::as_select())
+        .load(connection)?
+        .into_iter()
+        .map(|section| (section.name, section.id))
+        .collect();
+
+    let mut new_item_recipes = Vec::new();
+    let mut new_item_sections = Vec::new();
+    for item in items.collection_iter() {
+        let item_id = item_ids[item.name().as_str()];
 
-                diesel::insert_into(schema::items_recipes::table)
-                    .values(&new_item_recipe)
-                    .on_conflict_do_nothing()
-                    .execute(connection)
-                    .unwrap_or_else(|_| {
-                        panic!("Error transferring item_recipe for {}", item.name())
-                    });
-            }
+        for recipe in item.recipes().into_iter().flatten() {
+            new_item_recipes.push(NewItemRecipe {
+                item_id,
+                recipe_id: recipe_ids[&recipe.to_string()],
+            });
         }
 
-        if let Some(item_section) = item.section() {
-            // log the item_id in items_sections
-            let results = sections_table
-                .filter(schema::sections::dsl::name.eq(item_section.to_string()))
-                .load::<models::Section>(connection)?;
-
-            assert_eq!(results.len(), 1);
-
-            for result in results {
-                let section_id = result.id;
-
-                let new_item_section = NewItemSection {
-                    item_id,
-                    section_id,
-                };
-
-                diesel::insert_into(schema::items_sections::table)
-                    .values(&new_item_section)
-                    .on_conflict_do_nothing()
-                    .execute(connection)
-                    .unwrap_or_else(|_| {
-                        panic!("Error transferring item_section for {}", item.name())
-                    });
-            }
+        if let Some(section) = item.section() {
+            new_item_sections.push(NewItemSection {
+                item_id,
+                section_id: section_ids[section.as_str()],
+            });
         }
     }
+    for chunk in new_item_recipes.chunks(BATCH_SIZE) {
+        summary.item_recipe_links += diesel::insert_or_ignore_into(schema::items_recipes::table)
+            .values(chunk)
+            .execute(connection)?;
+    }
+    for chunk in new_item_sections.chunks(BATCH_SIZE) {
+        summary.item_section_links += diesel::insert_or_ignore_into(schema::items_sections::table)
+            .values(chunk)
+            .execute(connection)?;
+    }
 
-    Ok(())
+    Ok(summary)
 }
@@ -14,3 +14,27 @@ pub fn run_migrations(connection: &mut impl MigrationHarness<Sqlite>) -> Result<
 
     Ok(())
 }
+
+/// Versions of the migrations already run against the database, oldest first.
+pub fn applied_migrations(
+    connection: &mut impl MigrationHarness<Sqlite>,
+) -> Result<Vec<String>, StoreError> {
+    let mut versions = connection
+        .applied_migrations()?
+        .into_iter()
+        .map(|version| version.to_string())
+        .collect::<Vec<_>>();
+    versions.sort();
+    Ok(versions)
+}
+
+/// Versions of the embedded migrations not yet run against the database.
+pub fn pending_migrations(
+    connection: &mut impl MigrationHarness<Sqlite>,
+) -> Result<Vec<String>, StoreError> {
+    Ok(connection
+        .pending_migrations(MIGRATIONS)?
+        .iter()
+        .map(|migration| migration.name().version().to_string())
+        .collect())
+}
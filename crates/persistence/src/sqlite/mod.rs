@@ -2,48 +2,152 @@ pub(crate) mod connection;
 mod import;
 mod migrations;
 
+pub use self::connection::{ConnectionPool, DbUri};
+
+#[cfg(feature = "fetch-limiter")]
+use common::fetcher::FetchLimiter;
 use common::{
+    commands::FetchOptions,
     export::{YamlSerializable, ITEMS_YAML_PATH, LIST_YAML_PATH},
+    fetcher::paprika,
     item::Name,
     items::Items,
-    list::List,
-    recipes::{Ingredients, Recipe},
+    list::{List, Trip, TripId},
+    recipes::{AddRecipeReport, Ingredients, Recipe, RecipeSort, RecipeStatus},
+};
+use diesel::{
+    dsl::{count, now, sql},
+    prelude::*,
+    r2d2::ConnectionManager,
+    sql_types::{Bool, Text},
+    upsert::excluded,
+    Connection as _, SqliteConnection,
 };
-use diesel::{prelude::*, r2d2::ConnectionManager, SqliteConnection};
 use r2d2::PooledConnection;
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::mpsc;
+use url::Url;
 
 use crate::{
-    import_store::ImportStore,
+    dump::{
+        DatabaseDump, FetchedRecipeRow, ItemRecipeRow, ItemRow, ItemSectionRow, ListItemRecipeRow,
+        ListRow, RecipeRow, SectionRow, TripItemRow, TripRow,
+    },
+    import_store::{ImportStore, MigrationSummary},
     models::{
-        self, Item, ItemInfo, NewChecklistItem, NewItem, NewItemRecipe, NewItemSection,
-        NewListItem, NewListRecipe, NewRecipe, NewSection, RecipeModel, Section,
+        Item, ItemInfo, NewChecklistItem, NewItem, NewItemRecipe, NewItemSection, NewListRecipe,
+        NewRecipe, NewSection, NewSubscription, RecipeModel, Section,
     },
     schema,
-    store::{Storage, StoreError, StoreResponse},
+    stats::DbStats,
+    store::{
+        Storage, StoreConfig, StoreError, StoreResponse, FETCH_CACHE_TTL, MAX_INGREDIENTS,
+        MAX_SUGGESTIONS, PREVIEW_INGREDIENTS,
+    },
 };
 
 use self::{
-    connection::{Connection, ConnectionPool, DatabaseConnector, DbUri},
-    import::{import_items, import_sections},
-    migrations::run_migrations,
+    connection::{Connection, DatabaseConnector, WriteTransaction},
+    import::{clear_tables, import_items, import_sections, BATCH_SIZE},
+    migrations::{applied_migrations, pending_migrations, run_migrations},
 };
 
 #[derive(Clone)]
 pub struct SqliteStore {
     pool: ConnectionPool,
+    read_pool: ConnectionPool,
+    max_ingredients: usize,
+    #[cfg(feature = "fetch-limiter")]
+    fetch_limiter: FetchLimiter,
 }
 
 impl SqliteStore {
     pub async fn new(db_uri: DbUri) -> Result<Self, StoreError> {
         let pool = DatabaseConnector::new(db_uri).try_connect().await?;
-        let store = Self { pool };
+        Self::from_pool(pool)
+    }
+
+    /// Builds a store whose queries go to `read_uri`, e.g. a read-replica,
+    /// while writes and migrations go to `write_uri`.
+    pub async fn from_config(config: StoreConfig) -> Result<Self, StoreError> {
+        let StoreConfig {
+            write_uri,
+            read_uri,
+            seed_sections,
+            max_ingredients,
+            #[cfg(feature = "fetch-limiter")]
+            max_fetches,
+        } = config;
+        let mut store = Self::new(write_uri)
+            .await?
+            .with_max_ingredients(max_ingredients);
+        #[cfg(feature = "fetch-limiter")]
+        {
+            store = store.with_fetch_limiter(FetchLimiter::new(max_fetches));
+        }
+        if seed_sections {
+            store.seed_default_sections().await?;
+        }
+        if let Some(read_uri) = read_uri {
+            store = store.with_read_pool(DatabaseConnector::new(read_uri).try_connect().await?);
+        }
+        Ok(store)
+    }
+
+    /// Builds a store on top of a caller-provided connection pool, e.g. one
+    /// configured with a custom connection customizer, and runs migrations.
+    pub fn from_pool(pool: ConnectionPool) -> Result<Self, StoreError> {
+        let store = Self {
+            read_pool: pool.clone(),
+            pool,
+            max_ingredients: MAX_INGREDIENTS,
+            #[cfg(feature = "fetch-limiter")]
+            fetch_limiter: FetchLimiter::shared(),
+        };
         store.run_migrations()?;
         Ok(store)
     }
 
+    /// Sends queries to `read_pool` instead of the primary pool. Queries can
+    /// then miss writes the replica hasn't caught up with yet.
+    pub fn with_read_pool(mut self, read_pool: ConnectionPool) -> Self {
+        self.read_pool = read_pool;
+        self
+    }
+
+    /// Refuses recipes with more than `max_ingredients` ingredients.
+    pub fn with_max_ingredients(mut self, max_ingredients: usize) -> Self {
+        self.max_ingredients = max_ingredients;
+        self
+    }
+
+    /// Bounds the store's recipe fetches with `fetch_limiter` instead of the
+    /// limiter shared by every fetcher.
+    #[cfg(feature = "fetch-limiter")]
+    pub fn with_fetch_limiter(mut self, fetch_limiter: FetchLimiter) -> Self {
+        self.fetch_limiter = fetch_limiter;
+        self
+    }
+
+    fn check_ingredient_count(
+        &self,
+        recipe: &Recipe,
+        ingredients: &Ingredients,
+    ) -> Result<(), StoreError> {
+        let count = ingredients.iter().count();
+        if count > self.max_ingredients {
+            return Err(StoreError::TooManyIngredients(
+                recipe.to_string(),
+                count,
+                self.max_ingredients,
+            ));
+        }
+        Ok(())
+    }
+
     pub(crate) fn run_migrations(&self) -> Result<(), StoreError> {
         let mut connection = self.connection()?;
-        connection.immediate_transaction(run_migrations)
+        connection.write_transaction(run_migrations)
     }
 
     pub(crate) fn connection(
@@ -52,28 +156,179 @@ impl SqliteStore {
         self.pool.get()
     }
 
+    pub(crate) fn read_connection(
+        &self,
+    ) -> Result<PooledConnection<ConnectionManager<SqliteConnection>>, r2d2::Error> {
+        self.read_pool.get()
+    }
+
+    /// Sends the rows `next_batch` reads to the returned channel, calling it
+    /// again until it comes back empty or the receiver is dropped. Each batch
+    /// is read in a transaction of its own, and no lock is held while a slow
+    /// reader catches up, so writes aren't kept waiting on the stream.
+    fn stream_batches<T, F>(&self, mut next_batch: F) -> mpsc::Receiver<Result<T, StoreError>>
+    where
+        T: Send + 'static,
+        F: FnMut(&mut SqliteConnection) -> Result<Vec<T>, StoreError> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(BATCH_SIZE);
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || loop {
+            let batch =
+                store
+                    .read_connection()
+                    .map_err(StoreError::from)
+                    .and_then(|mut connection| {
+                        connection.transaction(|connection| next_batch(connection))
+                    });
+            let batch = match batch {
+                Ok(batch) if batch.is_empty() => return,
+                Ok(batch) => batch,
+                Err(e) => {
+                    tx.blocking_send(Err(e)).ok();
+                    return;
+                }
+            };
+            for row in batch {
+                if tx.blocking_send(Ok(row)).is_err() {
+                    // Nobody's reading any more
+                    return;
+                }
+            }
+        });
+        rx
+    }
+
+    /// Puts the item on the list, counting it as listed once more if it
+    /// wasn't there already. With `manual`, an item already on the list is
+    /// marked as added by hand. Returns whether the item is new to the list.
+    fn insert_list_item(
+        connection: &mut SqliteConnection,
+        item_id: i32,
+        manual: bool,
+    ) -> Result<bool, StoreError> {
+        use crate::schema::{items, list};
+
+        let inserted = diesel::insert_or_ignore_into(list::table)
+            .values((list::id.eq(item_id), list::manual.eq(manual)))
+            .execute(connection)?
+            > 0;
+        if inserted {
+            diesel::update(items::table.find(item_id))
+                .set(items::times_listed.eq(items::times_listed + 1))
+                .execute(connection)?;
+        } else if manual {
+            diesel::update(list::table.find(item_id))
+                .set(list::manual.eq(true))
+                .execute(connection)?;
+        }
+        Ok(inserted)
+    }
+
+    /// Puts the recipe on the list along with its ingredients, recording
+    /// that the recipe brought each of them. With `refresh`, ingredients it
+    /// brought before are taken off first.
+    fn insert_list_recipe(
+        connection: &mut SqliteConnection,
+        recipe_id: i32,
+        refresh: bool,
+    ) -> Result<(), StoreError> {
+        use crate::schema::{items_recipes, list_items_recipes, list_recipes};
+
+        if refresh {
+            Self::remove_list_recipe_items(connection, recipe_id)?;
+        }
+        diesel::insert_into(list_recipes::table)
+            .values(NewListRecipe { id: recipe_id })
+            .on_conflict_do_nothing()
+            .execute(connection)?;
+        let ingredients = items_recipes::table
+            .filter(items_recipes::recipe_id.eq(recipe_id))
+            .select(items_recipes::item_id)
+            .load::<i32>(connection)?;
+        for item_id in ingredients {
+            Self::insert_list_item(connection, item_id, false)?;
+            diesel::insert_or_ignore_into(list_items_recipes::table)
+                .values((
+                    list_items_recipes::item_id.eq(item_id),
+                    list_items_recipes::recipe_id.eq(recipe_id),
+                ))
+                .execute(connection)?;
+        }
+        Ok(())
+    }
+
+    /// Up to `limit` recipes in `sort` order, skipping the first `offset`.
+    fn load_recipes_page(
+        connection: &mut SqliteConnection,
+        offset: usize,
+        limit: usize,
+        sort: RecipeSort,
+    ) -> Result<Vec<RecipeModel>, StoreError> {
+        use crate::schema::recipes;
+
+        let query = recipes::table
+            .filter(recipes::deleted_at.is_null())
+            .select(RecipeModel::as_select())
+            .offset(i64::try_from(offset).unwrap_or(i64::MAX))
+            .limit(i64::try_from(limit).unwrap_or(i64::MAX));
+        Ok(match sort {
+            RecipeSort::NameAsc => query.order_by(recipes::name.asc()).load(connection)?,
+            RecipeSort::NameDesc => query.order_by(recipes::name.desc()).load(connection)?,
+        })
+    }
+
+    /// Upserts the item in a single statement, returning its id.
     fn get_or_insert_item(
         connection: &mut SqliteConnection,
         name: &str,
     ) -> Result<i32, StoreError> {
-        diesel::insert_into(schema::items::table)
+        use crate::schema::items;
+
+        // The no-op update makes the conflicting row available to `RETURNING`
+        Ok(diesel::insert_into(items::table)
             .values(NewItem { name })
-            .on_conflict_do_nothing()
-            .execute(connection)?;
+            .on_conflict(items::name)
+            .do_update()
+            .set(items::name.eq(excluded(items::name)))
+            .returning(items::id)
+            .get_result(connection)?)
+    }
+
+    /// Returns the item's id and whether the item was newly inserted.
+    fn get_or_insert_item_created(
+        connection: &mut SqliteConnection,
+        name: &str,
+    ) -> Result<(i32, bool), StoreError> {
+        use crate::schema::items;
 
-        let item_query = schema::items::table.filter(schema::items::dsl::name.eq(name));
+        let inserted = diesel::insert_into(items::table)
+            .values(NewItem { name })
+            .on_conflict_do_nothing()
+            .returning(items::id)
+            .get_result(connection)
+            .optional()?;
 
-        Ok(item_query
-            .select(schema::items::dsl::id)
-            .first(connection)?)
+        match inserted {
+            Some(id) => Ok((id, true)),
+            None => Ok((
+                items::table
+                    .filter(items::name.eq(name))
+                    .select(items::id)
+                    .first(connection)?,
+                false,
+            )),
+        }
     }
 
+    /// The id of the recipe called `recipe`, unless it's been deleted.
     fn get_recipe_id(
         connection: &mut SqliteConnection,
         recipe: &str,
     ) -> Result<Option<i32>, StoreError> {
         Ok(schema::recipes::table
             .filter(schema::recipes::dsl::name.eq(recipe))
+            .filter(schema::recipes::dsl::deleted_at.is_null())
             .select(schema::recipes::dsl::id)
             .first(connection)
             .optional()?)
@@ -86,6 +341,7 @@ impl SqliteStore {
         match Self::get_recipe_id(connection, name)? {
             Some(id) => Ok(id),
             None => {
+                Self::purge_deleted_recipe(connection, name)?;
                 diesel::insert_into(schema::recipes::table)
                     .values(NewRecipe { name })
                     .on_conflict_do_nothing()
@@ -99,6 +355,164 @@ impl SqliteStore {
         }
     }
 
+    /// Records that recipe `id` was fetched from `url`, taking the URL from
+    /// any other recipe that had it.
+    fn set_source_url(
+        connection: &mut SqliteConnection,
+        id: i32,
+        url: &Url,
+    ) -> Result<(), StoreError> {
+        use schema::recipes;
+
+        diesel::update(
+            recipes::table
+                .filter(recipes::source_url.eq(url.as_str()))
+                .filter(recipes::id.ne(id)),
+        )
+        .set(recipes::source_url.eq(None::<String>))
+        .execute(connection)?;
+        diesel::update(recipes::table.find(id))
+            .set(recipes::source_url.eq(url.as_str()))
+            .execute(connection)?;
+        Ok(())
+    }
+
+    /// Permanently removes a deleted recipe called `name`, if there is one,
+    /// so a new recipe can take its name without inheriting its ingredients.
+    fn purge_deleted_recipe(
+        connection: &mut SqliteConnection,
+        name: &str,
+    ) -> Result<(), StoreError> {
+        use schema::{fetched_recipes, items_recipes, list_items_recipes, list_recipes, recipes};
+
+        let Some(id) = recipes::table
+            .filter(recipes::name.eq(name))
+            .filter(recipes::deleted_at.is_not_null())
+            .select(recipes::id)
+            .first::<i32>(connection)
+            .optional()?
+        else {
+            return Ok(());
+        };
+        diesel::delete(items_recipes::table.filter(items_recipes::recipe_id.eq(id)))
+            .execute(connection)?;
+        diesel::delete(list_items_recipes::table.filter(list_items_recipes::recipe_id.eq(id)))
+            .execute(connection)?;
+        diesel::delete(list_recipes::table.find(id)).execute(connection)?;
+        diesel::delete(fetched_recipes::table.filter(fetched_recipes::recipe_id.eq(id)))
+            .execute(connection)?;
+        diesel::delete(recipes::table.find(id)).execute(connection)?;
+        Ok(())
+    }
+
+    /// Adds `recipe` with `ingredients`, putting any item it creates in
+    /// `section_id`.
+    fn insert_recipe(
+        connection: &mut SqliteConnection,
+        recipe: &Recipe,
+        ingredients: &Ingredients,
+        section_id: Option<i32>,
+    ) -> Result<AddRecipeReport, StoreError> {
+        let recipe_id = Self::get_or_insert_recipe(connection, recipe.as_str())?;
+
+        let mut new_items = Vec::new();
+        let mut existing_items = Vec::new();
+        for ingredient in ingredients.iter() {
+            let (item_id, created) =
+                Self::get_or_insert_item_created(connection, ingredient.as_str())?;
+            if created {
+                if let Some(section_id) = section_id {
+                    Self::insert_item_section(connection, item_id, section_id)?;
+                }
+                new_items.push(ingredient.clone());
+            } else {
+                existing_items.push(ingredient.clone());
+            }
+            Self::insert_item_recipe(connection, item_id, recipe_id)?;
+        }
+        Ok(AddRecipeReport::new(recipe_id, new_items, existing_items))
+    }
+
+    /// The section most common among the items sharing a recipe with
+    /// `item_id`, as [`Storage::suggest_section`].
+    fn suggested_section(
+        connection: &mut SqliteConnection,
+        item_id: i32,
+    ) -> Result<Option<common::section::Section>, StoreError> {
+        use crate::schema::{items_recipes, items_sections, sections};
+
+        let (shared_recipes, own) =
+            diesel::alias!(items_recipes as shared_recipes, items_recipes as own);
+        let item_recipes = own
+            .filter(own.field(items_recipes::item_id).eq(item_id))
+            .select(own.field(items_recipes::recipe_id));
+        let counts = shared_recipes
+            .inner_join(
+                items_sections::table
+                    .on(items_sections::item_id.eq(shared_recipes.field(items_recipes::item_id))),
+            )
+            .inner_join(sections::table.on(sections::id.eq(items_sections::section_id)))
+            .filter(
+                shared_recipes
+                    .field(items_recipes::recipe_id)
+                    .eq_any(item_recipes),
+            )
+            .filter(shared_recipes.field(items_recipes::item_id).ne(item_id))
+            .group_by(sections::name)
+            .select((sections::name, count(sections::id)))
+            .load::<(String, i64)>(connection)?;
+
+        // Ties go to the alphabetically first section, so the suggestion
+        // doesn't depend on row order
+        Ok(counts
+            .into_iter()
+            .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))
+            .map(|(section, _)| common::section::Section::from(section)))
+    }
+
+    /// Records `url` as fetched now for `recipe_id` with `ingredients`, and
+    /// as the recipe's source.
+    fn record_fetch(
+        connection: &mut SqliteConnection,
+        url: &Url,
+        recipe_id: i32,
+        ingredients: &str,
+    ) -> Result<(), StoreError> {
+        use crate::schema::fetched_recipes;
+
+        diesel::replace_into(fetched_recipes::table)
+            .values((
+                fetched_recipes::url.eq(url.as_str()),
+                fetched_recipes::recipe_id.eq(recipe_id),
+                fetched_recipes::ingredients.eq(ingredients),
+            ))
+            .execute(connection)?;
+        Self::set_source_url(connection, recipe_id, url)
+    }
+
+    fn update_ingredient_amount(
+        connection: &mut SqliteConnection,
+        recipe_id: i32,
+        item: &Name,
+        quantity: i32,
+        unit: Option<String>,
+    ) -> Result<(), StoreError> {
+        use crate::schema::items_recipes;
+
+        let item_id = Self::get_item_id(connection, item.as_str())?
+            .ok_or_else(|| StoreError::ItemNotFound(item.to_string()))?;
+        let updated = diesel::update(items_recipes::table.find((item_id, recipe_id)))
+            .set((
+                items_recipes::quantity.eq(quantity),
+                items_recipes::unit.eq(unit),
+            ))
+            .execute(connection)?;
+        if updated == 0 {
+            return Err(StoreError::ItemNotFound(item.to_string()));
+        }
+        Ok(())
+    }
+
     fn insert_item_recipe(
         connection: &mut SqliteConnection,
         item_id: i32,
@@ -157,20 +571,311 @@ impl SqliteStore {
         Ok(())
     }
 
-    async fn get_list(&self) -> Result<List, StoreError> {
+    /// Replaces any sections the item is in with `section_id`.
+    fn set_item_section(
+        connection: &mut SqliteConnection,
+        item_id: i32,
+        section_id: i32,
+    ) -> Result<(), StoreError> {
+        diesel::delete(
+            schema::items_sections::table.filter(schema::items_sections::dsl::item_id.eq(item_id)),
+        )
+        .execute(connection)?;
+        Self::insert_item_section(connection, item_id, section_id)
+    }
+
+    fn get_item_id(
+        connection: &mut SqliteConnection,
+        item: &str,
+    ) -> Result<Option<i32>, StoreError> {
+        Ok(schema::items::table
+            .filter(schema::items::dsl::name.eq(item))
+            .select(schema::items::dsl::id)
+            .first(connection)
+            .optional()?)
+    }
+
+    /// Moves every row referring to item `from` over to item `to`, keeping
+    /// `to`'s section if it already has one.
+    fn merge_item(connection: &mut SqliteConnection, from: i32, to: i32) -> Result<(), StoreError> {
+        use diesel::sql_types::Integer;
+        use schema::{
+            checklist, items, items_recipes, items_sections, list, list_items_recipes,
+            subscriptions,
+        };
+
+        // `from`'s rows are copied whole where `to` doesn't have its own
+        diesel::insert_or_ignore_into(list::table)
+            .values(
+                list::table
+                    .filter(list::id.eq(from))
+                    .select((to.into_sql::<Integer>(), list::manual)),
+            )
+            .into_columns((list::id, list::manual))
+            .execute(connection)?;
+        if diesel::delete(list::table.filter(list::id.eq(from).and(list::manual)))
+            .execute(connection)?
+            > 0
+        {
+            diesel::update(list::table.filter(list::id.eq(to)))
+                .set(list::manual.eq(true))
+                .execute(connection)?;
+        }
+        diesel::delete(list::table.filter(list::id.eq(from))).execute(connection)?;
+        if diesel::delete(checklist::table.filter(checklist::id.eq(from))).execute(connection)? > 0
+        {
+            diesel::insert_into(checklist::table)
+                .values(NewChecklistItem { id: to })
+                .on_conflict_do_nothing()
+                .execute(connection)?;
+        }
+        if diesel::delete(subscriptions::table.filter(subscriptions::id.eq(from)))
+            .execute(connection)?
+            > 0
+        {
+            diesel::insert_into(subscriptions::table)
+                .values(NewSubscription { id: to })
+                .on_conflict_do_nothing()
+                .execute(connection)?;
+        }
+
+        diesel::insert_or_ignore_into(items_recipes::table)
+            .values(
+                items_recipes::table
+                    .filter(items_recipes::item_id.eq(from))
+                    .select((
+                        to.into_sql::<Integer>(),
+                        items_recipes::recipe_id,
+                        items_recipes::quantity,
+                        items_recipes::unit,
+                    )),
+            )
+            .into_columns((
+                items_recipes::item_id,
+                items_recipes::recipe_id,
+                items_recipes::quantity,
+                items_recipes::unit,
+            ))
+            .execute(connection)?;
+        diesel::delete(items_recipes::table.filter(items_recipes::item_id.eq(from)))
+            .execute(connection)?;
+        diesel::insert_or_ignore_into(list_items_recipes::table)
+            .values(
+                list_items_recipes::table
+                    .filter(list_items_recipes::item_id.eq(from))
+                    .select((to.into_sql::<Integer>(), list_items_recipes::recipe_id)),
+            )
+            .into_columns((list_items_recipes::item_id, list_items_recipes::recipe_id))
+            .execute(connection)?;
+        diesel::delete(list_items_recipes::table.filter(list_items_recipes::item_id.eq(from)))
+            .execute(connection)?;
+
+        let section_ids: Vec<i32> =
+            diesel::delete(items_sections::table.filter(items_sections::item_id.eq(from)))
+                .returning(items_sections::section_id)
+                .get_results(connection)?;
+        let has_section = items_sections::table
+            .filter(items_sections::item_id.eq(to))
+            .count()
+            .get_result::<i64>(connection)?
+            > 0;
+        if !has_section {
+            for section_id in section_ids {
+                Self::insert_item_section(connection, to, section_id)?;
+            }
+        }
+
+        let times_listed: i32 = diesel::delete(items::table.filter(items::id.eq(from)))
+            .returning(items::times_listed)
+            .get_result(connection)?;
+        diesel::update(items::table.filter(items::id.eq(to)))
+            .set(items::times_listed.eq(items::times_listed + times_listed))
+            .execute(connection)?;
+        Ok(())
+    }
+
+    /// Removes the items the recipe put on the list, keeping any that were
+    /// added by hand or that another recipe on the list still needs.
+    fn remove_list_recipe_items(
+        connection: &mut SqliteConnection,
+        recipe_id: i32,
+    ) -> Result<(), StoreError> {
+        use schema::{list, list_items_recipes};
+
+        let recipe_items = list_items_recipes::table
+            .filter(list_items_recipes::recipe_id.eq(recipe_id))
+            .select(list_items_recipes::item_id);
+        let other_list_recipe_items = list_items_recipes::table
+            .filter(list_items_recipes::recipe_id.ne(recipe_id))
+            .select(list_items_recipes::item_id);
+
+        diesel::delete(
+            list::table
+                .filter(list::id.eq_any(recipe_items))
+                .filter(list::id.ne_all(other_list_recipe_items))
+                .filter(list::manual.eq(false)),
+        )
+        .execute(connection)?;
+        Self::forget_list_recipe_items(connection, recipe_id)
+    }
+
+    /// Forgets which items `recipe_id` put on the list.
+    fn forget_list_recipe_items(
+        connection: &mut SqliteConnection,
+        recipe_id: i32,
+    ) -> Result<(), StoreError> {
+        use schema::list_items_recipes;
+
+        diesel::delete(
+            list_items_recipes::table.filter(list_items_recipes::recipe_id.eq(recipe_id)),
+        )
+        .execute(connection)?;
+        Ok(())
+    }
+
+    /// Forgets which recipes put items on the list that are no longer on it.
+    fn forget_unlisted_items(connection: &mut SqliteConnection) -> Result<(), StoreError> {
+        use schema::{list, list_items_recipes};
+
+        diesel::delete(
+            list_items_recipes::table
+                .filter(list_items_recipes::item_id.ne_all(list::table.select(list::id))),
+        )
+        .execute(connection)?;
+        Ok(())
+    }
+
+    /// Imports the JSON store's items. With `fresh`, existing data is cleared
+    /// in the same transaction first.
+    async fn import_from(
+        &self,
+        import_store: ImportStore,
+        fresh: bool,
+    ) -> Result<StoreResponse, StoreError> {
+        let mut connection = self.connection()?;
+        let items = import_store.items()?;
+        tokio::task::spawn_blocking(move || {
+            connection.write_transaction(|connection| {
+                if fresh {
+                    clear_tables(connection)?;
+                }
+                let sections = import_sections(connection)?;
+                let summary = import_items(connection, items.clone())?;
+                Ok(StoreResponse::ImportToSqlite(MigrationSummary {
+                    sections: sections + summary.sections,
+                    ..summary
+                }))
+            })
+        })
+        .await?
+    }
+
+    /// The list's items in store-walk order: by section position, then
+    /// section name, then item name, with unsectioned items last.
+    fn load_list_items(connection: &mut SqliteConnection) -> Result<Vec<Item>, StoreError> {
+        use crate::schema::{items, items_sections, list, sections};
+
+        let mut items = items::table
+            .inner_join(list::table.on(list::id.eq(items::id)))
+            .left_join(items_sections::table.on(items_sections::item_id.eq(items::id)))
+            .left_join(sections::table.on(sections::id.eq(items_sections::section_id)))
+            .order_by((
+                sections::position.is_null(),
+                sections::position,
+                sections::name.is_null(),
+                sections::name,
+                items::name,
+            ))
+            .select((items::id, items::name))
+            .load::<Item>(connection)?;
+
+        // An item in several sections is listed under the first one walked past
+        let mut seen = std::collections::HashSet::new();
+        items.retain(|item| seen.insert(item.id));
+        Ok(items)
+    }
+
+    /// The list's items with their quantities.
+    async fn get_list_items(&self) -> Result<Vec<common::item::Item>, StoreError> {
         let store = self.clone();
         tokio::task::spawn_blocking(move || {
-            let mut connection = store.connection()?;
-            connection.immediate_transaction(|connection| {
-                Ok(schema::items::table
-                    .filter(
-                        schema::items::dsl::id
-                            .eq_any(schema::list::table.select(schema::list::dsl::id)),
-                    )
-                    .load::<Item>(connection)?
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let quantities = Self::load_list_item_quantities(connection)?;
+                Ok(Self::load_list_items(connection)?
                     .into_iter()
-                    .map(Into::into)
-                    .collect::<List>())
+                    .map(|item| {
+                        let quantity = quantities.get(&item.id).copied().unwrap_or(1);
+                        common::item::Item::from(item).with_quantity(quantity)
+                    })
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    /// How many of each of its items the list needs: the quantities called
+    /// for by the recipes on the list, plus one if it was added by hand, and at
+    /// least one.
+    fn load_list_item_quantities(
+        connection: &mut SqliteConnection,
+    ) -> Result<HashMap<i32, usize>, StoreError> {
+        use crate::schema::{items_recipes, list, list_recipes};
+
+        let from_recipes: HashMap<i32, i64> = items_recipes::table
+            .filter(items_recipes::recipe_id.eq_any(list_recipes::table.select(list_recipes::id)))
+            .group_by(items_recipes::item_id)
+            .select((
+                items_recipes::item_id,
+                diesel::dsl::sum(items_recipes::quantity),
+            ))
+            .load::<(i32, Option<i64>)>(connection)?
+            .into_iter()
+            .map(|(id, quantity)| (id, quantity.unwrap_or_default()))
+            .collect();
+        Ok(list::table
+            .select((list::id, list::manual))
+            .load::<(i32, bool)>(connection)?
+            .into_iter()
+            .map(|(id, manual)| {
+                let from_recipes = from_recipes.get(&id).copied().unwrap_or_default();
+                let quantity =
+                    usize::try_from(from_recipes).unwrap_or_default() + usize::from(manual);
+                (id, quantity.max(1))
+            })
+            .collect())
+    }
+
+    /// The list's items with their sections and quantities, each annotated with
+    /// the recipes on the list that call for it.
+    async fn get_list_items_with_recipes(&self) -> Result<Vec<common::item::Item>, StoreError> {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let quantities = Self::load_list_item_quantities(connection)?;
+                Self::load_list_items(connection)?
+                    .into_iter()
+                    .map(|item| {
+                        let section = Self::get_section_model_for_item(connection, item.id)?;
+                        let recipes = Self::get_list_recipe_models_for_item(connection, item.id)?;
+                        let quantity = quantities.get(&item.id).copied().unwrap_or(1);
+                        let mut item = common::item::Item::from(item).with_quantity(quantity);
+                        if let Some(section) = section {
+                            item = item.with_section(section.name());
+                        }
+                        if recipes.is_empty() {
+                            return Ok(item);
+                        }
+                        Ok(item.with_recipes(
+                            recipes
+                                .into_iter()
+                                .map(Into::into)
+                                .collect::<Vec<Recipe>>()
+                                .as_slice(),
+                        ))
+                    })
+                    .collect()
             })
         })
         .await?
@@ -179,15 +884,16 @@ impl SqliteStore {
     async fn get_list_recipes(&self) -> Result<Vec<Recipe>, StoreError> {
         let store = self.clone();
         tokio::task::spawn_blocking(move || {
-            let mut connection = store.connection()?;
-            connection.immediate_transaction(|connection| {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
                 Ok(schema::recipes::table
                     .filter(
                         schema::recipes::dsl::id.eq_any(
                             schema::list_recipes::table.select(schema::list_recipes::dsl::id),
                         ),
                     )
-                    .load::<RecipeModel>(connection)?
+                    .select(RecipeModel::as_select())
+                    .load(connection)?
                     .into_iter()
                     .map(Into::into)
                     .collect())
@@ -196,10 +902,19 @@ impl SqliteStore {
         .await?
     }
 
-    fn load_item(connection: &mut SqliteConnection, item_id: i32) -> Result<Vec<Item>, StoreError> {
-        Ok(schema::items::table
-            .filter(schema::items::dsl::id.eq(&item_id))
-            .load::<Item>(connection)?)
+    fn load_empty_sections(
+        connection: &mut SqliteConnection,
+    ) -> Result<Vec<common::section::Section>, StoreError> {
+        use crate::schema::{items_sections, sections};
+
+        Ok(sections::table
+            .filter(sections::id.ne_all(items_sections::table.select(items_sections::section_id)))
+            .order_by(sections::name)
+            .select(Section::as_select())
+            .load(connection)?
+            .into_iter()
+            .map(|section| section.name().into())
+            .collect())
     }
 
     fn get_recipe_model_for_recipe(
@@ -208,10 +923,43 @@ impl SqliteStore {
     ) -> Result<Option<Vec<RecipeModel>>, StoreError> {
         Ok(schema::recipes::table
             .filter(schema::recipes::dsl::name.eq(recipe))
-            .load::<models::RecipeModel>(connection)
+            .filter(schema::recipes::dsl::deleted_at.is_null())
+            .select(RecipeModel::as_select())
+            .load(connection)
             .optional()?)
     }
 
+    fn with_sections_and_recipes(
+        connection: &mut SqliteConnection,
+        items: Vec<Item>,
+    ) -> Result<Items, StoreError> {
+        items
+            .into_iter()
+            .map(|item| {
+                let section = Self::get_section_model_for_item(connection, item.id)?;
+                let item_recipes = Self::get_recipe_models_for_item(connection, item.id)?;
+
+                let mut item: common::item::Item = item.into();
+
+                if let Some(section) = section {
+                    item = item.with_section(section.name());
+                }
+
+                if let Some(item_recipes) = item_recipes {
+                    item = item.with_recipes(
+                        item_recipes
+                            .into_iter()
+                            .map(Into::into)
+                            .collect::<Vec<Recipe>>()
+                            .as_slice(),
+                    );
+                }
+
+                Ok(item)
+            })
+            .collect()
+    }
+
     fn get_section_model_for_item(
         connection: &mut SqliteConnection,
         item_id: i32,
@@ -235,19 +983,35 @@ impl SqliteStore {
         Ok(items_recipes::table
             .filter(items_recipes::item_id.eq(item_id))
             .inner_join(recipes::table.on(recipes::id.eq(items_recipes::recipe_id)))
+            .filter(recipes::deleted_at.is_null())
             .select(RecipeModel::as_select())
             .load(connection)
             .optional()?)
     }
-}
 
-impl Storage for SqliteStore {
-    async fn add_checklist_item(&self, item: &Name) -> Result<StoreResponse, StoreError> {
-        let store = self.clone();
-        let item = item.clone();
+    fn get_list_recipe_models_for_item(
+        connection: &mut SqliteConnection,
+        item_id: i32,
+    ) -> Result<Vec<RecipeModel>, StoreError> {
+        use crate::schema::{items_recipes, list_recipes, recipes};
+
+        Ok(items_recipes::table
+            .filter(items_recipes::item_id.eq(item_id))
+            .inner_join(recipes::table.on(recipes::id.eq(items_recipes::recipe_id)))
+            .inner_join(list_recipes::table.on(list_recipes::id.eq(recipes::id)))
+            .order_by(recipes::name.asc())
+            .select(RecipeModel::as_select())
+            .load(connection)?)
+    }
+}
+
+impl Storage for SqliteStore {
+    async fn add_checklist_item(&self, item: &Name) -> Result<StoreResponse, StoreError> {
+        let store = self.clone();
+        let item = item.clone();
         tokio::task::spawn_blocking(move || {
             let mut connection = store.connection()?;
-            connection.immediate_transaction(|connection| {
+            connection.write_transaction(|connection| {
                 let id = Self::get_or_insert_item(connection, item.as_str())?;
                 let query = {
                     diesel::insert_into(schema::checklist::table)
@@ -255,593 +1019,6768 @@ impl Storage for SqliteStore {
                         .on_conflict_do_nothing()
                 };
                 query.execute(connection)?;
-                Ok(StoreResponse::AddedChecklistItem(item))
+                Ok(StoreResponse::AddedChecklistItem(item.clone()))
             })
         })
         .await?
     }
 
-    async fn add_item(
-        &self,
-        item: &Name,
-        section: &Option<common::section::Section>,
-    ) -> Result<StoreResponse, StoreError> {
+    async fn cached_recipe(&self, url: &Url) -> Result<Option<(Recipe, Ingredients)>, StoreError> {
+        use crate::schema::{fetched_recipes, recipes};
+
         let store = self.clone();
-        let item = item.clone();
-        let section = section.clone();
+        let url = url.clone();
         tokio::task::spawn_blocking(move || {
-            let mut connection = store.connection()?;
-            connection.immediate_transaction(|connection| {
-                let item_name = item.to_string();
-                let item_id = Self::get_or_insert_item(connection, &item_name)?;
-                if let Some(section) = section {
-                    let section_id = Self::get_or_insert_section(connection, section.as_str())?;
-                    Self::insert_item_section(connection, item_id, section_id)?;
-                }
-                Ok(StoreResponse::AddedItem(item))
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let cutoff = format!(
+                    "fetched_at > datetime('now', '-{} seconds')",
+                    FETCH_CACHE_TTL.as_secs()
+                );
+                let Some((recipe, ingredients)) = fetched_recipes::table
+                    .inner_join(recipes::table)
+                    .filter(fetched_recipes::url.eq(url.as_str()))
+                    .filter(recipes::deleted_at.is_null())
+                    .filter(sql::<Bool>(&cutoff))
+                    .select((recipes::name, fetched_recipes::ingredients))
+                    .first::<(String, String)>(connection)
+                    .optional()?
+                else {
+                    return Ok(None);
+                };
+                Ok(Some((
+                    Recipe::new_unchecked(recipe),
+                    serde_json::from_str(&ingredients)?,
+                )))
             })
         })
         .await?
     }
 
-    async fn add_list_item(&self, item: &Name) -> Result<StoreResponse, StoreError> {
+    async fn cache_fetched_recipe(
+        &self,
+        url: &Url,
+        recipe: &Recipe,
+        ingredients: &Ingredients,
+    ) -> Result<(), StoreError> {
         let store = self.clone();
-        let item = item.clone();
+        let url = url.clone();
+        let recipe = recipe.clone();
+        let ingredients = serde_json::to_string(ingredients)?;
         tokio::task::spawn_blocking(move || {
             let mut connection = store.connection()?;
-            connection.immediate_transaction(|connection| {
-                let id = Self::get_or_insert_item(connection, item.as_str())?;
-                let query = diesel::insert_into(schema::list::table)
-                    .values(NewListItem { id })
-                    .on_conflict_do_nothing();
-                query.execute(connection)?;
-                Ok(StoreResponse::AddedListItem(item))
+            connection.write_transaction(|connection| {
+                let Some(recipe_id) = Self::get_recipe_id(connection, recipe.as_str())? else {
+                    return Err(StoreError::RecipeNotFound(recipe.to_string()));
+                };
+                Self::record_fetch(connection, &url, recipe_id, &ingredients)
             })
         })
         .await?
     }
 
-    async fn add_list_recipe(&self, recipe: &Recipe) -> Result<StoreResponse, StoreError> {
-        let StoreResponse::RecipeIngredients(Some(ingredients)) =
-            self.recipe_ingredients(recipe).await?
-        else {
-            // TODO:
-            return Err(StoreError::RecipeIngredients(recipe.to_string()));
-        };
-
+    async fn add_fetched_recipe(
+        &self,
+        url: &Url,
+        recipe: &Recipe,
+        ingredients: &Ingredients,
+        default_section: &Option<common::section::Section>,
+        options: FetchOptions,
+    ) -> Result<AddRecipeReport, StoreError> {
+        self.check_ingredient_count(recipe, ingredients)?;
         let store = self.clone();
+        let url = url.clone();
         let recipe = recipe.clone();
+        let ingredients = ingredients.clone();
+        let fetched = serde_json::to_string(&ingredients)?;
+        let default_section = default_section.clone();
         tokio::task::spawn_blocking(move || {
             let mut connection = store.connection()?;
-            connection.immediate_transaction(|connection| {
-                let id = Self::get_or_insert_recipe(connection, recipe.as_str())?;
-                diesel::insert_into(schema::list_recipes::table)
-                    .values(NewListRecipe { id })
-                    .on_conflict_do_nothing()
-                    .execute(connection)?;
-                for item in ingredients.iter() {
-                    let item_id = Self::get_or_insert_item(connection, item.as_str())?;
-                    let query = diesel::insert_into(schema::list::table)
-                        .values(NewListItem { id: item_id })
-                        .on_conflict_do_nothing();
-                    query.execute(connection)?;
-
-                    let new_item_recipe = NewItemRecipe {
-                        item_id,
-                        recipe_id: id,
-                    };
-                    diesel::insert_into(schema::items_recipes::table)
-                        .values(&new_item_recipe)
-                        .on_conflict_do_nothing()
-                        .execute(connection)?;
+            connection.write_transaction(|connection| {
+                let section_id = default_section
+                    .as_ref()
+                    .map(|section| Self::get_or_insert_section(connection, section.as_str()))
+                    .transpose()?;
+                let report = Self::insert_recipe(connection, &recipe, &ingredients, section_id)?;
+                if options.auto_section {
+                    // Suggest every section before moving any item, so one
+                    // new item's section doesn't sway the next suggestion
+                    let mut suggested = Vec::new();
+                    for item in report.new_items() {
+                        let Some(item_id) = Self::get_item_id(connection, item.as_str())? else {
+                            continue;
+                        };
+                        if let Some(section) = Self::suggested_section(connection, item_id)? {
+                            suggested.push((item_id, section));
+                        }
+                    }
+                    for (item_id, section) in suggested {
+                        let section_id = Self::get_or_insert_section(connection, section.as_str())?;
+                        Self::set_item_section(connection, item_id, section_id)?;
+                    }
                 }
-                Ok(StoreResponse::AddedListRecipe(recipe))
+                Self::record_fetch(connection, &url, report.recipe_id(), &fetched)?;
+                Ok(report)
             })
         })
         .await?
     }
 
-    async fn add_recipe(
+    async fn recent_fetches(&self, limit: usize) -> Result<Vec<Recipe>, StoreError> {
+        use crate::schema::{fetched_recipes, recipes};
+
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                // `fetched_at` only counts seconds; fetches are written with
+                // `REPLACE`, so a later `rowid` breaks ties in favour of the
+                // latest fetch
+                Ok(fetched_recipes::table
+                    .inner_join(recipes::table)
+                    .filter(recipes::deleted_at.is_null())
+                    .select(RecipeModel::as_select())
+                    .order_by((
+                        fetched_recipes::fetched_at.desc(),
+                        sql::<diesel::sql_types::Integer>("fetched_recipes.rowid").desc(),
+                    ))
+                    .limit(limit)
+                    .load(connection)?
+                    .into_iter()
+                    .map(Into::into)
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn replace_recipe_ingredients(
         &self,
         recipe: &Recipe,
         ingredients: &Ingredients,
-    ) -> Result<StoreResponse, StoreError> {
+        source_url: &Url,
+    ) -> Result<(), StoreError> {
+        use crate::schema::items_recipes;
+
+        self.check_ingredient_count(recipe, ingredients)?;
         let store = self.clone();
         let recipe = recipe.clone();
         let ingredients = ingredients.clone();
+        let source_url = source_url.clone();
         tokio::task::spawn_blocking(move || {
-            let mut connection: PooledConnection<ConnectionManager<SqliteConnection>> =
-                store.connection()?;
-            connection.immediate_transaction(|connection| {
-                let recipe_id = Self::get_or_insert_recipe(connection, recipe.as_str())?;
-                let item_ids = ingredients
-                    .iter()
-                    .map(|ingredient| Self::get_or_insert_item(connection, ingredient.as_str()))
-                    .collect::<Result<Vec<i32>, _>>()?;
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let Some(recipe_id) = Self::get_recipe_id(connection, recipe.as_str())? else {
+                    return Err(StoreError::RecipeNotFound(recipe.to_string()));
+                };
 
-                for item_id in item_ids {
+                diesel::delete(items_recipes::table.filter(items_recipes::recipe_id.eq(recipe_id)))
+                    .execute(connection)?;
+                for ingredient in ingredients.iter() {
+                    let item_id = Self::get_or_insert_item(connection, ingredient.as_str())?;
                     Self::insert_item_recipe(connection, item_id, recipe_id)?;
                 }
-                Ok(StoreResponse::AddedRecipe(recipe))
+                Self::set_source_url(connection, recipe_id, &source_url)?;
+                Ok(())
             })
         })
         .await?
     }
 
-    async fn checklist(&self) -> Result<StoreResponse, StoreError> {
+    async fn recipe_by_url(&self, url: &Url) -> Result<Option<Recipe>, StoreError> {
+        use crate::schema::recipes;
+
         let store = self.clone();
+        let url = url.clone();
         tokio::task::spawn_blocking(move || {
-            let mut connection = store.connection()?;
-            connection.immediate_transaction(|connection| {
-                Ok(StoreResponse::Checklist(
-                    schema::items::table
-                        .filter(
-                            schema::items::dsl::id.eq_any(
-                                schema::checklist::table.select(schema::checklist::dsl::id),
-                            ),
-                        )
-                        .load::<Item>(connection)?
-                        .into_iter()
-                        .map(Into::into)
-                        .collect(),
-                ))
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(recipes::table
+                    .filter(recipes::source_url.eq(url.as_str()))
+                    .filter(recipes::deleted_at.is_null())
+                    .select(recipes::name)
+                    .first::<String>(connection)
+                    .optional()?
+                    .map(Recipe::new_unchecked))
             })
         })
         .await?
     }
 
-    async fn list(&self) -> Result<StoreResponse, StoreError> {
-        let mut list = self.get_list().await?;
-        list = list.with_recipes(self.get_list_recipes().await?);
-        let StoreResponse::Checklist(checklist) = self.checklist().await? else {
-            todo!()
-        };
-        list = list.with_checklist(checklist);
-        Ok(StoreResponse::List(list))
-    }
-
-    async fn delete_checklist_item(&self, item: &Name) -> Result<StoreResponse, StoreError> {
+    async fn checklist_existing_item(&self, item: &Name) -> Result<StoreResponse, StoreError> {
         let store = self.clone();
         let item = item.clone();
         tokio::task::spawn_blocking(move || {
             let mut connection = store.connection()?;
-            connection.immediate_transaction(|connection| {
-                diesel::delete(
-                    schema::checklist::table.filter(
-                        schema::checklist::dsl::id.eq_any(
-                            schema::items::table
-                                .select(schema::items::dsl::id)
-                                .filter(schema::items::dsl::name.eq(item.as_str())),
-                        ),
-                    ),
-                )
-                .execute(connection)?;
-                Ok(StoreResponse::DeletedChecklistItem(item))
+            connection.write_transaction(|connection| {
+                let Some(id) = Self::get_item_id(connection, item.as_str())? else {
+                    return Err(StoreError::ItemNotFound(item.to_string()));
+                };
+                diesel::insert_into(schema::checklist::table)
+                    .values(NewChecklistItem { id })
+                    .on_conflict_do_nothing()
+                    .execute(connection)?;
+                Ok(StoreResponse::AddedChecklistItem(item.clone()))
             })
         })
         .await?
     }
 
-    async fn delete_recipe(&self, recipe: &Recipe) -> Result<StoreResponse, StoreError> {
+    async fn rename_item(&self, item: &Name, new_name: &Name) -> Result<StoreResponse, StoreError> {
         let store = self.clone();
-        let recipe = recipe.clone();
-        let StoreResponse::RecipeIngredients(ingredients) =
-            self.recipe_ingredients(&recipe).await?
-        else {
-            todo!()
-        };
+        let item = item.clone();
+        let new_name = new_name.clone();
         tokio::task::spawn_blocking(move || {
             let mut connection = store.connection()?;
-            connection.immediate_transaction(|connection| {
-                let name = recipe.to_string();
-                diesel::delete(
-                    schema::items_recipes::table.filter(
-                        schema::items_recipes::dsl::recipe_id.eq_any(
-                            schema::recipes::table
-                                .select(schema::recipes::dsl::id)
-                                .filter(schema::recipes::dsl::name.eq(&name)),
-                        ),
-                    ),
-                )
-                .execute(connection)?;
-                diesel::delete(schema::recipes::table.filter(schema::recipes::dsl::name.eq(name)))
-                    .execute(connection)?;
-                if let Some(ingredients) = ingredients {
-                    for item in ingredients.iter() {
-                        diesel::delete(
-                            schema::items::table.filter(schema::items::dsl::name.eq(item.as_str())),
-                        )
-                        .execute(connection)?;
+            connection.write_transaction(|connection| {
+                let Some(id) = Self::get_item_id(connection, item.as_str())? else {
+                    return Err(StoreError::ItemNotFound(item.to_string()));
+                };
+                match Self::get_item_id(connection, new_name.as_str())? {
+                    Some(existing) if existing != id => Self::merge_item(connection, id, existing)?,
+                    Some(_) => {}
+                    None => {
+                        diesel::update(schema::items::table.filter(schema::items::dsl::id.eq(id)))
+                            .set(schema::items::dsl::name.eq(new_name.as_str()))
+                            .execute(connection)?;
                     }
                 }
-                Ok(StoreResponse::DeletedRecipe(recipe))
+                Ok(StoreResponse::RenamedItem(item.clone(), new_name.clone()))
             })
         })
         .await?
     }
 
-    async fn export(&self) -> Result<StoreResponse, StoreError> {
-        let items = self.items().await?;
-        let StoreResponse::List(list) = self.list().await? else {
-            todo!()
-        };
-
-        let items = items.collection().to_vec();
-
-        items.serialize_to_yaml_and_write(ITEMS_YAML_PATH)?;
-        list.serialize_to_yaml_and_write(LIST_YAML_PATH)?;
-
-        Ok(StoreResponse::Exported(items, list))
+    async fn rename_item_strict(
+        &self,
+        item: &Name,
+        new_name: &Name,
+    ) -> Result<StoreResponse, StoreError> {
+        let store = self.clone();
+        let item = item.clone();
+        let new_name = new_name.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let Some(id) = Self::get_item_id(connection, item.as_str())? else {
+                    return Err(StoreError::ItemNotFound(item.to_string()));
+                };
+                match Self::get_item_id(connection, new_name.as_str())? {
+                    Some(existing) if existing != id => {
+                        return Err(StoreError::ItemExists(new_name.to_string()));
+                    }
+                    Some(_) => {}
+                    None => {
+                        diesel::update(schema::items::table.filter(schema::items::dsl::id.eq(id)))
+                            .set(schema::items::dsl::name.eq(new_name.as_str()))
+                            .execute(connection)?;
+                    }
+                }
+                Ok(StoreResponse::RenamedItem(item.clone(), new_name.clone()))
+            })
+        })
+        .await?
     }
 
-    async fn import_from_json(&self) -> Result<StoreResponse, StoreError> {
-        let import_store = ImportStore::default();
-        let mut connection = self.connection()?;
-        let items = import_store.items()?;
+    async fn subscribe_item(&self, item: &Name) -> Result<StoreResponse, StoreError> {
+        let store = self.clone();
+        let item = item.clone();
         tokio::task::spawn_blocking(move || {
-            connection.immediate_transaction(|connection| {
-                import_sections(connection)?;
-                import_items(connection, items)?;
-                Ok(StoreResponse::ImportToSqlite)
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let id = Self::get_or_insert_item(connection, item.as_str())?;
+                diesel::insert_into(schema::subscriptions::table)
+                    .values(NewSubscription { id })
+                    .on_conflict_do_nothing()
+                    .execute(connection)?;
+                Ok(StoreResponse::AddedSubscription(item.clone()))
             })
         })
         .await?
     }
 
-    async fn items(&self) -> Result<Items, StoreError> {
-        use crate::schema::items;
+    async fn mark_section_as_staples(
+        &self,
+        section: &common::section::Section,
+    ) -> Result<StoreResponse, StoreError> {
+        use crate::schema::{items, items_sections, subscriptions};
 
         let store = self.clone();
+        let section = section.clone();
         tokio::task::spawn_blocking(move || {
             let mut connection = store.connection()?;
-            connection.immediate_transaction(|connection| {
-                let all_items: Vec<Item> = items::dsl::items.load::<Item>(connection)?;
-
-                all_items
-                    .into_iter()
-                    .map(|item| {
-                        let section = Self::get_section_model_for_item(connection, item.id)?;
-                        let item_recipes = Self::get_recipe_models_for_item(connection, item.id)?;
-
-                        let mut item: common::item::Item = item.into();
-
-                        if let Some(section) = section {
-                            item = item.with_section(section.name());
-                        }
-
-                        if let Some(item_recipes) = item_recipes {
-                            item = item.with_recipes(
-                                item_recipes
-                                    .into_iter()
-                                    .map(Into::into)
-                                    .collect::<Vec<Recipe>>()
-                                    .as_slice(),
-                            );
-                        }
-
-                        Ok(item)
-                    })
-                    .collect::<Result<_, _>>()
+            connection.write_transaction(|connection| {
+                let Some(id) = Self::get_section_id(connection, section.as_str())? else {
+                    return Err(StoreError::SectionNotFound(section.to_string()));
+                };
+                let staples = items::table
+                    .inner_join(items_sections::table)
+                    .filter(items_sections::section_id.eq(id))
+                    .select((items::id, items::name))
+                    .order_by(items::name)
+                    .load::<(i32, String)>(connection)?;
+                for (id, _) in &staples {
+                    diesel::insert_into(subscriptions::table)
+                        .values(NewSubscription { id: *id })
+                        .on_conflict_do_nothing()
+                        .execute(connection)?;
+                }
+                Ok(StoreResponse::SubscribedSection(
+                    section.clone(),
+                    staples
+                        .into_iter()
+                        .map(|(_, name)| Name::from(name.as_str()))
+                        .collect(),
+                ))
             })
         })
         .await?
     }
 
-    async fn refresh_list(&self) -> Result<StoreResponse, StoreError> {
+    async fn unsubscribe_item(&self, item: &Name) -> Result<StoreResponse, StoreError> {
         let store = self.clone();
+        let item = item.clone();
         tokio::task::spawn_blocking(move || {
             let mut connection = store.connection()?;
-            connection.immediate_transaction(|connection| {
-                diesel::delete(schema::list::table).execute(connection)?;
-                Ok(StoreResponse::RefreshList)
+            connection.write_transaction(|connection| {
+                diesel::delete(
+                    schema::subscriptions::table.filter(
+                        schema::subscriptions::dsl::id.eq_any(
+                            schema::items::table
+                                .select(schema::items::dsl::id)
+                                .filter(schema::items::dsl::name.eq(item.as_str())),
+                        ),
+                    ),
+                )
+                .execute(connection)?;
+                Ok(StoreResponse::DeletedSubscription(item.clone()))
             })
         })
         .await?
     }
 
-    async fn recipe_ingredients(&self, recipe: &Recipe) -> Result<StoreResponse, StoreError> {
+    async fn subscriptions(&self) -> Result<StoreResponse, StoreError> {
         let store = self.clone();
-        let recipe = recipe.clone();
         tokio::task::spawn_blocking(move || {
-            let mut connection = store.connection()?;
-            connection.immediate_transaction(|connection| {
-                let Some(results) = Self::get_recipe_model_for_recipe(connection, recipe.as_str())?
-                else {
-                    return Ok(StoreResponse::RecipeIngredients(None));
-                };
-
-                let mut v = Vec::<Ingredients>::with_capacity(results.len());
-
-                for recipe in results {
-                    let recipe_id = recipe.id;
-
-                    let results = schema::items_recipes::table
-                        .filter(schema::items_recipes::dsl::recipe_id.eq(&recipe_id))
-                        .load::<models::ItemRecipe>(connection)?;
-
-                    let ingredients = results
-                        .iter()
-                        .map(|item_recipe| Self::load_item(connection, item_recipe.item_id))
-                        .collect::<Result<Vec<Vec<Item>>, _>>()?
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(StoreResponse::Subscriptions(
+                    schema::items::table
+                        .filter(schema::items::dsl::id.eq_any(
+                            schema::subscriptions::table.select(schema::subscriptions::dsl::id),
+                        ))
+                        .order_by(schema::items::dsl::name)
+                        .select(Item::as_select())
+                        .load(connection)?
                         .into_iter()
-                        .flatten()
-                        .map(|item| Name::from(item.name.as_str()))
-                        .collect::<Ingredients>();
-
-                    v.push(ingredients);
-                }
-
-                Ok(StoreResponse::RecipeIngredients(
-                    v.into_iter().take(1).next(),
+                        .map(Into::into)
+                        .collect(),
                 ))
             })
         })
         .await?
     }
 
-    async fn sections(&self) -> Result<StoreResponse, StoreError> {
-        use schema::sections::dsl::sections;
+    async fn add_item(
+        &self,
+        item: &Name,
+        section: &Option<common::section::Section>,
+    ) -> Result<bool, StoreError> {
         let store = self.clone();
+        let item = item.clone();
+        let section = section.clone();
         tokio::task::spawn_blocking(move || {
             let mut connection = store.connection()?;
-            connection.immediate_transaction(|connection| {
-                Ok(StoreResponse::Sections(
-                    sections
-                        .load::<Section>(connection)?
-                        .into_iter()
-                        .map(|sec| sec.name().into())
-                        .collect::<Vec<common::section::Section>>(),
-                ))
+            connection.write_transaction(|connection| {
+                let (item_id, created) =
+                    Self::get_or_insert_item_created(connection, item.as_str())?;
+                if let Some(section) = &section {
+                    let section_id = Self::get_or_insert_section(connection, section.as_str())?;
+                    Self::insert_item_section(connection, item_id, section_id)?;
+                }
+                Ok(created)
             })
         })
         .await?
     }
 
-    async fn recipes(&self) -> Result<StoreResponse, StoreError> {
-        use schema::recipes::dsl::recipes;
+    async fn add_list_item(&self, item: &Name) -> Result<StoreResponse, StoreError> {
         let store = self.clone();
+        let item = item.clone();
         tokio::task::spawn_blocking(move || {
             let mut connection = store.connection()?;
-            connection.immediate_transaction(|connection| {
-                Ok(StoreResponse::Recipes(
-                    recipes
-                        .load::<models::RecipeModel>(connection)?
-                        .into_iter()
-                        .map(Into::into)
-                        .collect(),
-                ))
+            connection.write_transaction(|connection| {
+                let id = Self::get_or_insert_item(connection, item.as_str())?;
+                Self::insert_list_item(connection, id, true)?;
+                Ok(StoreResponse::AddedListItem(item.clone()))
             })
         })
         .await?
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use common::{item::Name, recipes::Ingredients};
+    async fn add_list_recipe(
+        &self,
+        recipe: &Recipe,
+        refresh: bool,
+    ) -> Result<StoreResponse, StoreError> {
+        let store = self.clone();
+        let recipe = recipe.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let Some(id) = Self::get_recipe_id(connection, recipe.as_str())? else {
+                    return Err(StoreError::RecipeIngredients(recipe.to_string()));
+                };
+                Self::insert_list_recipe(connection, id, refresh)?;
+                Ok(StoreResponse::AddedListRecipe(recipe.clone()))
+            })
+        })
+        .await?
+    }
+
+    async fn import_share_code(&self, code: &str) -> Result<StoreResponse, StoreError> {
+        use crate::schema::{items, items_recipes};
+
+        let list = List::from_share_code(code)?;
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let mut from_recipes = Vec::new();
+                for recipe in list.recipes() {
+                    // Recipes unknown to this library can't be put on the list
+                    let Some(id) = Self::get_recipe_id(connection, recipe.as_str())? else {
+                        continue;
+                    };
+                    Self::insert_list_recipe(connection, id, false)?;
+                    from_recipes.extend(
+                        items_recipes::table
+                            .inner_join(items::table)
+                            .filter(items_recipes::recipe_id.eq(id))
+                            .select(items::name)
+                            .load::<String>(connection)?,
+                    );
+                }
+                for item in list.items() {
+                    if from_recipes.iter().any(|name| name == item.name().as_str()) {
+                        continue;
+                    }
+                    let id = Self::get_or_insert_item(connection, item.name().as_str())?;
+                    Self::insert_list_item(connection, id, true)?;
+                }
+                for item in list.checklist() {
+                    let id = Self::get_or_insert_item(connection, item.name().as_str())?;
+                    diesel::insert_into(schema::checklist::table)
+                        .values(NewChecklistItem { id })
+                        .on_conflict_do_nothing()
+                        .execute(connection)?;
+                }
+                Ok(StoreResponse::ImportedShareCode(list.clone()))
+            })
+        })
+        .await?
+    }
+
+    async fn add_recipe(
+        &self,
+        recipe: &Recipe,
+        ingredients: &Ingredients,
+        default_section: &Option<common::section::Section>,
+    ) -> Result<AddRecipeReport, StoreError> {
+        self.check_ingredient_count(recipe, ingredients)?;
+        let store = self.clone();
+        let recipe = recipe.clone();
+        let ingredients = ingredients.clone();
+        let default_section = default_section.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection: PooledConnection<ConnectionManager<SqliteConnection>> =
+                store.connection()?;
+            connection.write_transaction(|connection| {
+                let section_id = default_section
+                    .as_ref()
+                    .map(|section| Self::get_or_insert_section(connection, section.as_str()))
+                    .transpose()?;
+                Self::insert_recipe(connection, &recipe, &ingredients, section_id)
+            })
+        })
+        .await?
+    }
+
+    async fn set_item_sections(
+        &self,
+        pairs: &[(Name, common::section::Section)],
+    ) -> Result<common::section::SectionMapReport, StoreError> {
+        use crate::schema::{items, items_sections, sections};
+
+        let store = self.clone();
+        let pairs = pairs.to_vec();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let names: Vec<&str> = pairs.iter().map(|(item, _)| item.as_str()).collect();
+                let mut item_ids: HashMap<String, i32> = HashMap::new();
+                for chunk in names.chunks(BATCH_SIZE) {
+                    item_ids.extend(
+                        items::table
+                            .filter(items::name.eq_any(chunk))
+                            .select((items::name, items::id))
+                            .load::<(String, i32)>(connection)?,
+                    );
+                }
+                let section_names: Vec<&str> =
+                    pairs.iter().map(|(_, section)| section.as_str()).collect();
+                let mut section_ids: HashMap<String, i32> = HashMap::new();
+                for chunk in section_names.chunks(BATCH_SIZE) {
+                    section_ids.extend(
+                        sections::table
+                            .filter(sections::name.eq_any(chunk))
+                            .select((sections::name, sections::id))
+                            .load::<(String, i32)>(connection)?,
+                    );
+                }
+
+                let mut applied = 0;
+                let mut resolved = HashMap::new();
+                let mut missing_items = Vec::new();
+                let mut missing_sections = Vec::new();
+                for (item, section) in &pairs {
+                    let Some(&item_id) = item_ids.get(item.as_str()) else {
+                        missing_items.push(item.clone());
+                        continue;
+                    };
+                    let Some(&section_id) = section_ids.get(section.as_str()) else {
+                        if !missing_sections.contains(section) {
+                            missing_sections.push(section.clone());
+                        }
+                        continue;
+                    };
+                    resolved.insert(item_id, section_id);
+                    applied += 1;
+                }
+
+                let new_item_sections: Vec<NewItemSection> = resolved
+                    .into_iter()
+                    .map(|(item_id, section_id)| NewItemSection {
+                        item_id,
+                        section_id,
+                    })
+                    .collect();
+                for chunk in new_item_sections.chunks(BATCH_SIZE) {
+                    let ids: Vec<i32> = chunk.iter().map(|row| row.item_id).collect();
+                    diesel::delete(
+                        items_sections::table.filter(items_sections::item_id.eq_any(ids)),
+                    )
+                    .execute(connection)?;
+                    diesel::insert_or_ignore_into(items_sections::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+
+                Ok(common::section::SectionMapReport::new(
+                    applied,
+                    missing_items,
+                    missing_sections,
+                ))
+            })
+        })
+        .await?
+    }
+
+    async fn import_item_sections(
+        &self,
+        rows: &[(Name, common::section::Section)],
+    ) -> Result<usize, StoreError> {
+        let store = self.clone();
+        let rows = rows.to_vec();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                for (item, section) in &rows {
+                    let item_id = Self::get_or_insert_item(connection, item.as_str())?;
+                    let section_id = Self::get_or_insert_section(connection, section.as_str())?;
+                    Self::set_item_section(connection, item_id, section_id)?;
+                }
+                Ok(rows.len())
+            })
+        })
+        .await?
+    }
+
+    async fn checklist(&self) -> Result<StoreResponse, StoreError> {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(StoreResponse::Checklist(
+                    schema::items::table
+                        .filter(
+                            schema::items::dsl::id.eq_any(
+                                schema::checklist::table.select(schema::checklist::dsl::id),
+                            ),
+                        )
+                        .select(Item::as_select())
+                        .order_by(schema::items::dsl::id)
+                        .load(connection)?
+                        .into_iter()
+                        .map(Into::into)
+                        .collect(),
+                ))
+            })
+        })
+        .await?
+    }
+
+    async fn list(&self) -> Result<StoreResponse, StoreError> {
+        let items = self.get_list_items_with_recipes().await?;
+        let recipes = self.get_list_recipes().await?;
+        let StoreResponse::Checklist(checklist) = self.checklist().await? else {
+            todo!()
+        };
+        Ok(StoreResponse::List(
+            List::new()
+                .with_items(items)
+                .with_recipes(recipes)
+                .with_checklist(checklist),
+        ))
+    }
+
+    async fn shopping_order(&self) -> Result<common::list::ShoppingOrder, StoreError> {
+        use crate::schema::{
+            items, items_recipes, items_sections, list, list_recipes, recipes, sections,
+        };
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let rows = items::table
+                    .inner_join(list::table.on(list::id.eq(items::id)))
+                    .left_join(items_sections::table.on(items_sections::item_id.eq(items::id)))
+                    .left_join(sections::table.on(sections::id.eq(items_sections::section_id)))
+                    .left_join(items_recipes::table.on(items_recipes::item_id.eq(items::id)))
+                    .left_join(
+                        list_recipes::table.on(list_recipes::id
+                            .nullable()
+                            .eq(items_recipes::recipe_id.nullable())),
+                    )
+                    .left_join(
+                        recipes::table.on(recipes::id.nullable().eq(list_recipes::id.nullable())),
+                    )
+                    .order_by((
+                        sections::position.is_null(),
+                        sections::position,
+                        sections::name.is_null(),
+                        sections::name,
+                        items::name,
+                        recipes::name,
+                    ))
+                    .select((
+                        items::id,
+                        items::name,
+                        list::manual,
+                        sections::name.nullable(),
+                        recipes::name.nullable(),
+                        items_recipes::quantity.nullable(),
+                    ))
+                    .load::<(
+                        i32,
+                        String,
+                        bool,
+                        Option<String>,
+                        Option<String>,
+                        Option<i32>,
+                    )>(connection)?;
+
+                struct Entry {
+                    id: i32,
+                    name: String,
+                    quantity: usize,
+                    recipes: Vec<Recipe>,
+                }
+
+                // Rows come one per item, section and recipe; fold them into
+                // one entry per item under the first section walked past
+                let mut order: Vec<(Option<String>, Vec<Entry>)> = Vec::new();
+                let mut item_sections: HashMap<i32, Option<String>> = HashMap::new();
+                for (id, name, manual, section, recipe, quantity) in rows {
+                    let first_section = item_sections.entry(id).or_insert_with(|| section.clone());
+                    if *first_section != section {
+                        continue;
+                    }
+                    if order.last().map(|(last, _)| last) != Some(&section) {
+                        order.push((section.clone(), Vec::new()));
+                    }
+                    let entries = &mut order.last_mut().expect("just pushed").1;
+                    if entries.last().map(|entry| entry.id) != Some(id) {
+                        entries.push(Entry {
+                            id,
+                            name,
+                            quantity: usize::from(manual),
+                            recipes: Vec::new(),
+                        });
+                    }
+                    let entry = entries.last_mut().expect("just pushed");
+                    if let (Some(recipe), Some(quantity)) = (recipe, quantity) {
+                        entry.quantity += usize::try_from(quantity).unwrap_or_default();
+                        entry.recipes.push(Recipe::new_unchecked(recipe));
+                    }
+                }
+
+                Ok(common::list::ShoppingOrder::new(
+                    order
+                        .into_iter()
+                        .map(|(section, entries)| {
+                            let items = entries
+                                .into_iter()
+                                .map(|entry| {
+                                    let mut item = common::item::Item::new(entry.name)
+                                        .with_quantity(entry.quantity.max(1));
+                                    if let Some(section) = &section {
+                                        item = item.with_section(section);
+                                    }
+                                    if !entry.recipes.is_empty() {
+                                        item = item.with_recipes(&entry.recipes);
+                                    }
+                                    item
+                                })
+                                .collect();
+                            (section.map(common::section::Section::from), items)
+                        })
+                        .collect(),
+                ))
+            })
+        })
+        .await?
+    }
+
+    async fn list_items(&self) -> Result<StoreResponse, StoreError> {
+        let items = self.get_list_items().await?;
+        Ok(StoreResponse::List(List::new().with_items(items)))
+    }
+
+    async fn list_items_missing_from_library(&self) -> Result<Vec<i32>, StoreError> {
+        use crate::schema::{items, list};
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(list::table
+                    .filter(list::id.ne_all(items::table.select(items::id)))
+                    .select(list::id)
+                    .order_by(list::id)
+                    .load(connection)?)
+            })
+        })
+        .await?
+    }
+
+    async fn items_from_recipe_on_list(
+        &self,
+        recipe: &Recipe,
+    ) -> Result<Vec<common::item::Item>, StoreError> {
+        use crate::schema::{items, items_recipes, list, list_recipes, recipes};
+
+        let store = self.clone();
+        let recipe = recipe.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(items::table
+                    .inner_join(list::table.on(list::id.eq(items::id)))
+                    .inner_join(items_recipes::table.on(items_recipes::item_id.eq(items::id)))
+                    .inner_join(
+                        list_recipes::table.on(list_recipes::id.eq(items_recipes::recipe_id)),
+                    )
+                    .inner_join(recipes::table.on(recipes::id.eq(list_recipes::id)))
+                    .filter(recipes::name.eq(recipe.as_str()))
+                    .filter(recipes::deleted_at.is_null())
+                    .select((items::id, items::name))
+                    .order_by(items::name.asc())
+                    .load::<Item>(connection)?
+                    .into_iter()
+                    .map(Into::into)
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn complete_trip(&self) -> Result<TripId, StoreError> {
+        use crate::schema::{items, list, list_recipes, trip_items, trips};
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let trip_id = diesel::insert_into(trips::table)
+                    .default_values()
+                    .returning(trips::id)
+                    .get_result::<TripId>(connection)?;
+                let rows: Vec<TripItemRow> = items::table
+                    .inner_join(list::table.on(list::id.eq(items::id)))
+                    .select(items::name)
+                    .load::<String>(connection)?
+                    .into_iter()
+                    .map(|item| TripItemRow { trip_id, item })
+                    .collect();
+                for chunk in rows.chunks(BATCH_SIZE) {
+                    diesel::insert_into(trip_items::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+
+                diesel::delete(list::table).execute(connection)?;
+                diesel::delete(schema::list_items_recipes::table).execute(connection)?;
+                diesel::delete(list_recipes::table).execute(connection)?;
+                Ok(trip_id)
+            })
+        })
+        .await?
+    }
+
+    async fn trips(&self) -> Result<Vec<Trip>, StoreError> {
+        use crate::schema::{trip_items, trips};
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let completed = trips::table
+                    .select(TripRow::as_select())
+                    .order_by(trips::id.desc())
+                    .load(connection)?;
+                let mut items: BTreeMap<TripId, Vec<Name>> = BTreeMap::new();
+                for row in trip_items::table
+                    .select(TripItemRow::as_select())
+                    .order_by((trip_items::trip_id, trip_items::item))
+                    .load(connection)?
+                {
+                    items
+                        .entry(row.trip_id)
+                        .or_default()
+                        .push(Name::from(row.item.as_str()));
+                }
+                Ok(completed
+                    .into_iter()
+                    .map(|trip| {
+                        let items = items.remove(&trip.id).unwrap_or_default();
+                        Trip::new(trip.id, trip.completed_at, items)
+                    })
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn trip(&self, id: TripId) -> Result<Trip, StoreError> {
+        use crate::schema::{trip_items, trips};
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let Some(trip) = trips::table
+                    .find(id)
+                    .select(TripRow::as_select())
+                    .first(connection)
+                    .optional()?
+                else {
+                    return Err(StoreError::TripNotFound(id));
+                };
+                let items = trip_items::table
+                    .filter(trip_items::trip_id.eq(id))
+                    .select(trip_items::item)
+                    .order_by(trip_items::item)
+                    .load::<String>(connection)?
+                    .into_iter()
+                    .map(|item| Name::from(item.as_str()))
+                    .collect();
+                Ok(Trip::new(trip.id, trip.completed_at, items))
+            })
+        })
+        .await?
+    }
+
+    async fn common_across_list_recipes(&self) -> Result<Vec<Name>, StoreError> {
+        use crate::schema::{items, items_recipes, list_recipes, recipes};
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let planned: i64 = list_recipes::table
+                    .inner_join(recipes::table.on(recipes::id.eq(list_recipes::id)))
+                    .filter(recipes::deleted_at.is_null())
+                    .count()
+                    .get_result(connection)?;
+                if planned == 0 {
+                    return Ok(Vec::new());
+                }
+
+                Ok(items::table
+                    .inner_join(items_recipes::table.on(items_recipes::item_id.eq(items::id)))
+                    .inner_join(
+                        list_recipes::table.on(list_recipes::id.eq(items_recipes::recipe_id)),
+                    )
+                    .inner_join(recipes::table.on(recipes::id.eq(list_recipes::id)))
+                    .filter(recipes::deleted_at.is_null())
+                    .group_by((items::id, items::name))
+                    .having(count(items_recipes::recipe_id).eq(planned))
+                    .select(items::name)
+                    .order_by(items::name.asc())
+                    .load::<String>(connection)?
+                    .into_iter()
+                    .map(|name| Name::from(name.as_str()))
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn suggest_section(
+        &self,
+        item: &Name,
+    ) -> Result<Option<common::section::Section>, StoreError> {
+        use crate::schema::items;
+
+        let store = self.clone();
+        let item = item.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let Some(item_id) = items::table
+                    .filter(items::name.eq(item.as_str()))
+                    .select(items::id)
+                    .first::<i32>(connection)
+                    .optional()?
+                else {
+                    return Err(StoreError::ItemNotFound(item.to_string()));
+                };
+                Self::suggested_section(connection, item_id)
+            })
+        })
+        .await?
+    }
+
+    async fn list_checklist_overlap(&self) -> Result<Vec<Name>, StoreError> {
+        use crate::schema::{checklist, items, list};
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(items::table
+                    .inner_join(list::table.on(list::id.eq(items::id)))
+                    .inner_join(checklist::table.on(checklist::id.eq(items::id)))
+                    .select(items::name)
+                    .order_by(items::name)
+                    .load::<String>(connection)?
+                    .iter()
+                    .map(|name| Name::from(name.as_str()))
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn item_detail(&self, name: &Name) -> Result<common::item::ItemDetail, StoreError> {
+        use crate::schema::{checklist, items, list};
+
+        let store = self.clone();
+        let name = name.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let Some((item, on_list, on_checklist)) = items::table
+                    .filter(items::name.eq(name.as_str()))
+                    .select((
+                        Item::as_select(),
+                        diesel::dsl::exists(list::table.filter(list::id.eq(items::id))),
+                        diesel::dsl::exists(checklist::table.filter(checklist::id.eq(items::id))),
+                    ))
+                    .first::<(Item, bool, bool)>(connection)
+                    .optional()?
+                else {
+                    return Err(StoreError::ItemNotFound(name.to_string()));
+                };
+                let items = Self::with_sections_and_recipes(connection, vec![item])?;
+                Ok(common::item::ItemDetail::new(
+                    items.collection()[0].clone(),
+                    on_list,
+                    on_checklist,
+                ))
+            })
+        })
+        .await?
+    }
+
+    async fn item_or_suggestions(
+        &self,
+        name: &Name,
+    ) -> Result<common::item::ItemLookup, StoreError> {
+        use crate::schema::items;
+
+        let store = self.clone();
+        let name = name.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                if let Some(item) = items::table
+                    .filter(items::name.eq(name.as_str()))
+                    .select(Item::as_select())
+                    .first(connection)
+                    .optional()?
+                {
+                    let items = Self::with_sections_and_recipes(connection, vec![item])?;
+                    return Ok(common::item::ItemLookup::Found(
+                        items.collection()[0].clone(),
+                    ));
+                }
+
+                let mut near: Vec<(usize, String)> = items::table
+                    .select(items::name)
+                    .load::<String>(connection)?
+                    .into_iter()
+                    .filter_map(|candidate| {
+                        let distance = edit_distance(name.as_str(), &candidate);
+                        (distance <= SUGGESTION_DISTANCE || candidate.contains(name.as_str()))
+                            .then_some((distance, candidate))
+                    })
+                    .collect();
+                near.sort();
+                Ok(common::item::ItemLookup::NotFound {
+                    query: name,
+                    suggestions: near
+                        .into_iter()
+                        .take(MAX_SUGGESTIONS)
+                        .map(|(_, candidate)| Name::from(candidate.as_str()))
+                        .collect(),
+                })
+            })
+        })
+        .await?
+    }
+
+    async fn duplicate_item_groups(&self) -> Result<Vec<Vec<Name>>, StoreError> {
+        use crate::schema::items;
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let mut groups: BTreeMap<String, Vec<Name>> = BTreeMap::new();
+                for name in items::table
+                    .select(items::name)
+                    .order_by(items::name)
+                    .load::<String>(connection)?
+                {
+                    groups
+                        .entry(name.trim().to_lowercase())
+                        .or_default()
+                        .push(Name::new_unchecked(name));
+                }
+                Ok(groups
+                    .into_values()
+                    .filter(|group| group.len() > 1)
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn list_recipes(&self) -> Result<Vec<Recipe>, StoreError> {
+        self.get_list_recipes().await
+    }
+
+    async fn list_recipe_status(&self) -> Result<StoreResponse, StoreError> {
+        use crate::schema::{items_recipes, list, list_recipes, recipes};
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(StoreResponse::ListRecipeStatus(
+                    recipes::table
+                        .inner_join(list_recipes::table.on(list_recipes::id.eq(recipes::id)))
+                        .left_join(
+                            items_recipes::table.on(items_recipes::recipe_id.eq(recipes::id)),
+                        )
+                        .left_join(
+                            list::table
+                                .on(list::id.nullable().eq(items_recipes::item_id.nullable())),
+                        )
+                        .group_by((recipes::id, recipes::name))
+                        .select((
+                            recipes::name,
+                            count(items_recipes::item_id.nullable()),
+                            count(list::id.nullable()),
+                        ))
+                        .order_by(recipes::name.asc())
+                        .load::<(String, i64, i64)>(connection)?
+                        .into_iter()
+                        .map(|(name, total, on_list)| {
+                            RecipeStatus::new(
+                                Recipe::new_unchecked(name),
+                                on_list as usize,
+                                (total - on_list) as usize,
+                            )
+                        })
+                        .collect(),
+                ))
+            })
+        })
+        .await?
+    }
+
+    async fn recipes_by_list_coverage(&self) -> Result<Vec<(Recipe, i64, i64)>, StoreError> {
+        use crate::schema::{items_recipes, list, recipes};
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let mut coverage = recipes::table
+                    .inner_join(items_recipes::table.on(items_recipes::recipe_id.eq(recipes::id)))
+                    .left_join(list::table.on(list::id.eq(items_recipes::item_id)))
+                    .filter(recipes::deleted_at.is_null())
+                    .group_by((recipes::id, recipes::name))
+                    .select((
+                        recipes::name,
+                        count(list::id.nullable()),
+                        count(items_recipes::item_id),
+                    ))
+                    .order_by(recipes::name.asc())
+                    .load::<(String, i64, i64)>(connection)?;
+
+                // Compare have/total ratios without dividing; the sort is
+                // stable, so equal ratios stay in name order
+                coverage.sort_by(|(_, a_have, a_total), (_, b_have, b_total)| {
+                    (b_have * a_total).cmp(&(a_have * b_total))
+                });
+                Ok(coverage
+                    .into_iter()
+                    .map(|(name, have, total)| (Recipe::new_unchecked(name), have, total))
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn clear_checklist(&self) -> Result<StoreResponse, StoreError> {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                diesel::delete(schema::checklist::table).execute(connection)?;
+                Ok(StoreResponse::ClearedChecklist)
+            })
+        })
+        .await?
+    }
+
+    async fn delete_checklist_item(&self, item: &Name) -> Result<StoreResponse, StoreError> {
+        let store = self.clone();
+        let item = item.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                diesel::delete(
+                    schema::checklist::table.filter(
+                        schema::checklist::dsl::id.eq_any(
+                            schema::items::table
+                                .select(schema::items::dsl::id)
+                                .filter(schema::items::dsl::name.eq(item.as_str())),
+                        ),
+                    ),
+                )
+                .execute(connection)?;
+                Ok(StoreResponse::DeletedChecklistItem(item.clone()))
+            })
+        })
+        .await?
+    }
+
+    async fn delete_item(&self, item: &Name) -> Result<StoreResponse, StoreError> {
+        use schema::{checklist, items, items_recipes, items_sections, list, subscriptions};
+
+        let store = self.clone();
+        let item = item.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let Some(id) = items::table
+                    .filter(items::name.eq(item.as_str()))
+                    .select(items::id)
+                    .first::<i32>(connection)
+                    .optional()?
+                else {
+                    return Err(StoreError::ItemNotFound(item.to_string()));
+                };
+                diesel::delete(list::table.filter(list::id.eq(id))).execute(connection)?;
+                Self::forget_unlisted_items(connection)?;
+                diesel::delete(checklist::table.filter(checklist::id.eq(id)))
+                    .execute(connection)?;
+                diesel::delete(subscriptions::table.filter(subscriptions::id.eq(id)))
+                    .execute(connection)?;
+                diesel::delete(items_recipes::table.filter(items_recipes::item_id.eq(id)))
+                    .execute(connection)?;
+                diesel::delete(items_sections::table.filter(items_sections::item_id.eq(id)))
+                    .execute(connection)?;
+                diesel::delete(items::table.filter(items::id.eq(id))).execute(connection)?;
+                Ok(StoreResponse::DeletedItem(item.clone()))
+            })
+        })
+        .await?
+    }
+
+    async fn delete_list_item(&self, item: &Name) -> Result<StoreResponse, StoreError> {
+        let store = self.clone();
+        let item = item.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                diesel::delete(
+                    schema::list::table.filter(
+                        schema::list::dsl::id.eq_any(
+                            schema::items::table
+                                .select(schema::items::dsl::id)
+                                .filter(schema::items::dsl::name.eq(item.as_str())),
+                        ),
+                    ),
+                )
+                .execute(connection)?;
+                Self::forget_unlisted_items(connection)?;
+                Ok(StoreResponse::DeletedListItem(item.clone()))
+            })
+        })
+        .await?
+    }
+
+    async fn remove_list_recipe(
+        &self,
+        recipe: &Recipe,
+        with_items: bool,
+    ) -> Result<(), StoreError> {
+        let store = self.clone();
+        let recipe = recipe.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let Some(id) = Self::get_recipe_id(connection, recipe.as_str())? else {
+                    return Err(StoreError::RecipeNotFound(recipe.to_string()));
+                };
+                if with_items {
+                    Self::remove_list_recipe_items(connection, id)?;
+                } else {
+                    Self::forget_list_recipe_items(connection, id)?;
+                }
+                diesel::delete(schema::list_recipes::table.find(id)).execute(connection)?;
+                Ok(())
+            })
+        })
+        .await?
+    }
+
+    async fn delete_recipe(&self, recipe: &Recipe) -> Result<StoreResponse, StoreError> {
+        use crate::schema::{list_items_recipes, list_recipes, recipes};
+
+        let store = self.clone();
+        let recipe = recipe.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let recipe_query = recipes::table
+                    .filter(recipes::name.eq(recipe.as_str()))
+                    .filter(recipes::deleted_at.is_null());
+                diesel::delete(
+                    list_recipes::table
+                        .filter(list_recipes::id.eq_any(recipe_query.select(recipes::id))),
+                )
+                .execute(connection)?;
+                diesel::delete(list_items_recipes::table.filter(
+                    list_items_recipes::recipe_id.eq_any(recipe_query.select(recipes::id)),
+                ))
+                .execute(connection)?;
+                // Keep the recipe and its ingredients around so it can be restored
+                diesel::update(recipe_query)
+                    .set(recipes::deleted_at.eq(now.nullable()))
+                    .execute(connection)?;
+                Ok(StoreResponse::DeletedRecipe(recipe.clone()))
+            })
+        })
+        .await?
+    }
+
+    async fn rename_recipe(
+        &self,
+        recipe: &Recipe,
+        new_name: &Recipe,
+    ) -> Result<StoreResponse, StoreError> {
+        use crate::schema::recipes;
+
+        let store = self.clone();
+        let recipe = recipe.clone();
+        let new_name = new_name.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let Some(id) = Self::get_recipe_id(connection, recipe.as_str())? else {
+                    return Err(StoreError::RecipeNotFound(recipe.to_string()));
+                };
+                match Self::get_recipe_id(connection, new_name.as_str())? {
+                    Some(existing) if existing != id => {
+                        return Err(StoreError::RecipeExists(new_name.to_string()));
+                    }
+                    Some(_) => {
+                        return Ok(StoreResponse::RenamedRecipe(
+                            recipe.clone(),
+                            new_name.clone(),
+                        ))
+                    }
+                    None => Self::purge_deleted_recipe(connection, new_name.as_str())?,
+                }
+                diesel::update(recipes::table.find(id))
+                    .set(recipes::name.eq(new_name.as_str()))
+                    .execute(connection)?;
+                Ok(StoreResponse::RenamedRecipe(
+                    recipe.clone(),
+                    new_name.clone(),
+                ))
+            })
+        })
+        .await?
+    }
+
+    async fn merge_recipes(&self, keep: &Recipe, remove: &Recipe) -> Result<(), StoreError> {
+        use crate::schema::{
+            fetched_recipes, items_recipes, list_items_recipes, list_recipes, recipes,
+        };
+
+        let store = self.clone();
+        let keep = keep.clone();
+        let remove = remove.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let mut find = |recipe: &Recipe| {
+                    Self::get_recipe_id(connection, recipe.as_str())?
+                        .ok_or_else(|| StoreError::RecipeNotFound(recipe.to_string()))
+                };
+                let keep_id = find(&keep)?;
+                let remove_id = find(&remove)?;
+                if keep_id == remove_id {
+                    return Ok(());
+                }
+
+                diesel::insert_or_ignore_into(items_recipes::table)
+                    .values(
+                        items_recipes::table
+                            .filter(items_recipes::recipe_id.eq(remove_id))
+                            .select((
+                                items_recipes::item_id,
+                                keep_id.into_sql::<diesel::sql_types::Integer>(),
+                                items_recipes::quantity,
+                                items_recipes::unit,
+                            )),
+                    )
+                    .into_columns((
+                        items_recipes::item_id,
+                        items_recipes::recipe_id,
+                        items_recipes::quantity,
+                        items_recipes::unit,
+                    ))
+                    .execute(connection)?;
+                diesel::delete(items_recipes::table.filter(items_recipes::recipe_id.eq(remove_id)))
+                    .execute(connection)?;
+
+                if diesel::delete(list_recipes::table.find(remove_id)).execute(connection)? > 0 {
+                    diesel::insert_or_ignore_into(list_recipes::table)
+                        .values(NewListRecipe { id: keep_id })
+                        .execute(connection)?;
+                }
+                diesel::insert_or_ignore_into(list_items_recipes::table)
+                    .values(
+                        list_items_recipes::table
+                            .filter(list_items_recipes::recipe_id.eq(remove_id))
+                            .select((
+                                list_items_recipes::item_id,
+                                keep_id.into_sql::<diesel::sql_types::Integer>(),
+                            )),
+                    )
+                    .into_columns((list_items_recipes::item_id, list_items_recipes::recipe_id))
+                    .execute(connection)?;
+                Self::forget_list_recipe_items(connection, remove_id)?;
+                diesel::update(
+                    fetched_recipes::table.filter(fetched_recipes::recipe_id.eq(remove_id)),
+                )
+                .set(fetched_recipes::recipe_id.eq(keep_id))
+                .execute(connection)?;
+                diesel::delete(recipes::table.find(remove_id)).execute(connection)?;
+                Ok(())
+            })
+        })
+        .await?
+    }
+
+    async fn substitute_ingredient(
+        &self,
+        from: &Name,
+        to: &Name,
+    ) -> Result<StoreResponse, StoreError> {
+        use crate::schema::items_recipes;
+
+        let store = self.clone();
+        let from = from.clone();
+        let to = to.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let Some(from_id) = Self::get_item_id(connection, from.as_str())? else {
+                    return Err(StoreError::ItemNotFound(from.to_string()));
+                };
+                let to_id = Self::get_or_insert_item(connection, to.as_str())?;
+                if from_id != to_id {
+                    // The rows move over with their quantity and unit, except
+                    // where the recipe already calls for `to`
+                    let to_rows = diesel::alias!(items_recipes as to_rows);
+                    diesel::update(
+                        items_recipes::table
+                            .filter(items_recipes::item_id.eq(from_id))
+                            .filter(
+                                items_recipes::recipe_id.ne_all(
+                                    to_rows
+                                        .filter(to_rows.field(items_recipes::item_id).eq(to_id))
+                                        .select(to_rows.field(items_recipes::recipe_id)),
+                                ),
+                            ),
+                    )
+                    .set(items_recipes::item_id.eq(to_id))
+                    .execute(connection)?;
+                    diesel::delete(items_recipes::table.filter(items_recipes::item_id.eq(from_id)))
+                        .execute(connection)?;
+                }
+                Ok(StoreResponse::SubstitutedIngredient(
+                    from.clone(),
+                    to.clone(),
+                ))
+            })
+        })
+        .await?
+    }
+
+    async fn rename_section(
+        &self,
+        section: &common::section::Section,
+        new_name: &common::section::Section,
+    ) -> Result<StoreResponse, StoreError> {
+        use crate::schema::{items_sections, sections};
+
+        let store = self.clone();
+        let section = section.clone();
+        let new_name = new_name.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let Some(id) = Self::get_section_id(connection, section.as_str())? else {
+                    return Err(StoreError::SectionNotFound(section.to_string()));
+                };
+                match Self::get_section_id(connection, new_name.as_str())? {
+                    Some(existing) if existing != id => {
+                        diesel::insert_or_ignore_into(items_sections::table)
+                            .values(
+                                items_sections::table
+                                    .filter(items_sections::section_id.eq(id))
+                                    .select((
+                                        items_sections::item_id,
+                                        existing.into_sql::<diesel::sql_types::Integer>(),
+                                    )),
+                            )
+                            .into_columns((items_sections::item_id, items_sections::section_id))
+                            .execute(connection)?;
+                        diesel::delete(
+                            items_sections::table.filter(items_sections::section_id.eq(id)),
+                        )
+                        .execute(connection)?;
+                        diesel::delete(sections::table.filter(sections::id.eq(id)))
+                            .execute(connection)?;
+                    }
+                    _ => {
+                        diesel::update(sections::table.filter(sections::id.eq(id)))
+                            .set(sections::name.eq(new_name.as_str()))
+                            .execute(connection)?;
+                    }
+                }
+                Ok(StoreResponse::RenamedSection(
+                    section.clone(),
+                    new_name.clone(),
+                ))
+            })
+        })
+        .await?
+    }
+
+    async fn restore_recipe(&self, recipe: &Recipe) -> Result<StoreResponse, StoreError> {
+        use crate::schema::recipes;
+
+        let store = self.clone();
+        let recipe = recipe.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let restored = diesel::update(
+                    recipes::table
+                        .filter(recipes::name.eq(recipe.as_str()))
+                        .filter(recipes::deleted_at.is_not_null()),
+                )
+                .set(recipes::deleted_at.eq(None::<String>))
+                .execute(connection)?;
+                if restored == 0 {
+                    return Err(StoreError::RecipeNotFound(recipe.to_string()));
+                }
+                Ok(StoreResponse::RestoredRecipe(recipe.clone()))
+            })
+        })
+        .await?
+    }
+
+    async fn dump(&self) -> Result<DatabaseDump, StoreError> {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(DatabaseDump {
+                    items: schema::items::table
+                        .select(ItemRow::as_select())
+                        .order_by(schema::items::id)
+                        .load(connection)?,
+                    recipes: schema::recipes::table
+                        .select(RecipeRow::as_select())
+                        .order_by(schema::recipes::id)
+                        .load(connection)?,
+                    sections: schema::sections::table
+                        .select(SectionRow::as_select())
+                        .order_by(schema::sections::id)
+                        .load(connection)?,
+                    items_recipes: schema::items_recipes::table
+                        .select(ItemRecipeRow::as_select())
+                        .order_by((
+                            schema::items_recipes::item_id,
+                            schema::items_recipes::recipe_id,
+                        ))
+                        .load(connection)?,
+                    items_sections: schema::items_sections::table
+                        .select(ItemSectionRow::as_select())
+                        .order_by((
+                            schema::items_sections::item_id,
+                            schema::items_sections::section_id,
+                        ))
+                        .load(connection)?,
+                    list: schema::list::table
+                        .select(ListRow::as_select())
+                        .order_by(schema::list::id)
+                        .load(connection)?,
+                    list_recipes: schema::list_recipes::table
+                        .select(schema::list_recipes::id)
+                        .order_by(schema::list_recipes::id)
+                        .load(connection)?,
+                    checklist: schema::checklist::table
+                        .select(schema::checklist::id)
+                        .order_by(schema::checklist::id)
+                        .load(connection)?,
+                    subscriptions: schema::subscriptions::table
+                        .select(schema::subscriptions::id)
+                        .order_by(schema::subscriptions::id)
+                        .load(connection)?,
+                    fetched_recipes: schema::fetched_recipes::table
+                        .select(FetchedRecipeRow::as_select())
+                        .order_by(schema::fetched_recipes::url)
+                        .load(connection)?,
+                    trips: schema::trips::table
+                        .select(TripRow::as_select())
+                        .order_by(schema::trips::id)
+                        .load(connection)?,
+                    trip_items: schema::trip_items::table
+                        .select(TripItemRow::as_select())
+                        .order_by((schema::trip_items::trip_id, schema::trip_items::item))
+                        .load(connection)?,
+                    list_items_recipes: schema::list_items_recipes::table
+                        .select(ListItemRecipeRow::as_select())
+                        .order_by((
+                            schema::list_items_recipes::item_id,
+                            schema::list_items_recipes::recipe_id,
+                        ))
+                        .load(connection)?,
+                })
+            })
+        })
+        .await?
+    }
+
+    async fn restore(&self, dump: &DatabaseDump) -> Result<(), StoreError> {
+        let store = self.clone();
+        let dump = dump.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                clear_tables(connection)?;
+                diesel::delete(schema::subscriptions::table).execute(connection)?;
+                diesel::delete(schema::fetched_recipes::table).execute(connection)?;
+                diesel::delete(schema::trip_items::table).execute(connection)?;
+                diesel::delete(schema::trips::table).execute(connection)?;
+
+                for chunk in dump.items.chunks(BATCH_SIZE) {
+                    diesel::insert_into(schema::items::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+                for chunk in dump.recipes.chunks(BATCH_SIZE) {
+                    diesel::insert_into(schema::recipes::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+                for chunk in dump.sections.chunks(BATCH_SIZE) {
+                    diesel::insert_into(schema::sections::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+                for chunk in dump.items_recipes.chunks(BATCH_SIZE) {
+                    diesel::insert_into(schema::items_recipes::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+                for chunk in dump.items_sections.chunks(BATCH_SIZE) {
+                    diesel::insert_into(schema::items_sections::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+                for chunk in dump.list.chunks(BATCH_SIZE) {
+                    diesel::insert_into(schema::list::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+                for chunk in dump.fetched_recipes.chunks(BATCH_SIZE) {
+                    diesel::insert_into(schema::fetched_recipes::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+                let list_recipes: Vec<NewListRecipe> = dump
+                    .list_recipes
+                    .iter()
+                    .map(|&id| NewListRecipe { id })
+                    .collect();
+                for chunk in list_recipes.chunks(BATCH_SIZE) {
+                    diesel::insert_into(schema::list_recipes::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+                for chunk in dump.list_items_recipes.chunks(BATCH_SIZE) {
+                    diesel::insert_into(schema::list_items_recipes::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+                let checklist: Vec<NewChecklistItem> = dump
+                    .checklist
+                    .iter()
+                    .map(|&id| NewChecklistItem { id })
+                    .collect();
+                for chunk in checklist.chunks(BATCH_SIZE) {
+                    diesel::insert_into(schema::checklist::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+                let subscriptions: Vec<NewSubscription> = dump
+                    .subscriptions
+                    .iter()
+                    .map(|&id| NewSubscription { id })
+                    .collect();
+                for chunk in subscriptions.chunks(BATCH_SIZE) {
+                    diesel::insert_into(schema::subscriptions::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+                for chunk in dump.trips.chunks(BATCH_SIZE) {
+                    diesel::insert_into(schema::trips::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+                for chunk in dump.trip_items.chunks(BATCH_SIZE) {
+                    diesel::insert_into(schema::trip_items::table)
+                        .values(chunk)
+                        .execute(connection)?;
+                }
+                Ok(())
+            })
+        })
+        .await?
+    }
+
+    #[cfg(feature = "fetch-limiter")]
+    fn fetch_limiter(&self) -> FetchLimiter {
+        self.fetch_limiter.clone()
+    }
+
+    async fn export(&self) -> Result<StoreResponse, StoreError> {
+        let items = self.items().await?;
+        let StoreResponse::List(list) = self.list().await? else {
+            todo!()
+        };
+
+        let items = items.collection().to_vec();
+
+        items.serialize_to_yaml_and_write(ITEMS_YAML_PATH)?;
+        list.serialize_to_yaml_and_write(LIST_YAML_PATH)?;
+
+        Ok(StoreResponse::Exported(items, list))
+    }
+
+    async fn import_from_json(&self, fresh: bool) -> Result<StoreResponse, StoreError> {
+        self.import_from(ImportStore::default(), fresh).await
+    }
+
+    async fn applied_migrations(&self) -> Result<Vec<String>, StoreError> {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            applied_migrations(&mut connection)
+        })
+        .await?
+    }
+
+    async fn pending_migrations(&self) -> Result<Vec<String>, StoreError> {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            pending_migrations(&mut connection)
+        })
+        .await?
+    }
+
+    async fn is_empty(&self) -> Result<bool, StoreError> {
+        use crate::schema::items;
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(items::table.count().get_result::<i64>(connection)? == 0)
+            })
+        })
+        .await?
+    }
+
+    async fn items(&self) -> Result<Items, StoreError> {
+        use crate::schema::items;
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let all_items: Vec<Item> = items::dsl::items
+                    .select(Item::as_select())
+                    .order_by(items::id)
+                    .load(connection)?;
+
+                Self::with_sections_and_recipes(connection, all_items)
+            })
+        })
+        .await?
+    }
+
+    fn items_stream(&self) -> mpsc::Receiver<Result<common::item::Item, StoreError>> {
+        use crate::schema::items;
+
+        let mut last_id = i32::MIN;
+        self.stream_batches(move |connection| {
+            let batch: Vec<Item> = items::table
+                .filter(items::id.gt(last_id))
+                .select(Item::as_select())
+                .order_by(items::id)
+                .limit(BATCH_SIZE as i64)
+                .load(connection)?;
+            if let Some(item) = batch.last() {
+                last_id = item.id;
+            }
+            Ok(Self::with_sections_and_recipes(connection, batch)?
+                .collection()
+                .to_vec())
+        })
+    }
+
+    async fn items_by_prefix(&self, prefix: &str) -> Result<Items, StoreError> {
+        use crate::schema::items;
+
+        let store = self.clone();
+        let pattern = format!("{}%", escape_like(prefix));
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let matching: Vec<Item> = items::table
+                    .filter(items::name.like(pattern).escape('\\'))
+                    .select(Item::as_select())
+                    .order_by(items::name)
+                    .load(connection)?;
+
+                Self::with_sections_and_recipes(connection, matching)
+            })
+        })
+        .await?
+    }
+
+    async fn items_with_sections(
+        &self,
+    ) -> Result<Vec<(common::item::Item, Option<common::section::Section>)>, StoreError> {
+        use crate::schema::{items, items_sections, sections};
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(items::table
+                    .left_join(items_sections::table.on(items_sections::item_id.eq(items::id)))
+                    .left_join(sections::table.on(sections::id.eq(items_sections::section_id)))
+                    .select((items::name, sections::name.nullable()))
+                    .order_by(items::name.asc())
+                    .load::<(String, Option<String>)>(connection)?
+                    .into_iter()
+                    .map(|(item, section)| {
+                        (
+                            common::item::Item::new(item),
+                            section.map(common::section::Section::from),
+                        )
+                    })
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn items_in_section(
+        &self,
+        section: &common::section::Section,
+    ) -> Result<Items, StoreError> {
+        use crate::schema::{items, items_sections, sections};
+
+        let store = self.clone();
+        let section = section.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let section_items = items::table
+                    .inner_join(items_sections::table)
+                    .inner_join(sections::table.on(sections::id.eq(items_sections::section_id)))
+                    .filter(sections::name.eq(section.as_str()))
+                    .select((items::id, items::name))
+                    .order_by(items::id)
+                    .load::<Item>(connection)?;
+
+                Self::with_sections_and_recipes(connection, section_items)
+            })
+        })
+        .await?
+    }
+
+    async fn clear_list(&self) -> Result<StoreResponse, StoreError> {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                diesel::delete(schema::list::table).execute(connection)?;
+                diesel::delete(schema::list_items_recipes::table).execute(connection)?;
+                diesel::delete(schema::list_recipes::table).execute(connection)?;
+                Ok(StoreResponse::ClearedList)
+            })
+        })
+        .await?
+    }
+
+    async fn add_all_items_to_list(&self) -> Result<usize, StoreError> {
+        use crate::schema::items;
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let mut added = 0;
+                for id in items::table.select(items::id).load::<i32>(connection)? {
+                    if Self::insert_list_item(connection, id, false)? {
+                        added += 1;
+                    }
+                }
+                Ok(added)
+            })
+        })
+        .await?
+    }
+
+    async fn set_list(&self, items: &[Name]) -> Result<(), StoreError> {
+        let store = self.clone();
+        let items = items.to_vec();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                diesel::delete(schema::list::table).execute(connection)?;
+                diesel::delete(schema::list_items_recipes::table).execute(connection)?;
+                for item in &items {
+                    let id = Self::get_or_insert_item(connection, item.as_str())?;
+                    Self::insert_list_item(connection, id, true)?;
+                }
+                Ok(())
+            })
+        })
+        .await?
+    }
+
+    async fn toggle_list_item(&self, name: &Name) -> Result<bool, StoreError> {
+        let store = self.clone();
+        let name = name.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let id = Self::get_or_insert_item(connection, name.as_str())?;
+                let removed = diesel::delete(schema::list::table.filter(schema::list::id.eq(id)))
+                    .execute(connection)?;
+                if removed > 0 {
+                    Self::forget_unlisted_items(connection)?;
+                    return Ok(false);
+                }
+                Self::insert_list_item(connection, id, true)?;
+                Ok(true)
+            })
+        })
+        .await?
+    }
+
+    async fn refresh_list(&self, subscriptions: bool) -> Result<StoreResponse, StoreError> {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                diesel::delete(schema::list::table).execute(connection)?;
+                diesel::delete(schema::list_items_recipes::table).execute(connection)?;
+                if subscriptions {
+                    // subscriptions are kept on the list like items added by hand
+                    for id in schema::subscriptions::table
+                        .select(schema::subscriptions::id)
+                        .load::<i32>(connection)?
+                    {
+                        Self::insert_list_item(connection, id, true)?;
+                    }
+                }
+                Ok(StoreResponse::RefreshList)
+            })
+        })
+        .await?
+    }
+
+    async fn refresh_list_keeping(&self, keep: &[Name]) -> Result<StoreResponse, StoreError> {
+        use crate::schema::{items, list};
+
+        let store = self.clone();
+        let keep: Vec<String> = keep.iter().map(|name| name.to_string()).collect();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                diesel::delete(
+                    list::table.filter(
+                        list::id.ne_all(
+                            items::table
+                                .filter(items::name.eq_any(&keep))
+                                .select(items::id),
+                        ),
+                    ),
+                )
+                .execute(connection)?;
+                Self::forget_unlisted_items(connection)?;
+                Ok(StoreResponse::RefreshList)
+            })
+        })
+        .await?
+    }
+
+    async fn combined_ingredients(&self, recipes: &[Recipe]) -> Result<Ingredients, StoreError> {
+        use crate::schema::{items, items_recipes};
+
+        let store = self.clone();
+        let names = recipes.to_vec();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let mut recipe_ids = Vec::with_capacity(names.len());
+                for recipe in names {
+                    let Some(recipe_id) = Self::get_recipe_id(connection, recipe.as_str())? else {
+                        return Err(StoreError::RecipeNotFound(recipe.to_string()));
+                    };
+                    recipe_ids.push(recipe_id);
+                }
+
+                Ok(items_recipes::table
+                    .filter(items_recipes::recipe_id.eq_any(recipe_ids))
+                    .inner_join(items::table)
+                    .select(items::name)
+                    .distinct()
+                    .order_by(items::name.asc())
+                    .load::<String>(connection)?
+                    .iter()
+                    .map(|name| Name::from(name.as_str()))
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn recipes_not_on_list(&self) -> Result<Vec<Recipe>, StoreError> {
+        use crate::schema::{list_recipes, recipes};
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(recipes::table
+                    .filter(recipes::deleted_at.is_null())
+                    .filter(recipes::id.ne_all(list_recipes::table.select(list_recipes::id)))
+                    .order_by(recipes::name)
+                    .select(RecipeModel::as_select())
+                    .load(connection)?
+                    .into_iter()
+                    .map(Into::into)
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn empty_recipes(&self) -> Result<Vec<Recipe>, StoreError> {
+        use crate::schema::{items_recipes, recipes};
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(recipes::table
+                    .filter(recipes::deleted_at.is_null())
+                    .filter(
+                        recipes::id.ne_all(items_recipes::table.select(items_recipes::recipe_id)),
+                    )
+                    .order_by(recipes::name)
+                    .select(RecipeModel::as_select())
+                    .load(connection)?
+                    .into_iter()
+                    .map(Into::into)
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn empty_sections(&self) -> Result<Vec<common::section::Section>, StoreError> {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| Self::load_empty_sections(connection))
+        })
+        .await?
+    }
+
+    async fn delete_empty_sections(&self) -> Result<Vec<common::section::Section>, StoreError> {
+        use crate::schema::{items_sections, sections};
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let empty = Self::load_empty_sections(connection)?;
+                diesel::delete(sections::table.filter(
+                    sections::id.ne_all(items_sections::table.select(items_sections::section_id)),
+                ))
+                .execute(connection)?;
+                Ok(empty)
+            })
+        })
+        .await?
+    }
+
+    async fn item_id(&self, name: &Name) -> Result<Option<i32>, StoreError> {
+        let store = self.clone();
+        let name = name.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| Self::get_item_id(connection, name.as_str()))
+        })
+        .await?
+    }
+
+    async fn recipe_id(&self, recipe: &Recipe) -> Result<Option<i32>, StoreError> {
+        let store = self.clone();
+        let recipe = recipe.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| Self::get_recipe_id(connection, recipe.as_str()))
+        })
+        .await?
+    }
+
+    async fn recipe_shopping_status(
+        &self,
+        recipe: &Recipe,
+    ) -> Result<Vec<(Name, bool)>, StoreError> {
+        use crate::schema::{items, items_recipes, list};
+
+        let store = self.clone();
+        let recipe = recipe.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let Some(recipe_id) = Self::get_recipe_id(connection, recipe.as_str())? else {
+                    return Err(StoreError::RecipeNotFound(recipe.to_string()));
+                };
+
+                Ok(items_recipes::table
+                    .filter(items_recipes::recipe_id.eq(recipe_id))
+                    .inner_join(items::table)
+                    .left_join(list::table.on(list::id.eq(items::id)))
+                    .select((items::name, list::id.nullable()))
+                    .order_by(items::name.asc())
+                    .load::<(String, Option<i32>)>(connection)?
+                    .into_iter()
+                    .map(|(name, on_list)| (Name::from(name.as_str()), on_list.is_some()))
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn recipe_ingredient_count(&self, recipe: &Recipe) -> Result<i64, StoreError> {
+        use crate::schema::items_recipes;
+
+        let store = self.clone();
+        let recipe = recipe.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let Some(recipe_id) = Self::get_recipe_id(connection, recipe.as_str())? else {
+                    return Err(StoreError::RecipeNotFound(recipe.to_string()));
+                };
+
+                Ok(items_recipes::table
+                    .filter(items_recipes::recipe_id.eq(recipe_id))
+                    .select(count(items_recipes::item_id))
+                    .first(connection)?)
+            })
+        })
+        .await?
+    }
+
+    async fn db_stats(&self) -> Result<DbStats, StoreError> {
+        use crate::schema::{
+            checklist, fetched_recipes, items, items_recipes, items_sections, list,
+            list_items_recipes, list_recipes, recipes, sections, subscriptions, trip_items, trips,
+        };
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let rows = [
+                    (
+                        "checklist",
+                        checklist::table.count().get_result(connection)?,
+                    ),
+                    (
+                        "fetched_recipes",
+                        fetched_recipes::table.count().get_result(connection)?,
+                    ),
+                    ("items", items::table.count().get_result(connection)?),
+                    (
+                        "items_recipes",
+                        items_recipes::table.count().get_result(connection)?,
+                    ),
+                    (
+                        "items_sections",
+                        items_sections::table.count().get_result(connection)?,
+                    ),
+                    ("list", list::table.count().get_result(connection)?),
+                    (
+                        "list_items_recipes",
+                        list_items_recipes::table.count().get_result(connection)?,
+                    ),
+                    (
+                        "list_recipes",
+                        list_recipes::table.count().get_result(connection)?,
+                    ),
+                    ("recipes", recipes::table.count().get_result(connection)?),
+                    ("sections", sections::table.count().get_result(connection)?),
+                    (
+                        "subscriptions",
+                        subscriptions::table.count().get_result(connection)?,
+                    ),
+                    (
+                        "trip_items",
+                        trip_items::table.count().get_result(connection)?,
+                    ),
+                    ("trips", trips::table.count().get_result(connection)?),
+                ]
+                .into_iter()
+                .map(|(table, rows)| (table.to_string(), rows))
+                .collect();
+
+                // An in-memory database is listed without a file
+                let file: String = diesel::select(sql::<Text>(
+                    "(SELECT file FROM pragma_database_list WHERE name = 'main')",
+                ))
+                .get_result(connection)?;
+                let file_size = if file.is_empty() {
+                    None
+                } else {
+                    Some(std::fs::metadata(file)?.len())
+                };
+
+                Ok(DbStats { rows, file_size })
+            })
+        })
+        .await?
+    }
+
+    async fn distinct_units(&self) -> Result<Vec<String>, StoreError> {
+        use crate::schema::items_recipes;
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(items_recipes::table
+                    .filter(items_recipes::unit.is_not_null())
+                    .select(items_recipes::unit.assume_not_null())
+                    .distinct()
+                    .order_by(items_recipes::unit)
+                    .load(connection)?)
+            })
+        })
+        .await?
+    }
+
+    async fn normalize_unit(&self, from: &str, to: &str) -> Result<usize, StoreError> {
+        use crate::schema::items_recipes;
+
+        let store = self.clone();
+        let (from, to) = (from.to_string(), to.to_string());
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                Ok(
+                    diesel::update(items_recipes::table.filter(items_recipes::unit.eq(&from)))
+                        .set(items_recipes::unit.eq(&to))
+                        .execute(connection)?,
+                )
+            })
+        })
+        .await?
+    }
+
+    async fn set_ingredient_amount(
+        &self,
+        recipe: &Recipe,
+        item: &Name,
+        quantity: i32,
+        unit: Option<&str>,
+    ) -> Result<(), StoreError> {
+        let store = self.clone();
+        let recipe = recipe.clone();
+        let item = item.clone();
+        let unit = unit.map(str::to_string);
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let recipe_id = Self::get_recipe_id(connection, recipe.as_str())?
+                    .ok_or_else(|| StoreError::RecipeNotFound(recipe.to_string()))?;
+                Self::update_ingredient_amount(connection, recipe_id, &item, quantity, unit.clone())
+            })
+        })
+        .await?
+    }
+
+    async fn import_checklist_json(&self, json: &str) -> Result<Vec<Name>, StoreError> {
+        let names: Vec<Name> = serde_json::from_str::<Vec<String>>(json)?
+            .iter()
+            .map(|name| Name::from(name.as_str()))
+            .collect();
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                for name in &names {
+                    let id = Self::get_or_insert_item(connection, name.as_str())?;
+                    diesel::insert_into(schema::checklist::table)
+                        .values(NewChecklistItem { id })
+                        .on_conflict_do_nothing()
+                        .execute(connection)?;
+                }
+                Ok(names.clone())
+            })
+        })
+        .await?
+    }
+
+    async fn import_paprika(&self, json: &str) -> Result<Vec<Recipe>, StoreError> {
+        let export = paprika::parse_export(json)?;
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(|connection| {
+                let mut recipes = Vec::new();
+                for (recipe, lines) in &export {
+                    let ingredients = lines.iter().map(|line| line.name().clone()).collect();
+                    store.check_ingredient_count(recipe, &ingredients)?;
+                    let recipe_id =
+                        Self::insert_recipe(connection, recipe, &ingredients, None)?.recipe_id();
+                    for line in lines {
+                        Self::update_ingredient_amount(
+                            connection,
+                            recipe_id,
+                            line.name(),
+                            line.quantity(),
+                            line.unit().map(String::from),
+                        )?;
+                    }
+                    recipes.push(recipe.clone());
+                }
+                Ok(recipes)
+            })
+        })
+        .await?
+    }
+
+    async fn ingredient_count_histogram(&self) -> Result<BTreeMap<i64, i64>, StoreError> {
+        use crate::schema::{items_recipes, recipes};
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let sizes = items_recipes::table
+                    .inner_join(recipes::table)
+                    .filter(recipes::deleted_at.is_null())
+                    .group_by(items_recipes::recipe_id)
+                    .select(count(items_recipes::item_id))
+                    .load::<i64>(connection)?;
+
+                let mut histogram = BTreeMap::new();
+                for size in sizes {
+                    *histogram.entry(size).or_insert(0) += 1;
+                }
+                Ok(histogram)
+            })
+        })
+        .await?
+    }
+
+    async fn recipe_ingredients(&self, recipe: &Recipe) -> Result<StoreResponse, StoreError> {
+        let store = self.clone();
+        let recipe = recipe.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let Some(results) = Self::get_recipe_model_for_recipe(connection, recipe.as_str())?
+                else {
+                    return Ok(StoreResponse::RecipeIngredients(None));
+                };
+
+                let mut v = Vec::<Ingredients>::with_capacity(results.len());
+
+                for recipe in results {
+                    let recipe_id = recipe.id;
+
+                    let ingredients = schema::items_recipes::table
+                        .filter(schema::items_recipes::dsl::recipe_id.eq(&recipe_id))
+                        .inner_join(schema::items::table)
+                        .select(schema::items::dsl::name)
+                        .order_by(schema::items::dsl::name.asc())
+                        .load::<String>(connection)?
+                        .iter()
+                        .map(|name| Name::from(name.as_str()))
+                        .collect::<Ingredients>();
+
+                    v.push(ingredients);
+                }
+
+                Ok(StoreResponse::RecipeIngredients(
+                    v.into_iter().take(1).next(),
+                ))
+            })
+        })
+        .await?
+    }
+
+    async fn recipe_ingredients_detailed(
+        &self,
+        recipe: &Recipe,
+    ) -> Result<Vec<(common::item::Item, Option<common::section::Section>)>, StoreError> {
+        use crate::schema::{items, items_recipes, items_sections, sections};
+
+        let store = self.clone();
+        let recipe = recipe.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let Some(recipe_id) = Self::get_recipe_id(connection, recipe.as_str())? else {
+                    return Err(StoreError::RecipeNotFound(recipe.to_string()));
+                };
+
+                Ok(items_recipes::table
+                    .filter(items_recipes::recipe_id.eq(recipe_id))
+                    .inner_join(items::table)
+                    .left_join(items_sections::table.on(items_sections::item_id.eq(items::id)))
+                    .left_join(sections::table.on(sections::id.eq(items_sections::section_id)))
+                    .select((items::name, sections::name.nullable()))
+                    .order_by(items::name.asc())
+                    .load::<(String, Option<String>)>(connection)?
+                    .into_iter()
+                    .map(|(item, section)| {
+                        (
+                            common::item::Item::new(item),
+                            section.map(common::section::Section::from),
+                        )
+                    })
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn seed_default_sections(&self) -> Result<usize, StoreError> {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.connection()?;
+            connection.write_transaction(import_sections)
+        })
+        .await?
+    }
+
+    async fn sections(&self) -> Result<StoreResponse, StoreError> {
+        use schema::sections::dsl::{name, position, sections};
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(StoreResponse::Sections(
+                    sections
+                        .order_by((position.is_null(), position, name))
+                        .select(Section::as_select())
+                        .load(connection)?
+                        .into_iter()
+                        .map(|sec| sec.name().into())
+                        .collect::<Vec<common::section::Section>>(),
+                ))
+            })
+        })
+        .await?
+    }
+
+    async fn recipes(&self) -> Result<StoreResponse, StoreError> {
+        use schema::recipes::dsl::{deleted_at, recipes};
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(StoreResponse::Recipes(
+                    recipes
+                        .filter(deleted_at.is_null())
+                        .select(RecipeModel::as_select())
+                        .load(connection)?
+                        .into_iter()
+                        .map(Into::into)
+                        .collect(),
+                ))
+            })
+        })
+        .await?
+    }
+
+    fn recipes_stream(&self) -> mpsc::Receiver<Result<Recipe, StoreError>> {
+        use crate::schema::recipes;
+
+        let mut last_id = i32::MIN;
+        self.stream_batches(move |connection| {
+            let batch: Vec<RecipeModel> = recipes::table
+                .filter(recipes::id.gt(last_id))
+                .filter(recipes::deleted_at.is_null())
+                .select(RecipeModel::as_select())
+                .order_by(recipes::id)
+                .limit(BATCH_SIZE as i64)
+                .load(connection)?;
+            if let Some(recipe) = batch.last() {
+                last_id = recipe.id;
+            }
+            Ok(batch.into_iter().map(Into::into).collect())
+        })
+    }
+
+    async fn recipes_paged_sorted(
+        &self,
+        offset: usize,
+        limit: usize,
+        sort: RecipeSort,
+    ) -> Result<Vec<Recipe>, StoreError> {
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(Self::load_recipes_page(connection, offset, limit, sort)?
+                    .into_iter()
+                    .map(Into::into)
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn recipe_previews_paged_sorted(
+        &self,
+        offset: usize,
+        limit: usize,
+        sort: RecipeSort,
+    ) -> Result<Vec<(Recipe, Vec<Name>)>, StoreError> {
+        #[derive(QueryableByName)]
+        struct PreviewRow {
+            #[diesel(sql_type = diesel::sql_types::Integer)]
+            recipe_id: i32,
+            #[diesel(sql_type = Text)]
+            name: String,
+        }
+
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                let page = Self::load_recipes_page(connection, offset, limit, sort)?;
+                let ids: Vec<i32> = page.iter().map(|recipe| recipe.id).collect();
+
+                // Numbering each recipe's ingredients lets SQLite drop all
+                // but the first few, rather than reading them all
+                let rows: Vec<PreviewRow> = diesel::sql_query(
+                    "SELECT recipe_id, name FROM ( \
+                         SELECT items_recipes.recipe_id AS recipe_id, items.name AS name, \
+                             ROW_NUMBER() OVER ( \
+                                 PARTITION BY items_recipes.recipe_id ORDER BY items.name \
+                             ) AS position \
+                         FROM items_recipes \
+                         INNER JOIN items ON items.id = items_recipes.item_id \
+                         WHERE items_recipes.recipe_id IN (SELECT value FROM json_each(?)) \
+                     ) \
+                     WHERE position <= ? \
+                     ORDER BY recipe_id, position",
+                )
+                .bind::<Text, _>(serde_json::to_string(&ids)?)
+                .bind::<diesel::sql_types::BigInt, _>(PREVIEW_INGREDIENTS as i64)
+                .load(connection)?;
+
+                let mut previews: HashMap<i32, Vec<Name>> = HashMap::new();
+                for row in rows {
+                    previews
+                        .entry(row.recipe_id)
+                        .or_default()
+                        .push(Name::from(row.name.as_str()));
+                }
+                Ok(page
+                    .into_iter()
+                    .map(|recipe| {
+                        let preview = previews.remove(&recipe.id).unwrap_or_default();
+                        (recipe.into(), preview)
+                    })
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn frequently_listed(&self, limit: usize) -> Result<Vec<(Name, usize)>, StoreError> {
+        use crate::schema::items;
+
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(items::table
+                    .filter(items::times_listed.gt(0))
+                    .order_by((items::times_listed.desc(), items::name.asc()))
+                    .select((items::name, items::times_listed))
+                    .limit(limit)
+                    .load::<(String, i32)>(connection)?
+                    .into_iter()
+                    .map(|(name, count)| (Name::from(name.as_str()), count as usize))
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn recipes_using(&self, item: &Name) -> Result<Vec<Recipe>, StoreError> {
+        use crate::schema::{items, items_recipes, recipes};
+
+        let store = self.clone();
+        let pattern = format!("%{}%", escape_like(item.as_str()));
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(recipes::table
+                    .inner_join(items_recipes::table.inner_join(items::table))
+                    .filter(items::name.like(pattern).escape('\\'))
+                    .filter(recipes::deleted_at.is_null())
+                    .select(RecipeModel::as_select())
+                    .distinct()
+                    .order_by(recipes::name.asc())
+                    .load(connection)?
+                    .into_iter()
+                    .map(Into::into)
+                    .collect())
+            })
+        })
+        .await?
+    }
+
+    async fn deleted_recipes(&self) -> Result<StoreResponse, StoreError> {
+        use schema::recipes::dsl::{deleted_at, recipes};
+        let store = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = store.read_connection()?;
+            connection.transaction(|connection| {
+                Ok(StoreResponse::Recipes(
+                    recipes
+                        .filter(deleted_at.is_not_null())
+                        .order_by(deleted_at.desc())
+                        .select(RecipeModel::as_select())
+                        .load(connection)?
+                        .into_iter()
+                        .map(Into::into)
+                        .collect(),
+                ))
+            })
+        })
+        .await?
+    }
+}
+
+/// Most single-character edits for a name to count as a near match.
+const SUGGESTION_DISTANCE: usize = 2;
+
+/// The Levenshtein distance between `a` and `b`, counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Escapes `\`, `%` and `_` so `s` matches itself literally in a `LIKE`
+/// pattern with `ESCAPE '\'`.
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Store;
+    use common::{
+        commands::{ApiCommand, Read},
+        item::Name,
+        recipes::Ingredients,
+    };
+
+    async fn inmem_sqlite_store() -> SqliteStore {
+        // Set up a connection to an in-memory SQLite database for testing
+        let store = SqliteStore::new(DbUri::inmem()).await.unwrap();
+        let migrations_store = store.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = migrations_store.connection().unwrap();
+            connection.immediate_transaction(run_migrations).unwrap();
+        })
+        .await
+        .unwrap();
+        store
+    }
+
+    #[tokio::test]
+    async fn test_add_recipe_too_many_ingredients() {
+        let store = SqliteStore::from_config(StoreConfig {
+            max_ingredients: 3,
+            ..StoreConfig::from(DbUri::inmem())
+        })
+        .await
+        .unwrap();
+
+        let recipe = Recipe::new("runaway scrape");
+        let ingredients = (0..4)
+            .map(|i| Name::from(format!("ingredient {i}").as_str()))
+            .collect::<Ingredients>();
+        assert!(matches!(
+            store.add_recipe(&recipe, &ingredients, &None).await,
+            Err(StoreError::TooManyIngredients(_, 4, 3))
+        ));
+        let StoreResponse::Recipes(recipes) = store.recipes().await.unwrap() else {
+            todo!()
+        };
+        assert!(recipes.is_empty());
+    }
+
+    #[cfg(feature = "fetch-limiter")]
+    #[tokio::test]
+    async fn test_max_fetches() {
+        let store = SqliteStore::from_config(StoreConfig {
+            max_fetches: 1,
+            ..StoreConfig::from(DbUri::inmem())
+        })
+        .await
+        .unwrap();
+        assert_eq!(store.fetch_limiter().max_in_flight(), 1);
+
+        let store = inmem_sqlite_store().await;
+        assert_eq!(
+            store.fetch_limiter().max_in_flight(),
+            common::fetcher::DEFAULT_MAX_IN_FLIGHT
+        );
+    }
+
+    #[tokio::test]
+    async fn test_seed_default_sections() {
+        let store = SqliteStore::from_config(StoreConfig {
+            seed_sections: true,
+            ..StoreConfig::from(DbUri::inmem())
+        })
+        .await
+        .unwrap();
+
+        let StoreResponse::Sections(sections) = store.sections().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(
+            sections
+                .iter()
+                .map(|section| section.as_str())
+                .collect::<Vec<_>>(),
+            vec!["fresh", "pantry", "protein", "dairy", "freezer"]
+        );
+
+        // seeding again adds nothing
+        assert_eq!(store.seed_default_sections().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_from_pool() {
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(ConnectionManager::<SqliteConnection>::new(":memory:"))
+            .unwrap();
+        let store = SqliteStore::from_pool(pool).unwrap();
+
+        let item_name = test_item_name();
+        store.add_item(&item_name, &None).await.unwrap();
+
+        let dispatch = Store::from_sqlite_store(store).init().await.unwrap();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        dispatch
+            .send((ApiCommand::Read(Read::All), tx))
+            .await
+            .unwrap();
+
+        let StoreResponse::Items(items) = rx.await.unwrap().unwrap() else {
+            todo!()
+        };
+
+        assert!(items
+            .collection_iter()
+            .any(|item| item.name() == &item_name));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_get_or_insert_item() {
+        #[derive(Debug)]
+        struct BusyTimeout;
+
+        impl r2d2::CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for BusyTimeout {
+            fn on_acquire(
+                &self,
+                connection: &mut SqliteConnection,
+            ) -> Result<(), diesel::r2d2::Error> {
+                diesel::sql_query("PRAGMA busy_timeout = 5000")
+                    .execute(connection)
+                    .map_err(diesel::r2d2::Error::QueryError)?;
+                Ok(())
+            }
+        }
+
+        // each task gets its own connection to the same file, so the
+        // inserts really race
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.path().join("groceries.db");
+        let pool = r2d2::Pool::builder()
+            .max_size(8)
+            .connection_customizer(Box::new(BusyTimeout))
+            .build(ConnectionManager::<SqliteConnection>::new(
+                path.to_str().unwrap(),
+            ))
+            .unwrap();
+        let store = SqliteStore::from_pool(pool).unwrap();
+
+        let tasks = (0..8)
+            .map(|_| {
+                let store = store.clone();
+                tokio::task::spawn_blocking(move || {
+                    let mut connection = store.connection().unwrap();
+                    SqliteStore::get_or_insert_item(&mut connection, "eggs").unwrap()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut ids = Vec::new();
+        for task in tasks {
+            ids.push(task.await.unwrap());
+        }
+        assert!(ids.iter().all(|id| *id == ids[0]));
+
+        let items = store.items().await.unwrap();
+        assert_eq!(items.collection().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_recipe_cache() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let store = inmem_sqlite_store().await;
+        let url = Url::parse("https://www.bbc.co.uk/food/recipes/pancakes").unwrap();
+        let fetches = Arc::new(AtomicUsize::new(0));
+
+        let fetch = || {
+            let fetches = fetches.clone();
+            move |_url| async move {
+                fetches.fetch_add(1, Ordering::SeqCst);
+                Ok((
+                    Recipe::new("pancakes"),
+                    Ingredients::from_iter(vec![Name::from("eggs"), Name::from("flour")]),
+                ))
+            }
+        };
+
+        let fetched = (
+            Recipe::new("pancakes"),
+            Ingredients::from_iter(vec![Name::from("eggs"), Name::from("flour")]),
+        );
+        for _ in 0..2 {
+            let StoreResponse::FetchedRecipe(cached) = store
+                .fetch_recipe_with(url.clone(), &None, FetchOptions::default(), fetch())
+                .await
+                .unwrap()
+            else {
+                todo!()
+            };
+            assert_eq!(cached, fetched);
+
+            // The cache gives what was fetched, not the recipe as since edited
+            store
+                .add_recipe(
+                    &Recipe::new("pancakes"),
+                    &Ingredients::from_iter(vec![Name::from("milk")]),
+                    &None,
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        store
+            .fetch_recipe_with(
+                url,
+                &None,
+                FetchOptions {
+                    force: true,
+                    ..Default::default()
+                },
+                fetch(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_recipe_auto_section() {
+        use common::section::Section;
+
+        let store = inmem_sqlite_store().await;
+        for item in ["eggs", "butter"] {
+            store
+                .add_item(&Name::from(item), &Some(Section::from("dairy")))
+                .await
+                .unwrap();
+        }
+        store
+            .add_recipe(
+                &Recipe::new("scrambled eggs"),
+                &Ingredients::from_iter(vec![Name::from("eggs"), Name::from("butter")]),
+                &None,
+            )
+            .await
+            .unwrap();
+
+        let url = Url::parse("https://www.bbc.co.uk/food/recipes/omelette").unwrap();
+        store
+            .fetch_recipe_with(
+                url,
+                &None,
+                FetchOptions {
+                    auto_section: true,
+                    ..Default::default()
+                },
+                |_url| async {
+                    Ok((
+                        Recipe::new("omelette"),
+                        Ingredients::from_iter(vec![
+                            Name::from("eggs"),
+                            Name::from("butter"),
+                            Name::from("chives"),
+                        ]),
+                    ))
+                },
+            )
+            .await
+            .unwrap();
+
+        assert!(store.items_with_sections().await.unwrap().contains(&(
+            common::item::Item::new("chives"),
+            Some(Section::from("dairy"))
+        )));
+        assert_eq!(
+            store.recent_fetches(10).await.unwrap(),
+            vec![Recipe::new("omelette")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recent_fetches() {
+        let store = inmem_sqlite_store().await;
+        let fetch = |url: &str, recipe: &str| {
+            let url = Url::parse(url).unwrap();
+            let recipe = Recipe::new(recipe);
+            let store = store.clone();
+            async move {
+                store
+                    .fetch_recipe_with(
+                        url,
+                        &None,
+                        FetchOptions {
+                            force: true,
+                            ..Default::default()
+                        },
+                        |_url| async {
+                            Ok((recipe, Ingredients::from_iter(vec![Name::from("eggs")])))
+                        },
+                    )
+                    .await
+                    .unwrap();
+            }
+        };
+
+        fetch("https://www.bbc.co.uk/food/recipes/pancakes", "pancakes").await;
+        fetch("https://www.bbc.co.uk/food/recipes/omelette", "omelette").await;
+        assert_eq!(
+            store.recent_fetches(10).await.unwrap(),
+            vec![Recipe::new("omelette"), Recipe::new("pancakes")]
+        );
+
+        fetch("https://www.bbc.co.uk/food/recipes/pancakes", "pancakes").await;
+        assert_eq!(
+            store.recent_fetches(1).await.unwrap(),
+            vec![Recipe::new("pancakes")]
+        );
+
+        // Fetches follow their recipe through a rename
+        store
+            .rename_recipe(&Recipe::new("pancakes"), &Recipe::new("crepes"))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.recent_fetches(10).await.unwrap(),
+            vec![Recipe::new("crepes"), Recipe::new("omelette")]
+        );
+        let Some((recipe, _)) = store
+            .cached_recipe(&Url::parse("https://www.bbc.co.uk/food/recipes/pancakes").unwrap())
+            .await
+            .unwrap()
+        else {
+            panic!("expected a cached fetch");
+        };
+        assert_eq!(recipe, Recipe::new("crepes"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_recipe_already_imported() {
+        let store = inmem_sqlite_store().await;
+        let url = Url::parse("https://www.bbc.co.uk/food/recipes/pancakes").unwrap();
+
+        store
+            .fetch_recipe_with(url.clone(), &None, FetchOptions::default(), |_url| async {
+                Ok((
+                    Recipe::new("pancakes"),
+                    Ingredients::from_iter(vec![Name::from("eggs"), Name::from("flour")]),
+                ))
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            store.recipe_by_url(&url).await.unwrap(),
+            Some(Recipe::new("pancakes"))
+        );
+
+        // Past the fetch cache, the recipe's source URL still finds it
+        diesel::delete(schema::fetched_recipes::table)
+            .execute(&mut store.connection().unwrap())
+            .unwrap();
+
+        let StoreResponse::FetchedRecipe((recipe, ingredients)) = store
+            .fetch_recipe_with(url, &None, FetchOptions::default(), |_url| async {
+                unreachable!("an imported recipe isn't scraped again")
+            })
+            .await
+            .unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(recipe, Recipe::new("pancakes"));
+        assert_eq!(ingredients.iter().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_refetch_recipe() {
+        let store = inmem_sqlite_store().await;
+        let url = Url::parse("https://www.bbc.co.uk/food/recipes/pancakes").unwrap();
+        let recipe = Recipe::new("pancakes");
+
+        store
+            .add_recipe(
+                &recipe,
+                &Ingredients::from_iter(vec![Name::from("eggs"), Name::from("flour")]),
+                &None,
+            )
+            .await
+            .unwrap();
+
+        store
+            .refetch_recipe_with(&recipe, url.clone(), |_url| async {
+                Ok((
+                    Recipe::new("pancakes"),
+                    Ingredients::from_iter(vec![
+                        Name::from("buttermilk"),
+                        Name::from("spelt flour"),
+                        Name::from("sugar"),
+                    ]),
+                ))
+            })
+            .await
+            .unwrap();
+
+        let StoreResponse::RecipeIngredients(Some(ingredients)) =
+            store.recipe_ingredients(&recipe).await.unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(
+            ingredients.iter().map(Name::as_str).collect::<Vec<_>>(),
+            ["buttermilk", "spelt flour", "sugar"]
+        );
+        assert_eq!(store.recipe_by_url(&url).await.unwrap(), Some(recipe));
+
+        // a URL belongs to one recipe, the one fetched from it last
+        let waffles = Recipe::new("waffles");
+        store
+            .add_recipe(
+                &waffles,
+                &Ingredients::from_iter(vec![Name::from("eggs")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store
+            .refetch_recipe_with(&waffles, url.clone(), |_url| async {
+                Ok((
+                    Recipe::new("waffles"),
+                    Ingredients::from_iter(vec![Name::from("flour")]),
+                ))
+            })
+            .await
+            .unwrap();
+        assert_eq!(store.recipe_by_url(&url).await.unwrap(), Some(waffles));
+    }
+
+    #[tokio::test]
+    async fn test_frequently_listed() {
+        let store = inmem_sqlite_store().await;
+
+        store.add_list_item(&Name::from("milk")).await.unwrap();
+        store.add_list_item(&Name::from("bread")).await.unwrap();
+        // Adding an item already on the list doesn't count again
+        store.add_list_item(&Name::from("milk")).await.unwrap();
+        store.refresh_list(false).await.unwrap();
+        store.add_list_item(&Name::from("milk")).await.unwrap();
+
+        assert_eq!(
+            store.frequently_listed(10).await.unwrap(),
+            [(Name::from("milk"), 2), (Name::from("bread"), 1)]
+        );
+        assert_eq!(
+            store.frequently_listed(1).await.unwrap(),
+            [(Name::from("milk"), 2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_frequently_listed_counts_toggled_items() {
+        let store = inmem_sqlite_store().await;
+        let milk = Name::from("milk");
+
+        store.toggle_list_item(&milk).await.unwrap();
+        store.toggle_list_item(&milk).await.unwrap();
+        store.toggle_list_item(&milk).await.unwrap();
+        store.set_list(&[Name::from("bread")]).await.unwrap();
+
+        assert_eq!(
+            store.frequently_listed(10).await.unwrap(),
+            [(milk, 2), (Name::from("bread"), 1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dump_and_restore() {
+        use common::section::Section;
+
+        let store = inmem_sqlite_store().await;
+        store
+            .add_item(&Name::from("milk"), &Some(Section::from("dairy")))
+            .await
+            .unwrap();
+        store
+            .add_recipe(
+                &Recipe::new("pancakes"),
+                &Ingredients::from_iter(vec![Name::from("eggs"), Name::from("milk")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store
+            .add_recipe(
+                &Recipe::new("toast"),
+                &Ingredients::from_iter(vec![Name::from("bread")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store.delete_recipe(&Recipe::new("toast")).await.unwrap();
+        store
+            .add_list_recipe(&Recipe::new("pancakes"), false)
+            .await
+            .unwrap();
+        store.add_list_item(&Name::from("bread")).await.unwrap();
+        store.add_checklist_item(&Name::from("salt")).await.unwrap();
+        store.subscribe_item(&Name::from("milk")).await.unwrap();
+
+        let dump = store.dump().await.unwrap();
+        let restored = inmem_sqlite_store().await;
+        restored
+            .add_item(&Name::from("stale"), &None)
+            .await
+            .unwrap();
+        restored.restore(&dump).await.unwrap();
+
+        assert_eq!(restored.dump().await.unwrap(), dump);
+        let reads = || {
+            vec![
+                Read::All,
+                Read::Checklist,
+                Read::DeletedRecipes,
+                Read::List,
+                Read::Recipes,
+                Read::Sections,
+                Read::Subscriptions,
+                Read::Recipe(Recipe::new("pancakes")),
+            ]
+        };
+        for (original, copy) in reads().into_iter().zip(reads()) {
+            assert_eq!(
+                format!("{:?}", store.read(original).await.unwrap()),
+                format!("{:?}", restored.read(copy).await.unwrap())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_item_groups() {
+        let store = inmem_sqlite_store().await;
+        {
+            let mut connection = store.connection().unwrap();
+            for name in ["Milk", "milk ", "eggs", "bread", " Bread"] {
+                diesel::insert_into(schema::items::table)
+                    .values(NewItem { name })
+                    .execute(&mut connection)
+                    .unwrap();
+            }
+        }
+
+        assert_eq!(
+            store.duplicate_item_groups().await.unwrap(),
+            [
+                vec![Name::new_unchecked(" Bread"), Name::new_unchecked("bread")],
+                vec![Name::new_unchecked("Milk"), Name::new_unchecked("milk ")],
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recipe_previews_paged_sorted() {
+        let store = inmem_sqlite_store().await;
+        for (recipe, ingredients) in [
+            (
+                "apple pie",
+                vec!["apples", "butter", "flour", "sugar", "cinnamon"],
+            ),
+            ("bread", vec!["flour", "water"]),
+            ("crumble", vec!["apples", "oats"]),
+        ] {
+            store
+                .add_recipe(
+                    &Recipe::new(recipe),
+                    &Ingredients::from_iter(ingredients.into_iter().map(Name::from)),
+                    &None,
+                )
+                .await
+                .unwrap();
+        }
+
+        let page = store
+            .recipe_previews_paged_sorted(0, 2, RecipeSort::NameAsc)
+            .await
+            .unwrap();
+        assert_eq!(
+            page,
+            [
+                (
+                    Recipe::new("apple pie"),
+                    vec![
+                        Name::from("apples"),
+                        Name::from("butter"),
+                        Name::from("cinnamon")
+                    ]
+                ),
+                (
+                    Recipe::new("bread"),
+                    vec![Name::from("flour"), Name::from("water")]
+                ),
+            ]
+        );
+
+        let page = store
+            .recipe_previews_paged_sorted(2, 2, RecipeSort::NameAsc)
+            .await
+            .unwrap();
+        assert_eq!(
+            page,
+            [(
+                Recipe::new("crumble"),
+                vec![Name::from("apples"), Name::from("oats")]
+            )]
+        );
+
+        let page = store
+            .recipe_previews_paged_sorted(0, 1, RecipeSort::NameDesc)
+            .await
+            .unwrap();
+        assert_eq!(
+            page,
+            [(
+                Recipe::new("crumble"),
+                vec![Name::from("apples"), Name::from("oats")]
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remove_list_recipe() {
+        let store = inmem_sqlite_store().await;
+        let pancakes = Recipe::new("pancakes");
+        store
+            .add_recipe(
+                &pancakes,
+                &Ingredients::from_iter(vec![Name::from("eggs"), Name::from("flour")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store.add_list_recipe(&pancakes, false).await.unwrap();
+        store.add_list_item(&Name::from("eggs")).await.unwrap();
+        store.add_list_item(&Name::from("coffee")).await.unwrap();
+
+        store.remove_list_recipe(&pancakes, false).await.unwrap();
+        assert!(store.get_list_recipes().await.unwrap().is_empty());
+        assert_eq!(store.get_list_items().await.unwrap().len(), 3);
+
+        store.add_list_recipe(&pancakes, false).await.unwrap();
+        store.remove_list_recipe(&pancakes, true).await.unwrap();
+        assert!(store.get_list_recipes().await.unwrap().is_empty());
+        let items = store.get_list_items().await.unwrap();
+        let mut names: Vec<&str> = items.iter().map(|item| item.name().as_str()).collect();
+        names.sort();
+        assert_eq!(names, ["coffee", "eggs"]);
+    }
+
+    #[tokio::test]
+    async fn test_import_share_code_lists_ingredients_through_recipe() {
+        let store = inmem_sqlite_store().await;
+        let pancakes = Recipe::new("pancakes");
+        store
+            .add_recipe(
+                &pancakes,
+                &Ingredients::from_iter(vec![Name::from("eggs"), Name::from("flour")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        let code = List::new()
+            .with_items(["eggs", "flour", "bread"].map(common::item::Item::new))
+            .with_recipes([pancakes.clone(), Recipe::new("waffles")])
+            .to_share_code();
+
+        store.import_share_code(&code).await.unwrap();
+        assert_eq!(
+            store.get_list_recipes().await.unwrap(),
+            std::slice::from_ref(&pancakes)
+        );
+
+        store.remove_list_recipe(&pancakes, true).await.unwrap();
+        let items = store.get_list_items().await.unwrap();
+        let names: Vec<&str> = items.iter().map(|item| item.name().as_str()).collect();
+        assert_eq!(names, ["bread"]);
+    }
+
+    #[tokio::test]
+    async fn test_recipe_ingredients_on_list() {
+        let store = inmem_sqlite_store().await;
+        let recipe = Recipe::new("pancakes");
+        store
+            .add_recipe(
+                &recipe,
+                &Ingredients::from_iter(vec![Name::from("eggs"), Name::from("flour")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store.add_list_item(&Name::from("eggs")).await.unwrap();
+        store.add_list_item(&Name::from("coffee")).await.unwrap();
+
+        assert_eq!(
+            store.recipe_ingredients_on_list(&recipe).await.unwrap(),
+            [Name::from("eggs")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_suggest_section() {
+        let store = inmem_sqlite_store().await;
+        let dairy = Some(common::section::Section::from("dairy"));
+        let produce = Some(common::section::Section::from("fresh"));
+        for (item, section) in [
+            ("milk", &dairy),
+            ("butter", &dairy),
+            ("cream", &dairy),
+            ("apples", &produce),
+        ] {
+            store.add_item(&Name::from(item), section).await.unwrap();
+        }
+        for (recipe, ingredients) in [
+            ("custard", vec!["milk", "cream", "vanilla"]),
+            ("apple pie", vec!["apples", "butter", "vanilla"]),
+        ] {
+            store
+                .add_recipe(
+                    &Recipe::new(recipe),
+                    &Ingredients::from_iter(ingredients.into_iter().map(Name::from)),
+                    &None,
+                )
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            store.suggest_section(&Name::from("vanilla")).await.unwrap(),
+            dairy
+        );
+        assert!(matches!(
+            store.suggest_section(&Name::from("coffee")).await,
+            Err(StoreError::ItemNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_checklist_json_round_trip() {
+        let store = inmem_sqlite_store().await;
+        for name in ["coffee", "milk", "toilet paper"] {
+            store.add_checklist_item(&Name::from(name)).await.unwrap();
+        }
+        let json = store.export_checklist_json().await.unwrap();
+        insta::assert_display_snapshot!(json, @r###"["coffee","milk","toilet paper"]"###);
+
+        let restored = inmem_sqlite_store().await;
+        assert_eq!(
+            restored.import_checklist_json(&json).await.unwrap(),
+            [
+                Name::from("coffee"),
+                Name::from("milk"),
+                Name::from("toilet paper")
+            ]
+        );
+        assert_eq!(restored.export_checklist_json().await.unwrap(), json);
+    }
+
+    #[tokio::test]
+    async fn test_item_detail() {
+        let store = inmem_sqlite_store().await;
+
+        let eggs = Name::from("eggs");
+        let recipe = Recipe::from("omelette");
+        store
+            .add_item(&eggs, &Some(common::section::Section::from("dairy")))
+            .await
+            .unwrap();
+        store
+            .add_recipe(&recipe, &Ingredients::from_iter(vec![eggs.clone()]), &None)
+            .await
+            .unwrap();
+        store.add_list_item(&eggs).await.unwrap();
+        store.add_checklist_item(&eggs).await.unwrap();
+        store.add_item(&Name::from("salt"), &None).await.unwrap();
+
+        let detail = store.item_detail(&eggs).await.unwrap();
+        assert_eq!(detail.item().name(), &eggs);
+        assert_eq!(
+            detail.item().section(),
+            Some(&common::section::Section::from("dairy"))
+        );
+        assert_eq!(detail.item().recipes(), Some(&vec![recipe]));
+        assert!(detail.on_list());
+        assert!(detail.on_checklist());
+
+        let detail = store.item_detail(&Name::from("salt")).await.unwrap();
+        assert_eq!(detail.item().section(), None);
+        assert!(!detail.on_list());
+        assert!(!detail.on_checklist());
+
+        assert!(matches!(
+            store.item_detail(&Name::from("saffron")).await,
+            Err(StoreError::ItemNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_item_or_suggestions() {
+        let store = inmem_sqlite_store().await;
+        for name in ["milk", "oat milk", "mint", "eggs"] {
+            store.add_item(&Name::from(name), &None).await.unwrap();
+        }
+
+        let common::item::ItemLookup::Found(item) = store
+            .item_or_suggestions(&Name::from("eggs"))
+            .await
+            .unwrap()
+        else {
+            panic!("expected eggs to be found");
+        };
+        assert_eq!(item.name(), &Name::from("eggs"));
+
+        assert_eq!(
+            store
+                .item_or_suggestions(&Name::from("mlik"))
+                .await
+                .unwrap(),
+            common::item::ItemLookup::NotFound {
+                query: Name::from("mlik"),
+                suggestions: vec![Name::from("milk")],
+            }
+        );
+        assert_eq!(
+            store.item_or_suggestions(&Name::from("mil")).await.unwrap(),
+            common::item::ItemLookup::NotFound {
+                query: Name::from("mil"),
+                suggestions: vec![
+                    Name::from("milk"),
+                    Name::from("mint"),
+                    Name::from("oat milk")
+                ],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_recipes() {
+        let store = inmem_sqlite_store().await;
+        let keep = Recipe::new("pancakes");
+        let remove = Recipe::new("pancakes (old)");
+        store
+            .add_recipe(
+                &keep,
+                &Ingredients::from_iter(vec![Name::from("eggs"), Name::from("flour")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store
+            .add_recipe(
+                &remove,
+                &Ingredients::from_iter(vec![Name::from("flour"), Name::from("milk")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store.add_list_recipe(&remove, false).await.unwrap();
+
+        store.merge_recipes(&keep, &remove).await.unwrap();
+
+        let StoreResponse::RecipeIngredients(Some(ingredients)) =
+            store.recipe_ingredients(&keep).await.unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(
+            ingredients,
+            Ingredients::from_iter(vec![
+                Name::from("eggs"),
+                Name::from("flour"),
+                Name::from("milk")
+            ])
+        );
+        let StoreResponse::Recipes(recipes) = store.recipes().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(recipes, vec![keep.clone()]);
+        assert_eq!(store.get_list_recipes().await.unwrap(), vec![keep]);
+    }
+
+    #[tokio::test]
+    async fn test_read_pool() {
+        fn pool() -> ConnectionPool {
+            r2d2::Pool::builder()
+                .max_size(1)
+                .build(ConnectionManager::<SqliteConnection>::new(":memory:"))
+                .unwrap()
+        }
+
+        // Stands in for a replica that already has the data
+        let replica = SqliteStore::from_pool(pool()).unwrap();
+        let replicated = Name::from("replicated item");
+        replica.add_item(&replicated, &None).await.unwrap();
+
+        let store = SqliteStore::from_pool(pool())
+            .unwrap()
+            .with_read_pool(replica.read_pool.clone());
+        let written = Name::from("written item");
+        store.add_item(&written, &None).await.unwrap();
+
+        let items = store.items().await.unwrap();
+        assert!(items
+            .collection_iter()
+            .any(|item| item.name() == &replicated));
+        assert!(items.collection_iter().all(|item| item.name() != &written));
+
+        // Writes went to the primary
+        let mut connection = store.connection().unwrap();
+        assert_eq!(
+            schema::items::table
+                .select(schema::items::dsl::name)
+                .load::<String>(&mut connection)
+                .unwrap(),
+            vec![written.to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migration_status() {
+        let store = inmem_sqlite_store().await;
+
+        insta::assert_debug_snapshot!(store.applied_migrations().await.unwrap(), @r###"
+        [
+            "20230914155309",
+            "20261016151537",
+            "20261016154906",
+            "20261016155129",
+            "20261016155716",
+            "20261016161900",
+            "20261016163249",
+            "20261016170432",
+            "20261016174627",
+            "20261016181311",
+            "20261016185741",
+            "20261016192214",
+            "20261016194530",
+        ]
+        "###);
+        assert!(store.pending_migrations().await.unwrap().is_empty());
+    }
+
+    fn test_item_name() -> Name {
+        Name::from("test item")
+    }
+
+    #[tokio::test]
+    async fn test_add_checklist_item() {
+        let store = inmem_sqlite_store().await;
+
+        let item_name = test_item_name();
+        store.add_checklist_item(&item_name).await.unwrap();
+
+        let StoreResponse::Checklist(list) = store.checklist().await.unwrap() else {
+            todo!()
+        };
+
+        assert!(list.iter().any(|item| item.name() == &item_name));
+    }
+
+    #[tokio::test]
+    async fn test_add_item() {
+        let store = inmem_sqlite_store().await;
+
+        let item_name = test_item_name();
+        store.add_item(&item_name, &None).await.unwrap();
+
+        let items = store.items().await.unwrap();
+
+        assert!(items
+            .collection_iter()
+            .any(|item| item.name() == &item_name));
+    }
+
+    #[tokio::test]
+    async fn test_add_item_created() {
+        let store = inmem_sqlite_store().await;
+
+        let item_name = test_item_name();
+        assert!(store.add_item(&item_name, &None).await.unwrap());
+        assert!(!store.add_item(&item_name, &None).await.unwrap());
+
+        let items = store.items().await.unwrap();
+        assert_eq!(items.collection().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_add_item_error_propagates() {
+        let store = inmem_sqlite_store().await;
+
+        {
+            let mut connection = store.connection().unwrap();
+            diesel::sql_query("DROP TABLE items")
+                .execute(&mut connection)
+                .unwrap();
+        }
+
+        assert!(matches!(
+            store.add_item(&test_item_name(), &None).await,
+            Err(StoreError::DBQuery(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_add_item_section_error_rolls_back() {
+        let store = inmem_sqlite_store().await;
+
+        {
+            let mut connection = store.connection().unwrap();
+            diesel::sql_query("DROP TABLE items_sections")
+                .execute(&mut connection)
+                .unwrap();
+        }
+
+        let section = common::section::Section::from("fresh");
+        assert!(matches!(
+            store.add_item(&test_item_name(), &Some(section)).await,
+            Err(StoreError::DBQuery(_))
+        ));
+
+        // The failed insert must not leave a half-added item behind
+        let mut connection = store.connection().unwrap();
+        assert_eq!(
+            schema::items::table
+                .count()
+                .get_result::<i64>(&mut connection)
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_checklist_existing_item() {
+        let store = inmem_sqlite_store().await;
+
+        let unknown = Name::from("unknown item");
+        assert!(matches!(
+            store.checklist_existing_item(&unknown).await,
+            Err(StoreError::ItemNotFound(_))
+        ));
+        assert!(store.items().await.unwrap().collection().is_empty());
+
+        let item_name = test_item_name();
+        store.add_item(&item_name, &None).await.unwrap();
+        store.checklist_existing_item(&item_name).await.unwrap();
+
+        let StoreResponse::Checklist(checklist) = store.checklist().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(checklist.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_add_list_item() {
+        let store = inmem_sqlite_store().await;
+
+        let item_name = test_item_name();
+        store.add_list_item(&item_name).await.unwrap();
+
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+
+        let item_in_list = list.items().iter().any(|item| item.name() == &item_name);
+
+        assert!(item_in_list);
+    }
+
+    #[tokio::test]
+    async fn test_combined_ingredients() {
+        let store = inmem_sqlite_store().await;
+
+        let pancakes = Recipe::new("pancakes");
+        let omelette = Recipe::new("omelette");
+        store
+            .add_recipe(
+                &pancakes,
+                &Ingredients::from_input_string("flour, eggs, milk"),
+                &None,
+            )
+            .await
+            .unwrap();
+        store
+            .add_recipe(
+                &omelette,
+                &Ingredients::from_input_string("eggs, cheese"),
+                &None,
+            )
+            .await
+            .unwrap();
+
+        let ingredients = store
+            .combined_ingredients(&[pancakes, omelette])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            ingredients,
+            Ingredients::from_input_string("cheese, eggs, flour, milk")
+        );
+        assert!(matches!(
+            store
+                .combined_ingredients(&[Recipe::new("missing recipe")])
+                .await,
+            Err(StoreError::RecipeNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_empty_recipes() {
+        let store = inmem_sqlite_store().await;
+
+        let empty = Recipe::new("empty recipe");
+        store
+            .add_recipe(&empty, &Ingredients::default(), &None)
+            .await
+            .unwrap();
+        store
+            .add_recipe(
+                &Recipe::new("pancakes"),
+                &Ingredients::from_input_string("eggs, flour"),
+                &None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(store.empty_recipes().await.unwrap(), vec![empty]);
+    }
+
+    #[tokio::test]
+    async fn test_recipe_ingredient_count() {
+        let store = inmem_sqlite_store().await;
+
+        let recipe = Recipe::new("test recipe");
+        let ingredients =
+            Ingredients::from_iter(vec![Name::from("ingredient 1"), Name::from("ingredient 2")]);
+        store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap();
+
+        assert_eq!(store.recipe_ingredient_count(&recipe).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_recipe_ingredient_count_empty_recipe() {
+        let store = inmem_sqlite_store().await;
+
+        let recipe = Recipe::new("test recipe");
+        store
+            .add_recipe(&recipe, &Ingredients::default(), &None)
+            .await
+            .unwrap();
+
+        assert_eq!(store.recipe_ingredient_count(&recipe).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_recipe_ingredient_count_missing_recipe() {
+        let store = inmem_sqlite_store().await;
+
+        assert!(matches!(
+            store
+                .recipe_ingredient_count(&Recipe::new("missing recipe"))
+                .await,
+            Err(StoreError::RecipeNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_add_list_recipe() {
+        let store = inmem_sqlite_store().await;
+
+        let ingredients =
+            Ingredients::from_iter(vec![Name::from("ingredient 1"), Name::from("ingredient 2")]);
+
+        let recipe = Recipe::new("test recipe");
+        store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap();
+
+        store.add_list_recipe(&recipe, false).await.unwrap();
+
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        insta::assert_debug_snapshot!(list, @r###"
+        List {
+            checklist: [],
+            recipes: [
+                Recipe(
+                    "test recipe",
+                ),
+            ],
+            items: [
+                Item {
+                    name: Name(
+                        "ingredient 1",
+                    ),
+                    section: None,
+                    recipes: Some(
+                        [
+                            Recipe(
+                                "test recipe",
+                            ),
+                        ],
+                    ),
+                    quantity: Some(
+                        1,
+                    ),
+                },
+                Item {
+                    name: Name(
+                        "ingredient 2",
+                    ),
+                    section: None,
+                    recipes: Some(
+                        [
+                            Recipe(
+                                "test recipe",
+                            ),
+                        ],
+                    ),
+                    quantity: Some(
+                        1,
+                    ),
+                },
+            ],
+        }
+        "###);
+    }
+
+    #[tokio::test]
+    async fn test_list_item_shared_by_recipes() {
+        let store = inmem_sqlite_store().await;
+
+        let pancakes = Recipe::new("pancakes");
+        let omelette = Recipe::new("omelette");
+        store
+            .add_recipe(
+                &pancakes,
+                &Ingredients::from_iter(vec![Name::from("eggs"), Name::from("flour")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store
+            .add_recipe(
+                &omelette,
+                &Ingredients::from_iter(vec![Name::from("eggs"), Name::from("chives")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store.add_list_recipe(&pancakes, false).await.unwrap();
+        store.add_list_recipe(&omelette, false).await.unwrap();
+
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        let eggs = list
+            .items()
+            .iter()
+            .filter(|item| item.name() == &Name::from("eggs"))
+            .collect::<Vec<_>>();
+        assert_eq!(eggs.len(), 1);
+        assert_eq!(eggs[0].recipes(), Some(&vec![omelette, pancakes.clone()]));
+
+        let flour = list
+            .items()
+            .iter()
+            .find(|item| item.name() == &Name::from("flour"))
+            .unwrap();
+        assert_eq!(flour.recipes(), Some(&vec![pancakes]));
+    }
+
+    #[tokio::test]
+    async fn test_add_list_recipe_refresh() {
+        let store = inmem_sqlite_store().await;
+
+        let recipe = Recipe::new("test recipe");
+        let ingredients =
+            Ingredients::from_iter(vec![Name::from("ingredient 1"), Name::from("ingredient 2")]);
+        store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap();
+        store.add_list_recipe(&recipe, false).await.unwrap();
+
+        // Editing the recipe adds to its ingredients
+        store
+            .add_recipe(
+                &recipe,
+                &Ingredients::from_iter(vec![Name::from("ingredient 3")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store.add_list_recipe(&recipe, true).await.unwrap();
+
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        let mut names = list
+            .items()
+            .iter()
+            .map(|item| item.name().as_str())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["ingredient 1", "ingredient 2", "ingredient 3"]);
+        assert_eq!(list.recipes(), &vec![recipe]);
+    }
+
+    #[tokio::test]
+    async fn test_add_list_recipe_refresh_drops_removed_ingredients() {
+        let store = inmem_sqlite_store().await;
+
+        let soup = Recipe::new("soup");
+        let stew = Recipe::new("stew");
+        store
+            .add_recipe(
+                &soup,
+                &Ingredients::from_iter(vec![
+                    Name::from("leeks"),
+                    Name::from("potatoes"),
+                    Name::from("stock"),
+                ]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store
+            .add_recipe(
+                &stew,
+                &Ingredients::from_iter(vec![Name::from("stock")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store.add_list_recipe(&soup, false).await.unwrap();
+        store.add_list_recipe(&stew, false).await.unwrap();
+        store.add_list_item(&Name::from("salt")).await.unwrap();
+
+        // The soup no longer calls for potatoes or stock
+        store
+            .replace_recipe_ingredients(
+                &soup,
+                &Ingredients::from_iter(vec![Name::from("leeks"), Name::from("cream")]),
+                &Url::parse("https://example.com/soup").unwrap(),
+            )
+            .await
+            .unwrap();
+        store.add_list_recipe(&soup, true).await.unwrap();
+
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        let mut names = list
+            .items()
+            .iter()
+            .map(|item| item.name().as_str())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["cream", "leeks", "salt", "stock"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_store_walk_order() {
+        use common::section::Section;
+
+        let store = inmem_sqlite_store().await;
+        {
+            let mut connection = store.connection().unwrap();
+            import_sections(&mut connection).unwrap();
+        }
+
+        for (item, section) in [
+            ("yogurt", Some("dairy")),
+            ("bananas", None),
+            ("apples", Some("fresh")),
+            ("rice", Some("pantry")),
+            ("butter", Some("dairy")),
+            ("candles", Some("household")),
+            ("anchovies", None),
+        ] {
+            let name = Name::from(item);
+            store
+                .add_item(&name, &section.map(Section::from))
+                .await
+                .unwrap();
+            store.add_list_item(&name).await.unwrap();
+        }
+
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        let lines = list
+            .items()
+            .iter()
+            .map(|item| match item.section() {
+                Some(section) => format!("{section}: {item}"),
+                None => item.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        insta::assert_snapshot!(lines, @r###"
+        fresh: apples
+        pantry: rice
+        dairy: butter
+        dairy: yogurt
+        household: candles
+        anchovies
+        bananas
+        "###);
+
+        let StoreResponse::List(items_only) = store.list_items().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(
+            items_only
+                .items()
+                .iter()
+                .map(|item| item.name())
+                .collect::<Vec<_>>(),
+            list.items()
+                .iter()
+                .map(|item| item.name())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_items() {
+        let store = inmem_sqlite_store().await;
+
+        let recipe = Recipe::new("test recipe");
+        let ingredients = Ingredients::from_iter(vec![Name::from("ingredient 1")]);
+        store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap();
+        store.add_list_recipe(&recipe, false).await.unwrap();
+        store
+            .add_checklist_item(&Name::from("checklist item"))
+            .await
+            .unwrap();
+
+        // Without the checklist, only a read that skips it can succeed
+        diesel::sql_query("DROP TABLE checklist")
+            .execute(&mut store.connection().unwrap())
+            .unwrap();
+        assert!(store.list().await.is_err());
+
+        let StoreResponse::List(list) = store.list_items().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(list.items().len(), 1);
+        assert!(list.recipes().is_empty());
+        assert!(list.checklist().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_recipe_status() {
+        let store = inmem_sqlite_store().await;
+
+        let ingredients = Ingredients::from_iter(vec![
+            Name::from("ingredient 1"),
+            Name::from("ingredient 2"),
+            Name::from("ingredient 3"),
+            Name::from("ingredient 4"),
+        ]);
+
+        let recipe = Recipe::new("test recipe");
+        store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap();
+
+        // Mark the recipe as on the list with only half its ingredients stocked
+        {
+            let mut connection = store.connection().unwrap();
+            let id = SqliteStore::get_recipe_id(&mut connection, recipe.as_str())
+                .unwrap()
+                .unwrap();
+            diesel::insert_into(schema::list_recipes::table)
+                .values(NewListRecipe { id })
+                .execute(&mut connection)
+                .unwrap();
+        }
+        store
+            .add_list_item(&Name::from("ingredient 1"))
+            .await
+            .unwrap();
+        store
+            .add_list_item(&Name::from("ingredient 3"))
+            .await
+            .unwrap();
+
+        let StoreResponse::ListRecipeStatus(statuses) = store.list_recipe_status().await.unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(statuses, vec![RecipeStatus::new(recipe, 2, 2)]);
+    }
+
+    #[tokio::test]
+    async fn test_add_recipe_reports_new_items() {
+        let store = inmem_sqlite_store().await;
+        store.add_item(&Name::from("eggs"), &None).await.unwrap();
+
+        let report = store
+            .add_recipe(
+                &Recipe::new("omelette"),
+                &Ingredients::from_iter(vec![Name::from("eggs"), Name::from("chives")]),
+                &None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(report.new_items(), [Name::from("chives")]);
+        assert_eq!(report.existing_items(), [Name::from("eggs")]);
+    }
+
+    #[tokio::test]
+    async fn test_add_recipe() {
+        let store = inmem_sqlite_store().await;
+
+        let ingredients =
+            Ingredients::from_iter(vec![Name::from("ingredient 1"), Name::from("ingredient 2")]);
+
+        let recipe = Recipe::new("test recipe");
+        let recipe_id = store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap()
+            .recipe_id();
+
+        let StoreResponse::Recipes(recipes) = store.recipes().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(recipes.len(), 1);
+
+        let added_recipe = &recipes[0];
+        assert_eq!(added_recipe.as_str(), "test recipe");
+
+        {
+            let mut connection = store.connection().unwrap();
+            assert_eq!(
+                SqliteStore::get_recipe_id(&mut connection, recipe.as_str()).unwrap(),
+                Some(recipe_id)
+            );
+        }
+
+        let StoreResponse::RecipeIngredients(Some(recipe_ingredients)) =
+            store.recipe_ingredients(&recipe).await.unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(recipe_ingredients, ingredients);
+
+        // Adding the same recipe again refers to the same row
+        assert_eq!(
+            store
+                .add_recipe(&recipe, &ingredients, &None)
+                .await
+                .unwrap()
+                .recipe_id(),
+            recipe_id
+        );
+    }
+
+    #[tokio::test]
+    async fn test_items_by_prefix() {
+        let store = inmem_sqlite_store().await;
+        for name in ["bananas", "Avocado", "apples", "a_b tea", "axb tea"] {
+            store.add_item(&Name::from(name), &None).await.unwrap();
+        }
+
+        let names = |items: Items| -> Vec<String> {
+            items
+                .collection_iter()
+                .map(|item| item.name().to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(store.items_by_prefix("A").await.unwrap()),
+            vec!["a_b tea", "apples", "avocado", "axb tea"]
+        );
+        assert_eq!(
+            names(store.items_by_prefix("b").await.unwrap()),
+            vec!["bananas"]
+        );
+        // `_` is matched literally rather than as a wildcard
+        assert_eq!(
+            names(store.items_by_prefix("a_").await.unwrap()),
+            vec!["a_b tea"]
+        );
+        assert!(names(store.items_by_prefix("z").await.unwrap()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ingredient_count_histogram() {
+        let store = inmem_sqlite_store().await;
+        for (recipe, ingredients) in [
+            ("toast", vec!["bread"]),
+            ("tea", vec!["tea bags", "milk"]),
+            ("omelette", vec!["eggs", "butter"]),
+        ] {
+            store
+                .add_recipe(
+                    &Recipe::new(recipe),
+                    &Ingredients::from_iter(ingredients.into_iter().map(Name::from)),
+                    &None,
+                )
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            store.ingredient_count_histogram().await.unwrap(),
+            BTreeMap::from([(1, 1), (2, 2)])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_common_across_list_recipes() {
+        let store = inmem_sqlite_store().await;
+        assert!(store.common_across_list_recipes().await.unwrap().is_empty());
+
+        for (recipe, ingredients) in [
+            ("tacos", vec!["tortillas", "onion", "cilantro"]),
+            ("soup", vec!["onion", "carrots"]),
+            ("salad", vec!["onion", "tortillas"]),
+        ] {
+            store
+                .add_recipe(
+                    &Recipe::new(recipe),
+                    &Ingredients::from_iter(ingredients.into_iter().map(Name::from)),
+                    &None,
+                )
+                .await
+                .unwrap();
+        }
+        store
+            .add_list_recipe(&Recipe::new("tacos"), false)
+            .await
+            .unwrap();
+        store
+            .add_list_recipe(&Recipe::new("soup"), false)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.common_across_list_recipes().await.unwrap(),
+            vec![Name::from("onion")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_complete_trip() {
+        let store = inmem_sqlite_store().await;
+        store
+            .set_list(&[Name::from("milk"), Name::from("bread")])
+            .await
+            .unwrap();
+
+        let id = store.complete_trip().await.unwrap();
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        assert!(list.items().is_empty());
+
+        let trip = store.trip(id).await.unwrap();
+        assert_eq!(trip.id(), id);
+        assert_eq!(trip.items(), [Name::from("bread"), Name::from("milk")]);
+        assert!(!trip.completed_at().is_empty());
+
+        store.set_list(&[Name::from("eggs")]).await.unwrap();
+        let next = store.complete_trip().await.unwrap();
+        let trips = store.trips().await.unwrap();
+        assert_eq!(
+            trips.iter().map(Trip::id).collect::<Vec<_>>(),
+            vec![next, id]
+        );
+        assert_eq!(trips[0].items(), [Name::from("eggs")]);
+
+        assert!(matches!(
+            store.trip(next + 1).await,
+            Err(StoreError::TripNotFound(missing)) if missing == next + 1
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_restore_trip() {
+        let store = inmem_sqlite_store().await;
+        store
+            .set_list(&[Name::from("milk"), Name::from("bread")])
+            .await
+            .unwrap();
+        let id = store.complete_trip().await.unwrap();
+        store.set_list(&[Name::from("eggs")]).await.unwrap();
+        store.clear_list().await.unwrap();
+
+        store.restore_trip(id).await.unwrap();
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        let mut names: Vec<&str> = list
+            .items()
+            .iter()
+            .map(|item| item.name().as_str())
+            .collect();
+        names.sort();
+        let trip = store.trip(id).await.unwrap();
+        let archived: Vec<&str> = trip.items().iter().map(Name::as_str).collect();
+        assert_eq!(names, archived);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_list_keeping() {
+        let store = inmem_sqlite_store().await;
+        store
+            .set_list(&[Name::from("milk"), Name::from("bread"), Name::from("eggs")])
+            .await
+            .unwrap();
+
+        store
+            .refresh_list_keeping(&[Name::from("bread")])
+            .await
+            .unwrap();
+
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(
+            list.items()
+                .iter()
+                .map(|item| item.name().as_str())
+                .collect::<Vec<_>>(),
+            vec!["bread"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recipes_by_list_coverage() {
+        let store = inmem_sqlite_store().await;
+        for (recipe, ingredients) in [
+            ("pancakes", vec!["flour", "eggs", "milk", "butter"]),
+            ("toast", vec!["bread", "butter"]),
+        ] {
+            store
+                .add_recipe(
+                    &Recipe::new(recipe),
+                    &Ingredients::from_iter(ingredients.into_iter().map(Name::from)),
+                    &None,
+                )
+                .await
+                .unwrap();
+        }
+        store
+            .set_list(&[
+                Name::from("bread"),
+                Name::from("butter"),
+                Name::from("eggs"),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.recipes_by_list_coverage().await.unwrap(),
+            vec![
+                (Recipe::new("toast"), 2, 2),
+                (Recipe::new("pancakes"), 2, 4),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_transaction_waits_out_a_locked_database() {
+        use common::commands::Add;
+
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.path().join("groceries.db");
+        let uri = path.to_str().unwrap();
+        let holder = SqliteStore::new(DbUri::from(uri)).await.unwrap();
+        let store = SqliteStore::new(DbUri::from(uri)).await.unwrap();
+
+        // another writer takes the lock and keeps it for `held`
+        let hold_lock = |held: std::time::Duration| {
+            let holder = holder.clone();
+            let (locked, is_locked) = std::sync::mpsc::channel();
+            let writer = tokio::task::spawn_blocking(move || {
+                let mut connection = holder.connection().unwrap();
+                connection
+                    .immediate_transaction(|connection| {
+                        locked.send(()).unwrap();
+                        std::thread::sleep(held);
+                        diesel::insert_or_ignore_into(schema::items::table)
+                            .values(NewItem { name: "bread" })
+                            .execute(connection)
+                    })
+                    .unwrap();
+            });
+            is_locked.recv().unwrap();
+            writer
+        };
+
+        let writer = hold_lock(std::time::Duration::from_millis(100));
+        assert!(matches!(
+            store
+                .execute_transaction(ApiCommand::Add(Add::ChecklistItem(Name::from("milk"))))
+                .await
+                .unwrap(),
+            StoreResponse::AddedChecklistItem(_)
+        ));
+        writer.await.unwrap();
+
+        // Held past every retry, the write gives up
+        let writer = hold_lock(std::time::Duration::from_secs(1));
+        assert!(matches!(
+            store.add_checklist_item(&Name::from("eggs")).await,
+            Err(StoreError::Busy)
+        ));
+        writer.await.unwrap();
+
+        // A reader only stops the write at `COMMIT`, which is retried the same
+        let (reading, is_reading) = std::sync::mpsc::channel();
+        let reader = tokio::task::spawn_blocking(move || {
+            let mut connection = holder.connection().unwrap();
+            connection
+                .transaction(|connection| {
+                    schema::items::table
+                        .select(schema::items::id)
+                        .load::<i32>(connection)?;
+                    reading.send(()).unwrap();
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                    Ok::<_, diesel::result::Error>(())
+                })
+                .unwrap();
+        });
+        is_reading.recv().unwrap();
+        store.add_checklist_item(&Name::from("eggs")).await.unwrap();
+        reader.await.unwrap();
+    }
+
+    /// A store with two recipes whose ingredients are measured in `g` and
+    /// `tbsp`.
+    async fn store_with_units() -> SqliteStore {
+        let store = inmem_sqlite_store().await;
+        store
+            .add_recipe(
+                &Recipe::new("pancakes"),
+                &Ingredients::from_iter(vec![
+                    Name::from("flour"),
+                    Name::from("sugar"),
+                    Name::from("eggs"),
+                ]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store
+            .add_recipe(
+                &Recipe::new("cookies"),
+                &Ingredients::from_iter(vec![Name::from("flour"), Name::from("butter")]),
+                &None,
+            )
+            .await
+            .unwrap();
+
+        for (recipe, item, quantity, unit) in [
+            ("pancakes", "flour", 200, "g"),
+            ("pancakes", "sugar", 2, "tbsp"),
+            ("cookies", "flour", 250, "g"),
+            ("cookies", "butter", 125, "g"),
+        ] {
+            store
+                .set_ingredient_amount(
+                    &Recipe::new(recipe),
+                    &Name::from(item),
+                    quantity,
+                    Some(unit),
+                )
+                .await
+                .unwrap();
+        }
+        store
+    }
+
+    #[tokio::test]
+    async fn test_set_ingredient_amount() {
+        let store = store_with_units().await;
+
+        let StoreResponse::RecipeIngredients(Some(ingredients)) = store
+            .recipe_ingredients(&Recipe::new("cookies"))
+            .await
+            .unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(ingredients.len(), 2);
+        assert!(matches!(
+            store
+                .set_ingredient_amount(&Recipe::new("cookies"), &Name::from("eggs"), 2, None)
+                .await,
+            Err(StoreError::ItemNotFound(_))
+        ));
+        assert!(matches!(
+            store
+                .set_ingredient_amount(&Recipe::new("bread"), &Name::from("flour"), 500, Some("g"))
+                .await,
+            Err(StoreError::RecipeNotFound(_))
+        ));
+
+        // Merging keeps the amounts of the recipe merged in
+        store
+            .merge_recipes(&Recipe::new("pancakes"), &Recipe::new("cookies"))
+            .await
+            .unwrap();
+        {
+            use schema::{items, items_recipes};
+
+            let amounts: Vec<(String, i32, Option<String>)> = items_recipes::table
+                .inner_join(items::table)
+                .select((items::name, items_recipes::quantity, items_recipes::unit))
+                .order_by(items::name)
+                .load(&mut store.connection().unwrap())
+                .unwrap();
+            assert_eq!(
+                amounts,
+                vec![
+                    ("butter".to_string(), 125, Some("g".to_string())),
+                    ("eggs".to_string(), 1, None),
+                    ("flour".to_string(), 200, Some("g".to_string())),
+                    ("sugar".to_string(), 2, Some("tbsp".to_string())),
+                ]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_distinct_units() {
+        let store = store_with_units().await;
+        assert_eq!(store.distinct_units().await.unwrap(), vec!["g", "tbsp"]);
+    }
+
+    #[tokio::test]
+    async fn test_normalize_unit() {
+        let store = store_with_units().await;
+
+        // flour in both recipes, and butter
+        assert_eq!(store.normalize_unit("g", "grams").await.unwrap(), 3);
+        assert_eq!(store.normalize_unit("g", "grams").await.unwrap(), 0);
+        assert_eq!(store.distinct_units().await.unwrap(), vec!["grams", "tbsp"]);
+    }
+
+    #[tokio::test]
+    async fn test_db_stats() {
+        let store = inmem_sqlite_store().await;
+        store
+            .add_recipe(
+                &Recipe::new("pancakes"),
+                &Ingredients::from_iter(vec![Name::from("eggs"), Name::from("flour")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store.set_list(&[Name::from("bread")]).await.unwrap();
+        store
+            .add_list_recipe(&Recipe::new("pancakes"), false)
+            .await
+            .unwrap();
+
+        let stats = store.db_stats().await.unwrap();
+        assert_eq!(stats.rows("items"), Some(3));
+        assert_eq!(stats.rows("recipes"), Some(1));
+        assert_eq!(stats.rows("items_recipes"), Some(2));
+        assert_eq!(stats.rows("list"), Some(3));
+        assert_eq!(stats.rows("list_items_recipes"), Some(2));
+        assert_eq!(stats.rows("list_recipes"), Some(1));
+        assert_eq!(stats.rows("trips"), Some(0));
+        assert_eq!(stats.file_size(), None);
+    }
+
+    #[tokio::test]
+    async fn test_add_recipe_replaces_deleted_recipe() {
+        let store = inmem_sqlite_store().await;
+
+        let recipe = Recipe::from("soup");
+        store
+            .add_recipe(
+                &recipe,
+                &Ingredients::from_iter([Name::from("leeks"), Name::from("potatoes")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store.delete_recipe(&recipe).await.unwrap();
+        store
+            .add_recipe(
+                &recipe,
+                &Ingredients::from_iter([Name::from("tomatoes")]),
+                &None,
+            )
+            .await
+            .unwrap();
+
+        let StoreResponse::RecipeIngredients(Some(ingredients)) =
+            store.recipe_ingredients(&recipe).await.unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(
+            ingredients,
+            Ingredients::from_iter([Name::from("tomatoes")])
+        );
+
+        let StoreResponse::Recipes(deleted) = store.deleted_recipes().await.unwrap() else {
+            todo!()
+        };
+        assert!(deleted.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_add_recipe_command_reports_new_items() {
+        use common::commands::Add;
+
+        let store = inmem_sqlite_store().await;
+        store.add_item(&Name::from("flour"), &None).await.unwrap();
+
+        let StoreResponse::AddedRecipe(recipe, report) = store
+            .execute_transaction(ApiCommand::Add(Add::Recipe {
+                recipe: Recipe::from("bread"),
+                ingredients: Ingredients::from_input_string("flour, yeast"),
+            }))
+            .await
+            .unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(recipe, Recipe::from("bread"));
+        assert_eq!(report.new_items(), [Name::from("yeast")]);
+        assert_eq!(report.existing_items(), [Name::from("flour")]);
+    }
+
+    #[tokio::test]
+    async fn test_deleted_recipe_lookups() {
+        let store = inmem_sqlite_store().await;
+
+        let recipe = Recipe::from("soup");
+        store
+            .add_recipe(
+                &recipe,
+                &Ingredients::from_iter([Name::from("leeks")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store.add_list_recipe(&recipe, false).await.unwrap();
+        store.delete_recipe(&recipe).await.unwrap();
+
+        assert_eq!(store.recipe_id(&recipe).await.unwrap(), None);
+        assert!(matches!(
+            store.recipe_shopping_status(&recipe).await,
+            Err(StoreError::RecipeNotFound(_))
+        ));
+        assert!(matches!(
+            store.remove_list_recipe(&recipe, false).await,
+            Err(StoreError::RecipeNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_recipes_using() {
+        let store = inmem_sqlite_store().await;
+
+        let tacos = Recipe::new("tacos");
+        let salsa = Recipe::new("salsa verde");
+        store
+            .add_recipe(
+                &tacos,
+                &Ingredients::from_iter(vec![Name::from("cilantro"), Name::from("tortillas")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store
+            .add_recipe(
+                &salsa,
+                &Ingredients::from_iter(vec![
+                    Name::from("fresh cilantro leaves"),
+                    Name::from("tomatillos"),
+                ]),
+                &None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.recipes_using(&Name::from("tortillas")).await.unwrap(),
+            vec![tacos.clone()]
+        );
+        assert_eq!(
+            store.recipes_using(&Name::from("Cilantro")).await.unwrap(),
+            vec![salsa.clone(), tacos]
+        );
+        assert!(store
+            .recipes_using(&Name::from("%"))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_recipe_ingredients_stable_order() {
+        let store = inmem_sqlite_store().await;
+
+        let ingredients = Ingredients::from_iter(vec![
+            Name::from("zucchini"),
+            Name::from("apple"),
+            Name::from("milk"),
+        ]);
+
+        let recipe = Recipe::new("test recipe");
+        store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap();
+
+        let StoreResponse::RecipeIngredients(Some(recipe_ingredients)) =
+            store.recipe_ingredients(&recipe).await.unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(
+            recipe_ingredients,
+            Ingredients::from_iter(vec![
+                Name::from("apple"),
+                Name::from("milk"),
+                Name::from("zucchini"),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_checklist_item() {
+        let store = inmem_sqlite_store().await;
+
+        let item_name = test_item_name();
+        store.add_checklist_item(&item_name).await.unwrap();
+
+        let StoreResponse::Checklist(checklist) = store.checklist().await.unwrap() else {
+            todo!()
+        };
+
+        assert!(checklist.iter().any(|item| item.name() == &item_name));
+
+        store.delete_checklist_item(&item_name).await.unwrap();
+
+        let StoreResponse::Checklist(checklist) = store.checklist().await.unwrap() else {
+            todo!()
+        };
+
+        assert!(checklist.iter().all(|item| item.name() != &item_name));
+    }
+
+    #[tokio::test]
+    async fn test_delete_item() {
+        let store = inmem_sqlite_store().await;
+
+        let eggs = Name::from("eggs");
+        store
+            .add_recipe(
+                &Recipe::new("omelette"),
+                &Ingredients::from_input_string("eggs, butter"),
+                &Some(common::section::Section::from("dairy")),
+            )
+            .await
+            .unwrap();
+        store.add_list_item(&eggs).await.unwrap();
+        store.add_checklist_item(&eggs).await.unwrap();
+        store.subscribe_item(&eggs).await.unwrap();
+
+        store.delete_item(&eggs).await.unwrap();
+
+        assert_eq!(store.item_id(&eggs).await.unwrap(), None);
+        let StoreResponse::RecipeIngredients(Some(ingredients)) = store
+            .recipe_ingredients(&Recipe::new("omelette"))
+            .await
+            .unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(ingredients, Ingredients::from_input_string("butter"));
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        assert!(list.items().is_empty());
+        assert!(list.checklist().is_empty());
+        let StoreResponse::Subscriptions(subscriptions) = store.subscriptions().await.unwrap()
+        else {
+            todo!()
+        };
+        assert!(subscriptions.is_empty());
+
+        assert!(matches!(
+            store.delete_item(&eggs).await,
+            Err(StoreError::ItemNotFound(_))
+        ));
+
+        store.add_checklist_item(&Name::from("salt")).await.unwrap();
+        store.clear_checklist().await.unwrap();
+        let StoreResponse::Checklist(checklist) = store.checklist().await.unwrap() else {
+            todo!()
+        };
+        assert!(checklist.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_recipe() {
+        let store = inmem_sqlite_store().await;
+
+        let ingredients =
+            Ingredients::from_iter(vec![Name::from("ingredient 1"), Name::from("ingredient 2")]);
+
+        let recipe = Recipe::new("test recipe");
+        store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap();
+
+        let StoreResponse::Recipes(recipes) = store.recipes().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(recipes.len(), 1);
+
+        let added_recipe = &recipes[0];
+        assert_eq!(added_recipe.as_str(), "test recipe");
+
+        let StoreResponse::RecipeIngredients(Some(recipe_ingredients)) =
+            store.recipe_ingredients(&recipe).await.unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(recipe_ingredients, ingredients);
+
+        store.delete_recipe(&recipe).await.unwrap();
+
+        let StoreResponse::Recipes(recipes) = store.recipes().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(recipes.len(), 0);
+
+        let StoreResponse::RecipeIngredients(recipe_ingredients) =
+            store.recipe_ingredients(&recipe).await.unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(recipe_ingredients, None);
+    }
+
+    #[tokio::test]
+    async fn test_items_in_section() {
+        let store = inmem_sqlite_store().await;
+
+        let fresh = common::section::Section::from("fresh");
+        let pantry = common::section::Section::from("pantry");
+        store
+            .add_item(&Name::from("carrots"), &Some(fresh.clone()))
+            .await
+            .unwrap();
+        store
+            .add_item(&Name::from("rice"), &Some(pantry.clone()))
+            .await
+            .unwrap();
+        store
+            .add_item(&Name::from("spinach"), &Some(fresh.clone()))
+            .await
+            .unwrap();
+        store.add_item(&Name::from("mystery"), &None).await.unwrap();
+
+        let items = store.items_in_section(&fresh).await.unwrap();
+        let names = items
+            .collection_iter()
+            .map(|item| item.name().as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["carrots", "spinach"]);
+        assert!(items
+            .collection_iter()
+            .all(|item| item.section() == Some(&fresh)));
+
+        let items = store.items_in_section(&pantry).await.unwrap();
+        assert_eq!(items.collection().len(), 1);
+
+        let items = store
+            .items_in_section(&common::section::Section::from("freezer"))
+            .await
+            .unwrap();
+        assert!(items.collection().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_add_recipe_default_section() {
+        let store = inmem_sqlite_store().await;
+
+        let dairy = common::section::Section::from("dairy");
+        let uncategorized = common::section::Section::from("uncategorized");
+        store
+            .add_item(&Name::from("milk"), &Some(dairy.clone()))
+            .await
+            .unwrap();
+
+        let ingredients = Ingredients::from_iter(vec![Name::from("milk"), Name::from("flour")]);
+        store
+            .add_recipe(
+                &Recipe::new("pancakes"),
+                &ingredients,
+                &Some(uncategorized.clone()),
+            )
+            .await
+            .unwrap();
+
+        let items = store.items_in_section(&uncategorized).await.unwrap();
+        let names = items
+            .collection_iter()
+            .map(|item| item.name().as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["flour"]);
+
+        // Items already in the library keep their section
+        let items = store.items_in_section(&dairy).await.unwrap();
+        assert_eq!(items.collection().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_batched_import_matches_per_row_import() {
+        use common::item::Item as CommonItem;
+
+        let fixture = || {
+            Items::from_iter([
+                CommonItem::new("eggs")
+                    .with_section("dairy")
+                    .with_recipes(&[Recipe::new("pancakes"), Recipe::new("omelette")]),
+                CommonItem::new("flour")
+                    .with_section("pantry")
+                    .with_recipes(&[Recipe::new("pancakes")]),
+                CommonItem::new("cheese").with_recipes(&[Recipe::new("omelette")]),
+                CommonItem::new("spinach").with_section("fresh"),
+                CommonItem::new("eggs").with_section("dairy"),
+            ])
+        };
+
+        fn dump(
+            connection: &mut SqliteConnection,
+        ) -> Vec<(String, Option<String>, Option<String>)> {
+            use schema::{items, items_recipes, items_sections, recipes, sections};
+
+            let mut rows: Vec<(String, Option<String>, Option<String>)> = items::table
+                .left_join(items_sections::table.inner_join(sections::table))
+                .left_join(items_recipes::table.inner_join(recipes::table))
+                .select((
+                    items::name,
+                    sections::name.nullable(),
+                    recipes::name.nullable(),
+                ))
+                .load(connection)
+                .unwrap();
+            rows.sort();
+            rows
+        }
+
+        // The import as it was before batching: a statement per row, looking
+        // up each id right after inserting it
+        fn import_per_row(connection: &mut SqliteConnection, items: Items) {
+            use schema::{items, items_recipes, items_sections, recipes, sections};
+
+            for item in items.collection_iter() {
+                diesel::insert_into(items::table)
+                    .values(NewItem {
+                        name: item.name().as_str(),
+                    })
+                    .on_conflict_do_nothing()
+                    .execute(connection)
+                    .unwrap();
+                let item_id: i32 = items::table
+                    .filter(items::name.eq(item.name().as_str()))
+                    .select(items::id)
+                    .first(connection)
+                    .unwrap();
+
+                for recipe in item.recipes().into_iter().flatten() {
+                    diesel::insert_into(recipes::table)
+                        .values(NewRecipe {
+                            name: recipe.as_str(),
+                        })
+                        .on_conflict_do_nothing()
+                        .execute(connection)
+                        .unwrap();
+                    let recipe_id: i32 = recipes::table
+                        .filter(recipes::name.eq(recipe.as_str()))
+                        .select(recipes::id)
+                        .first(connection)
+                        .unwrap();
+                    diesel::insert_into(items_recipes::table)
+                        .values(NewItemRecipe { item_id, recipe_id })
+                        .on_conflict_do_nothing()
+                        .execute(connection)
+                        .unwrap();
+                }
+
+                if let Some(section) = item.section() {
+                    let section_id: i32 = sections::table
+                        .filter(sections::name.eq(section.as_str()))
+                        .select(sections::id)
+                        .first(connection)
+                        .unwrap();
+                    diesel::insert_into(items_sections::table)
+                        .values(NewItemSection {
+                            item_id,
+                            section_id,
+                        })
+                        .on_conflict_do_nothing()
+                        .execute(connection)
+                        .unwrap();
+                }
+            }
+        }
+
+        let batched = inmem_sqlite_store().await;
+        let per_row = inmem_sqlite_store().await;
+        {
+            let mut connection = batched.connection().unwrap();
+            import_sections(&mut connection).unwrap();
+            import_items(&mut connection, fixture()).unwrap();
+        }
+        {
+            let mut connection = per_row.connection().unwrap();
+            import_sections(&mut connection).unwrap();
+            import_per_row(&mut connection, fixture());
+        }
+
+        let batched = dump(&mut batched.connection().unwrap());
+        assert_eq!(batched, dump(&mut per_row.connection().unwrap()));
+        assert_eq!(batched.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_import_from_summary() {
+        use common::item::Item as CommonItem;
+
+        let store = inmem_sqlite_store().await;
+        let dir = assert_fs::TempDir::new().unwrap();
+        let import_store =
+            ImportStore::new(dir.path().join("items.json"), dir.path().join("list.json"));
+        import_store
+            .export_items(Items::from_iter([
+                CommonItem::new("eggs")
+                    .with_section("dairy")
+                    .with_recipes(&[Recipe::new("pancakes"), Recipe::new("omelette")]),
+                CommonItem::new("flour")
+                    .with_section("pantry")
+                    .with_recipes(&[Recipe::new("pancakes")]),
+                CommonItem::new("salt"),
+            ]))
+            .unwrap();
+
+        let StoreResponse::ImportToSqlite(summary) = store
+            .import_from(import_store.clone(), false)
+            .await
+            .unwrap()
+        else {
+            todo!()
+        };
+        insta::assert_display_snapshot!(summary, @r###"
+        items: 3
+        recipes: 2
+        sections: 5
+        recipe ingredients: 3
+        item sections: 2
+        "###);
+
+        // importing again adds nothing new
+        let StoreResponse::ImportToSqlite(summary) =
+            store.import_from(import_store, false).await.unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(summary, MigrationSummary::default());
+    }
+
+    #[tokio::test]
+    async fn test_import_from_resolves_section_ids() {
+        use crate::schema::{items, items_sections, sections};
+        use common::item::Item as CommonItem;
+
+        let store = inmem_sqlite_store().await;
+        let dir = assert_fs::TempDir::new().unwrap();
+        let import_store =
+            ImportStore::new(dir.path().join("items.json"), dir.path().join("list.json"));
+        import_store
+            .export_items(Items::from_iter([
+                CommonItem::new("eggs").with_section("dairy"),
+                CommonItem::new("flour").with_section("pantry"),
+                CommonItem::new("tea lights").with_section("garden"),
+                CommonItem::new("salt"),
+            ]))
+            .unwrap();
+
+        let StoreResponse::ImportToSqlite(summary) =
+            store.import_from(import_store, false).await.unwrap()
+        else {
+            todo!()
+        };
+        // "garden" isn't a default section, so the import adds it
+        assert_eq!(summary.sections(), 6);
+        assert_eq!(summary.item_section_links(), 3);
+
+        let mut connection = store.connection().unwrap();
+        let links: Vec<(String, i32)> = items_sections::table
+            .inner_join(items::table.on(items::id.eq(items_sections::item_id)))
+            .select((items::name, items_sections::section_id))
+            .order_by(items::name)
+            .load(&mut connection)
+            .unwrap();
+        let section_ids: HashMap<String, i32> = sections::table
+            .select((sections::name, sections::id))
+            .load(&mut connection)
+            .unwrap()
+            .into_iter()
+            .collect();
+        let expected = vec![
+            ("eggs".to_string(), section_ids["dairy"]),
+            ("flour".to_string(), section_ids["pantry"]),
+            ("tea lights".to_string(), section_ids["garden"]),
+        ];
+        assert_eq!(links, expected);
+    }
+
+    #[tokio::test]
+    async fn test_fresh_import_resolves_recreated_section_ids() {
+        use crate::schema::{items, items_sections, sections};
+        use common::item::Item as CommonItem;
+
+        let store = inmem_sqlite_store().await;
+        let dir = assert_fs::TempDir::new().unwrap();
+        let import_store =
+            ImportStore::new(dir.path().join("items.json"), dir.path().join("list.json"));
+        import_store
+            .export_items(Items::from_iter([
+                CommonItem::new("eggs").with_section("dairy")
+            ]))
+            .unwrap();
+
+        store
+            .import_from(import_store.clone(), false)
+            .await
+            .unwrap();
+        // clearing the tables recreates the sections under new ids
+        store.import_from(import_store, true).await.unwrap();
+
+        let mut connection = store.connection().unwrap();
+        let section: String = items_sections::table
+            .inner_join(items::table.on(items::id.eq(items_sections::item_id)))
+            .inner_join(sections::table.on(sections::id.eq(items_sections::section_id)))
+            .filter(items::name.eq("eggs"))
+            .select(sections::name)
+            .first(&mut connection)
+            .unwrap();
+        assert_eq!(section, "dairy");
+    }
+
+    #[tokio::test]
+    async fn test_items_with_sections() {
+        use common::{item::Item as CommonItem, section::Section};
+
+        let store = inmem_sqlite_store().await;
+
+        store
+            .add_item(&Name::from("milk"), &Some(Section::from("dairy")))
+            .await
+            .unwrap();
+        store.add_item(&Name::from("mystery"), &None).await.unwrap();
+        store
+            .add_item(&Name::from("carrots"), &Some(Section::from("fresh")))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.items_with_sections().await.unwrap(),
+            vec![
+                (CommonItem::new("carrots"), Some(Section::from("fresh"))),
+                (CommonItem::new("milk"), Some(Section::from("dairy"))),
+                (CommonItem::new("mystery"), None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_section_shows_in_grouped_list() {
+        use common::section::Section;
+
+        let store = inmem_sqlite_store().await;
+
+        store
+            .add_item(&Name::from("carrots"), &Some(Section::from("veg")))
+            .await
+            .unwrap();
+        store
+            .add_item(&Name::from("milk"), &Some(Section::from("dairy")))
+            .await
+            .unwrap();
+        store
+            .add_item(&Name::from("apples"), &Some(Section::from("fresh")))
+            .await
+            .unwrap();
+        store
+            .set_list(&["apples", "carrots", "milk"].map(Name::from))
+            .await
+            .unwrap();
+
+        store
+            .rename_section(&Section::from("dairy"), &Section::from("fridge"))
+            .await
+            .unwrap();
+        store
+            .rename_section(&Section::from("veg"), &Section::from("fresh"))
+            .await
+            .unwrap();
+
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        insta::assert_display_snapshot!(list.to_plain_by_section("other"), @r###"
+        fresh:
+          apples
+          carrots
+        fridge:
+          milk
+        "###);
+
+        assert!(matches!(
+            store
+                .rename_section(&Section::from("veg"), &Section::from("greens"))
+                .await,
+            Err(StoreError::SectionNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_shopping_order() {
+        use common::section::Section;
+
+        let store = inmem_sqlite_store().await;
+        store.seed_default_sections().await.unwrap();
+
+        for (name, section) in [
+            ("milk", Some("dairy")),
+            ("spinach", Some("fresh")),
+            ("eggs", Some("dairy")),
+            ("carrots", Some("fresh")),
+            ("mystery", None),
+        ] {
+            store
+                .add_item(&Name::from(name), &section.map(Section::from))
+                .await
+                .unwrap();
+        }
+        let omelette = Recipe::new("omelette");
+        store
+            .add_recipe(
+                &omelette,
+                &Ingredients::from_iter(vec![Name::from("eggs"), Name::from("spinach")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        let quiche = Recipe::new("quiche");
+        store
+            .add_recipe(
+                &quiche,
+                &Ingredients::from_iter(vec![Name::from("eggs"), Name::from("milk")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store.add_list_recipe(&omelette, false).await.unwrap();
+        store.add_list_recipe(&quiche, false).await.unwrap();
+        for name in ["carrots", "milk", "mystery"] {
+            store.add_list_item(&Name::from(name)).await.unwrap();
+        }
+
+        insta::assert_display_snapshot!(store.shopping_order().await.unwrap(), @r###"
+        fresh:
+          carrots
+          spinach [omelette]
+        dairy:
+          eggs (x2) [omelette, quiche]
+          milk (x2) [quiche]
+        other:
+          mystery
+        "###);
+    }
+
+    #[tokio::test]
+    async fn test_is_empty() {
+        let store = inmem_sqlite_store().await;
+        assert!(store.is_empty().await.unwrap());
+
+        store.add_item(&Name::from("milk"), &None).await.unwrap();
+        assert!(!store.is_empty().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_recipes_paged_sorted() {
+        let store = inmem_sqlite_store().await;
+
+        for name in ["omelette", "crepes", "waffles", "pancakes", "toast"] {
+            store
+                .add_recipe(
+                    &Recipe::new(name),
+                    &Ingredients::from_iter(vec![Name::from("eggs")]),
+                    &None,
+                )
+                .await
+                .unwrap();
+        }
+
+        let page = |offset, limit, sort| {
+            let store = store.clone();
+            async move {
+                store
+                    .recipes_paged_sorted(offset, limit, sort)
+                    .await
+                    .unwrap()
+                    .iter()
+                    .map(|recipe| recipe.to_string())
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(
+            page(0, 2, RecipeSort::NameAsc).await,
+            ["crepes", "omelette"]
+        );
+        assert_eq!(page(2, 2, RecipeSort::NameAsc).await, ["pancakes", "toast"]);
+        assert_eq!(page(4, 2, RecipeSort::NameAsc).await, ["waffles"]);
+        assert!(page(6, 2, RecipeSort::NameAsc).await.is_empty());
+        assert_eq!(
+            page(0, 3, RecipeSort::NameDesc).await,
+            ["waffles", "toast", "pancakes"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_item_and_recipe_ids() {
+        let store = inmem_sqlite_store().await;
+
+        let recipe = Recipe::new("pancakes");
+        store
+            .add_recipe(
+                &recipe,
+                &Ingredients::from_iter(vec![Name::from("eggs")]),
+                &None,
+            )
+            .await
+            .unwrap();
+
+        let id = store.item_id(&Name::from("eggs")).await.unwrap();
+        assert!(id.is_some());
+        assert_eq!(store.item_id(&Name::from("eggs")).await.unwrap(), id);
+        assert!(store.recipe_id(&recipe).await.unwrap().is_some());
+
+        assert_eq!(store.item_id(&Name::from("bacon")).await.unwrap(), None);
+        assert_eq!(
+            store.recipe_id(&Recipe::new("waffles")).await.unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_sections() {
+        use common::section::Section;
+
+        let store = inmem_sqlite_store().await;
+
+        store.seed_default_sections().await.unwrap();
+        store
+            .add_item(&Name::from("milk"), &Some(Section::from("dairy")))
+            .await
+            .unwrap();
+
+        let empty = ["freezer", "fresh", "pantry", "protein"].map(Section::from);
+        assert_eq!(store.empty_sections().await.unwrap(), empty.to_vec());
+        assert_eq!(store.delete_empty_sections().await.unwrap(), empty.to_vec());
+        assert!(store.empty_sections().await.unwrap().is_empty());
+
+        let StoreResponse::Sections(sections) = store.sections().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(sections, vec![Section::from("dairy")]);
+    }
+
+    #[tokio::test]
+    async fn test_recipe_ingredients_detailed() {
+        use common::{item::Item as CommonItem, section::Section};
+
+        let store = inmem_sqlite_store().await;
+
+        store
+            .add_item(&Name::from("eggs"), &Some(Section::from("dairy")))
+            .await
+            .unwrap();
+        store
+            .add_item(&Name::from("spinach"), &Some(Section::from("fresh")))
+            .await
+            .unwrap();
+
+        let recipe = Recipe::new("omelette");
+        let ingredients = Ingredients::from_iter(vec![
+            Name::from("eggs"),
+            Name::from("spinach"),
+            Name::from("salt"),
+        ]);
+        store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.recipe_ingredients_detailed(&recipe).await.unwrap(),
+            vec![
+                (CommonItem::new("eggs"), Some(Section::from("dairy"))),
+                (CommonItem::new("salt"), None),
+                (CommonItem::new("spinach"), Some(Section::from("fresh"))),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restore_recipe() {
+        let store = inmem_sqlite_store().await;
+
+        let ingredients =
+            Ingredients::from_iter(vec![Name::from("ingredient 1"), Name::from("ingredient 2")]);
+
+        let recipe = Recipe::new("test recipe");
+        store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap();
+        store.delete_recipe(&recipe).await.unwrap();
+
+        let StoreResponse::Recipes(recipes) = store.recipes().await.unwrap() else {
+            todo!()
+        };
+        assert!(recipes.is_empty());
+
+        let StoreResponse::Recipes(deleted) = store.deleted_recipes().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(deleted, vec![recipe.clone()]);
+
+        store.restore_recipe(&recipe).await.unwrap();
+
+        let StoreResponse::Recipes(recipes) = store.recipes().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(recipes, vec![recipe.clone()]);
+
+        let StoreResponse::Recipes(deleted) = store.deleted_recipes().await.unwrap() else {
+            todo!()
+        };
+        assert!(deleted.is_empty());
+
+        let StoreResponse::RecipeIngredients(Some(recipe_ingredients)) =
+            store.recipe_ingredients(&recipe).await.unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(recipe_ingredients, ingredients);
+
+        assert!(matches!(
+            store.restore_recipe(&recipe).await,
+            Err(StoreError::RecipeNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fresh_import_from_json() {
+        use common::item::Item as CommonItem;
+
+        let store = inmem_sqlite_store().await;
+
+        let stale = Recipe::new("stale recipe");
+        store
+            .add_recipe(
+                &stale,
+                &Ingredients::from_iter(vec![Name::from("stale item")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store.add_list_recipe(&stale, false).await.unwrap();
+        store
+            .add_checklist_item(&Name::from("stale item"))
+            .await
+            .unwrap();
+
+        let dir = assert_fs::TempDir::new().unwrap();
+        let import_store =
+            ImportStore::new(dir.path().join("items.json"), dir.path().join("list.json"));
+        import_store
+            .export_items(Items::from_iter([CommonItem::new("fresh item")
+                .with_section("fresh")
+                .with_recipes(&[Recipe::new("fresh recipe")])]))
+            .unwrap();
+
+        let StoreResponse::ImportToSqlite(summary) =
+            store.import_from(import_store, true).await.unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(summary.items(), 1);
+
+        let names = store
+            .items()
+            .await
+            .unwrap()
+            .collection_iter()
+            .map(|item| item.name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["fresh item"]);
+
+        let StoreResponse::Recipes(recipes) = store.recipes().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(recipes, vec![Recipe::new("fresh recipe")]);
+
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        assert!(list.items().is_empty());
+        assert!(list.recipes().is_empty());
+        assert!(list.checklist().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_paprika() {
+        use assert_fs::prelude::*;
+
+        let store = inmem_sqlite_store().await;
+
+        let file = assert_fs::NamedTempFile::new("recipes.json").unwrap();
+        file.write_str(
+            r#"[
+                {
+                    "name": "Pancakes",
+                    "ingredients": "1 1/2 cups plain flour\n2 eggs\n\nFor the topping:\n1 tbsp maple syrup, warmed",
+                    "directions": "Mix and fry."
+                },
+                { "name": "Green Salad", "ingredients": "1 head lettuce\nolive oil" }
+            ]"#,
+        )
+        .unwrap();
+
+        let StoreResponse::ImportedRecipes(recipes) = store
+            .execute_transaction(ApiCommand::ImportPaprika(file.path().to_path_buf()))
+            .await
+            .unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(
+            recipes,
+            vec![Recipe::from("pancakes"), Recipe::from("green salad")]
+        );
+
+        {
+            use schema::{items, items_recipes, recipes};
+
+            let amounts: Vec<(String, String, i32, Option<String>)> = items_recipes::table
+                .inner_join(items::table)
+                .inner_join(recipes::table)
+                .select((
+                    recipes::name,
+                    items::name,
+                    items_recipes::quantity,
+                    items_recipes::unit,
+                ))
+                .order_by((recipes::name, items::name))
+                .load(&mut store.connection().unwrap())
+                .unwrap();
+            assert_eq!(
+                amounts,
+                [
+                    ("green salad", "lettuce", 1, Some("head")),
+                    ("green salad", "olive oil", 1, None),
+                    ("pancakes", "eggs", 2, None),
+                    ("pancakes", "maple syrup", 1, Some("tbsp")),
+                    ("pancakes", "plain flour", 2, Some("cup")),
+                ]
+                .map(|(recipe, item, quantity, unit)| {
+                    (
+                        recipe.to_string(),
+                        item.to_string(),
+                        quantity,
+                        unit.map(String::from),
+                    )
+                })
+            );
+        }
+
+        // A recipe that can't be added leaves out the whole export
+        let store = store.with_max_ingredients(2);
+        file.write_str(
+            r#"[
+                { "name": "Toast", "ingredients": "bread\nbutter" },
+                { "name": "Porridge", "ingredients": "oats\nmilk\nhoney" }
+            ]"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            store
+                .execute_transaction(ApiCommand::ImportPaprika(file.path().to_path_buf()))
+                .await,
+            Err(StoreError::TooManyIngredients(_, 3, 2))
+        ));
+        let StoreResponse::RecipeIngredients(toast) = store
+            .recipe_ingredients(&Recipe::from("toast"))
+            .await
+            .unwrap()
+        else {
+            todo!()
+        };
+        assert!(toast.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_import_items_csv() {
+        use assert_fs::prelude::*;
+        use common::section::{ItemsCsvReport, Section};
+
+        let store = inmem_sqlite_store().await;
+        store
+            .add_item(&Name::from("milk"), &Some(Section::from("fresh")))
+            .await
+            .unwrap();
+
+        let file = assert_fs::NamedTempFile::new("items.csv").unwrap();
+        file.write_str("name,section\nmilk,dairy\ncandles,household\nnot a row\n")
+            .unwrap();
+
+        let StoreResponse::ImportedItemsCsv(report) = store
+            .execute_transaction(ApiCommand::ImportItemsCsv(file.path().to_path_buf()))
+            .await
+            .unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(report, ItemsCsvReport::new(2, vec![4]));
+
+        assert_eq!(
+            store.items_with_sections().await.unwrap(),
+            vec![
+                (
+                    common::item::Item::new("candles"),
+                    Some(Section::from("household"))
+                ),
+                (
+                    common::item::Item::new("milk"),
+                    Some(Section::from("dairy"))
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_item_sections() {
+        use common::section::{Section, SectionMapReport};
+
+        let store = inmem_sqlite_store().await;
+        store.seed_default_sections().await.unwrap();
+
+        for name in ["milk", "spinach"] {
+            store.add_item(&Name::from(name), &None).await.unwrap();
+        }
+
+        let report = store
+            .set_item_sections(&[
+                (Name::from("milk"), Section::from("dairy")),
+                (Name::from("spinach"), Section::from("fresh")),
+                (Name::from("unicorn steak"), Section::from("protein")),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            SectionMapReport::new(2, vec![Name::from("unicorn steak")], vec![])
+        );
+
+        assert_eq!(
+            store.items_with_sections().await.unwrap(),
+            vec![
+                (
+                    common::item::Item::new("milk"),
+                    Some(Section::from("dairy"))
+                ),
+                (
+                    common::item::Item::new("spinach"),
+                    Some(Section::from("fresh"))
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_item_sections_many_pairs() {
+        use common::section::Section;
+
+        let store = inmem_sqlite_store().await;
+        store.seed_default_sections().await.unwrap();
+        store.add_item(&Name::from("milk"), &None).await.unwrap();
+
+        // more names than SQLite allows bound variables in one statement
+        let mut pairs: Vec<(Name, Section)> = (0..300_000)
+            .map(|i| {
+                (
+                    Name::from(format!("item {i}").as_str()),
+                    Section::from("pantry"),
+                )
+            })
+            .collect();
+        pairs.push((Name::from("milk"), Section::from("dairy")));
+
+        let report = store.set_item_sections(&pairs).await.unwrap();
+        assert_eq!(report.applied(), 1);
+        assert_eq!(report.missing_items().len(), 300_000);
+    }
+
+    #[tokio::test]
+    async fn test_apply_section_map() {
+        use assert_fs::prelude::*;
+        use common::{
+            load::Load,
+            section::{Section, SectionMap, SectionMapReport},
+        };
+
+        let store = inmem_sqlite_store().await;
+
+        store
+            .add_item(&Name::from("milk"), &Some(Section::from("fresh")))
+            .await
+            .unwrap();
+        store
+            .add_item(&Name::from("cheese"), &Some(Section::from("dairy")))
+            .await
+            .unwrap();
+
+        let file = assert_fs::NamedTempFile::new("sections.json").unwrap();
+        file.write_str(r#"{ "milk": "dairy", "unicorn steak": "dairy", "cheese": "cheese cave" }"#)
+            .unwrap();
+        let map = SectionMap::from_json(file.path()).unwrap();
+
+        let report = store.apply_section_map(&map).await.unwrap();
+        assert_eq!(
+            report,
+            SectionMapReport::new(
+                1,
+                vec![Name::from("unicorn steak")],
+                vec![Section::from("cheese cave")]
+            )
+        );
+
+        let items = store
+            .items_in_section(&Section::from("dairy"))
+            .await
+            .unwrap();
+        let names = items
+            .collection_iter()
+            .map(|item| item.name().as_str())
+            .collect::<Vec<_>>();
+        // items in a section come back in the order they were added
+        assert_eq!(names, vec!["milk", "cheese"]);
+        assert!(store
+            .items_in_section(&Section::from("fresh"))
+            .await
+            .unwrap()
+            .collection()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_list() {
+        let store = inmem_sqlite_store().await;
+
+        store.refresh_list(false).await.unwrap();
+
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(list.items().len(), 0);
+
+        let item1 = Name::from("item 1");
+        let item2 = Name::from("item 2");
+        store.add_list_item(&item1).await.unwrap();
+        store.add_list_item(&item2).await.unwrap();
+
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(list.items().len(), 2);
+        assert!(list.items().iter().any(|item| item.name() == &item1));
+        assert!(list.items().iter().any(|item| item.name() == &item2));
+
+        store.refresh_list(false).await.unwrap();
+
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(list.items().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_list_quantities_across_recipes() {
+        let store = inmem_sqlite_store().await;
+
+        let pancakes = Recipe::new("pancakes");
+        let omelette = Recipe::new("omelette");
+        store
+            .add_recipe(
+                &pancakes,
+                &Ingredients::from_input_string("eggs, flour"),
+                &None,
+            )
+            .await
+            .unwrap();
+        store
+            .add_recipe(
+                &omelette,
+                &Ingredients::from_input_string("eggs, cheese"),
+                &None,
+            )
+            .await
+            .unwrap();
+        store.add_list_recipe(&pancakes, false).await.unwrap();
+        store.add_list_recipe(&omelette, false).await.unwrap();
+        store.add_list_item(&Name::from("cheese")).await.unwrap();
+        store.add_list_item(&Name::from("salt")).await.unwrap();
+
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        let quantities = list
+            .items()
+            .iter()
+            .map(|item| (item.name().to_string(), item.quantity()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            quantities,
+            vec![
+                ("cheese".to_string(), Some(2)),
+                ("eggs".to_string(), Some(2)),
+                ("flour".to_string(), Some(1)),
+                ("salt".to_string(), Some(1)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_items_missing_from_library() {
+        let store = inmem_sqlite_store().await;
+
+        store.add_list_item(&Name::from("milk")).await.unwrap();
+        assert!(store
+            .list_items_missing_from_library()
+            .await
+            .unwrap()
+            .is_empty());
+
+        diesel::insert_into(schema::list::table)
+            .values(crate::models::NewListItem { id: 999 })
+            .execute(&mut store.connection().unwrap())
+            .unwrap();
+        assert_eq!(
+            store.list_items_missing_from_library().await.unwrap(),
+            vec![999]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_checklist_overlap() {
+        let store = inmem_sqlite_store().await;
+
+        store.add_list_item(&Name::from("milk")).await.unwrap();
+        store.add_checklist_item(&Name::from("soap")).await.unwrap();
+        assert!(store.list_checklist_overlap().await.unwrap().is_empty());
+
+        store.add_checklist_item(&Name::from("milk")).await.unwrap();
+        assert_eq!(
+            store.list_checklist_overlap().await.unwrap(),
+            vec![Name::from("milk")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_substitute_ingredient() {
+        let store = inmem_sqlite_store().await;
+
+        let (cookies, toast) = (Recipe::new("cookies"), Recipe::new("toast"));
+        store
+            .add_recipe(
+                &cookies,
+                &Ingredients::from_iter(vec![Name::from("butter"), Name::from("flour")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store
+            .add_recipe(
+                &toast,
+                &Ingredients::from_iter(vec![Name::from("bread"), Name::from("butter")]),
+                &None,
+            )
+            .await
+            .unwrap();
+
+        let spread = Recipe::new("spread");
+        store
+            .add_recipe(
+                &spread,
+                &Ingredients::from_iter(vec![Name::from("butter"), Name::from("margarine")]),
+                &None,
+            )
+            .await
+            .unwrap();
+        {
+            use schema::{items, items_recipes, recipes};
+
+            let mut connection = store.connection().unwrap();
+            let butter_in = |recipe: &'static str| {
+                items_recipes::item_id
+                    .eq_any(
+                        items::table
+                            .filter(items::name.eq("butter"))
+                            .select(items::id),
+                    )
+                    .and(
+                        items_recipes::recipe_id.eq_any(
+                            recipes::table
+                                .filter(recipes::name.eq(recipe))
+                                .select(recipes::id),
+                        ),
+                    )
+            };
+            diesel::update(items_recipes::table.filter(butter_in("cookies")))
+                .set((items_recipes::quantity.eq(250), items_recipes::unit.eq("g")))
+                .execute(&mut connection)
+                .unwrap();
+        }
 
-    async fn inmem_sqlite_store() -> SqliteStore {
-        // Set up a connection to an in-memory SQLite database for testing
-        let store = SqliteStore::new(DbUri::inmem()).await.unwrap();
-        let migrations_store = store.clone();
-        tokio::task::spawn_blocking(move || {
-            let mut connection = migrations_store.connection().unwrap();
-            connection.immediate_transaction(run_migrations).unwrap();
-        })
-        .await
-        .unwrap();
         store
+            .substitute_ingredient(&Name::from("butter"), &Name::from("margarine"))
+            .await
+            .unwrap();
+
+        {
+            use schema::{items, items_recipes, recipes};
+
+            let amounts: Vec<(String, i32, Option<String>)> = items_recipes::table
+                .inner_join(items::table)
+                .inner_join(recipes::table)
+                .filter(items::name.eq("margarine"))
+                .select((recipes::name, items_recipes::quantity, items_recipes::unit))
+                .order_by(recipes::name)
+                .load(&mut store.connection().unwrap())
+                .unwrap();
+            assert_eq!(
+                amounts,
+                vec![
+                    ("cookies".to_string(), 250, Some("g".to_string())),
+                    ("spread".to_string(), 1, None),
+                    ("toast".to_string(), 1, None),
+                ]
+            );
+        }
+
+        for (recipe, expected) in [
+            (cookies, vec!["flour", "margarine"]),
+            (toast, vec!["bread", "margarine"]),
+            (spread, vec!["margarine"]),
+        ] {
+            let StoreResponse::RecipeIngredients(Some(ingredients)) =
+                store.recipe_ingredients(&recipe).await.unwrap()
+            else {
+                todo!()
+            };
+            assert_eq!(
+                ingredients.iter().map(Name::as_str).collect::<Vec<_>>(),
+                expected
+            );
+        }
     }
 
-    fn test_item_name() -> Name {
-        Name::from("test item")
+    #[tokio::test]
+    async fn test_recipes_not_on_list() {
+        let store = inmem_sqlite_store().await;
+
+        let ingredients = Ingredients::from_iter(vec![Name::from("eggs")]);
+        let (omelette, quiche) = (Recipe::new("omelette"), Recipe::new("quiche"));
+        for recipe in [&omelette, &quiche] {
+            store.add_recipe(recipe, &ingredients, &None).await.unwrap();
+        }
+        store.add_list_recipe(&omelette, false).await.unwrap();
+
+        assert_eq!(store.recipes_not_on_list().await.unwrap(), vec![quiche]);
     }
 
     #[tokio::test]
-    async fn test_add_checklist_item() {
+    async fn test_recipe_shopping_status() {
         let store = inmem_sqlite_store().await;
 
-        let item_name = test_item_name();
-        store.add_checklist_item(&item_name).await.unwrap();
+        let recipe = Recipe::new("pancakes");
+        let ingredients = Ingredients::from_iter(vec![
+            Name::from("flour"),
+            Name::from("eggs"),
+            Name::from("milk"),
+        ]);
+        store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap();
+        store.add_list_item(&Name::from("eggs")).await.unwrap();
 
-        let StoreResponse::Checklist(list) = store.checklist().await.unwrap() else {
+        assert_eq!(
+            store.recipe_shopping_status(&recipe).await.unwrap(),
+            vec![
+                (Name::from("eggs"), true),
+                (Name::from("flour"), false),
+                (Name::from("milk"), false),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_items_from_recipe_on_list() {
+        let store = inmem_sqlite_store().await;
+
+        let recipe = Recipe::new("pancakes");
+        let ingredients = Ingredients::from_iter(vec![Name::from("flour"), Name::from("eggs")]);
+        store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap();
+        store.add_list_recipe(&recipe, false).await.unwrap();
+        store.add_list_item(&Name::from("coffee")).await.unwrap();
+
+        assert_eq!(
+            store
+                .items_from_recipe_on_list(&recipe)
+                .await
+                .unwrap()
+                .iter()
+                .map(|item| item.name().clone())
+                .collect::<Vec<_>>(),
+            vec![Name::from("eggs"), Name::from("flour")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_toggle_list_item() {
+        let store = inmem_sqlite_store().await;
+        let milk = Name::from("milk");
+
+        assert!(store.toggle_list_item(&milk).await.unwrap());
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
             todo!()
         };
+        assert_eq!(list.items().len(), 1);
 
-        assert!(list.iter().any(|item| item.name() == &item_name));
+        assert!(!store.toggle_list_item(&milk).await.unwrap());
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        assert!(list.items().is_empty());
     }
 
     #[tokio::test]
-    async fn test_add_item() {
+    async fn test_add_all_items_to_list() {
         let store = inmem_sqlite_store().await;
 
-        let item_name = test_item_name();
-        store.add_item(&item_name, &None).await.unwrap();
+        for name in ["bread", "eggs", "milk"] {
+            store.add_item(&Name::from(name), &None).await.unwrap();
+        }
+        store.add_list_item(&Name::from("milk")).await.unwrap();
 
-        let items = store.items().await.unwrap();
+        assert_eq!(store.add_all_items_to_list().await.unwrap(), 2);
 
-        assert!(items
-            .collection_iter()
-            .any(|item| item.name() == &item_name));
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        let mut names = list
+            .items()
+            .iter()
+            .map(|item| item.name().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["bread", "eggs", "milk"]);
+
+        let StoreResponse::List(items) = store.list_items().await.unwrap() else {
+            todo!()
+        };
+        assert!(items.items().iter().all(|item| item.quantity() == Some(1)));
     }
 
     #[tokio::test]
-    async fn test_add_list_item() {
+    async fn test_set_list() {
         let store = inmem_sqlite_store().await;
 
-        let item_name = test_item_name();
-        store.add_list_item(&item_name).await.unwrap();
+        store.add_list_item(&Name::from("milk")).await.unwrap();
+        store.add_list_item(&Name::from("bread")).await.unwrap();
+
+        let items = ["eggs", "flour", "milk"].map(Name::from);
+        store.set_list(&items).await.unwrap();
 
         let StoreResponse::List(list) = store.list().await.unwrap() else {
             todo!()
         };
-
-        let item_in_list = list.items().iter().any(|item| item.name() == &item_name);
-
-        assert!(item_in_list);
+        assert_eq!(
+            list.items()
+                .iter()
+                .map(|item| item.name().clone())
+                .collect::<Vec<_>>(),
+            items.to_vec()
+        );
     }
 
     #[tokio::test]
-    async fn test_add_list_recipe() {
+    async fn test_clear_list_keeps_checklist() {
         let store = inmem_sqlite_store().await;
 
-        let ingredients =
-            Ingredients::from_iter(vec![Name::from("ingredient 1"), Name::from("ingredient 2")]);
-
-        let recipe = Recipe::new("test recipe");
-        store.add_recipe(&recipe, &ingredients).await.unwrap();
+        let checklist_item = Name::from("toothpaste");
+        store.add_checklist_item(&checklist_item).await.unwrap();
+        let recipe = Recipe::new("pancakes");
+        store
+            .add_recipe(
+                &recipe,
+                &Ingredients::from_input_string("flour, milk"),
+                &None,
+            )
+            .await
+            .unwrap();
+        store.add_list_recipe(&recipe, false).await.unwrap();
 
-        store.add_list_recipe(&recipe).await.unwrap();
+        store.clear_list().await.unwrap();
 
         let StoreResponse::List(list) = store.list().await.unwrap() else {
             todo!()
         };
-        insta::assert_debug_snapshot!(list, @r###"
-        List {
-            checklist: [],
-            recipes: [
-                Recipe(
-                    "test recipe",
-                ),
-            ],
-            items: [
-                Item {
-                    name: Name(
-                        "ingredient 1",
-                    ),
-                    section: None,
-                    recipes: None,
-                },
-                Item {
-                    name: Name(
-                        "ingredient 2",
-                    ),
-                    section: None,
-                    recipes: None,
-                },
-            ],
-        }
-        "###);
+        assert!(list.items().is_empty());
+        assert!(list.recipes().is_empty());
+        let StoreResponse::Checklist(checklist) = store.checklist().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(
+            checklist
+                .iter()
+                .map(|item| item.name().clone())
+                .collect::<Vec<_>>(),
+            vec![checklist_item]
+        );
     }
 
     #[tokio::test]
-    async fn test_add_recipe() {
-        let store = inmem_sqlite_store().await;
+    async fn test_export_recipe_round_trip() {
+        use common::recipes::RecipeExport;
 
-        let ingredients =
-            Ingredients::from_iter(vec![Name::from("ingredient 1"), Name::from("ingredient 2")]);
+        let store = inmem_sqlite_store().await;
+        let recipe = Recipe::new("pancakes");
+        let ingredients = Ingredients::from_input_string("eggs, flour, milk");
+        store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap();
 
-        let recipe = Recipe::new("test recipe");
-        store.add_recipe(&recipe, &ingredients).await.unwrap();
+        let StoreResponse::ExportedRecipe(json) = store.export_recipe(recipe).await.unwrap() else {
+            todo!()
+        };
+        insta::assert_display_snapshot!(json, @r###"{"recipe":"pancakes","ingredients":["eggs","flour","milk"]}"###);
 
-        let StoreResponse::Recipes(recipes) = store.recipes().await.unwrap() else {
+        let exported = RecipeExport::from_json_str(&json).unwrap();
+        let other = inmem_sqlite_store().await;
+        other
+            .add_recipe(exported.recipe(), exported.ingredients(), &None)
+            .await
+            .unwrap();
+        let StoreResponse::RecipeIngredients(Some(imported)) =
+            other.recipe_ingredients(exported.recipe()).await.unwrap()
+        else {
             todo!()
         };
-        assert_eq!(recipes.len(), 1);
+        assert_eq!(imported, ingredients);
 
-        let added_recipe = &recipes[0];
-        assert_eq!(added_recipe.as_str(), "test recipe");
+        assert!(matches!(
+            store.export_recipe(Recipe::new("waffles")).await,
+            Err(StoreError::RecipeNotFound(_))
+        ));
+    }
 
-        let StoreResponse::RecipeIngredients(Some(recipe_ingredients)) =
-            store.recipe_ingredients(&recipe).await.unwrap()
+    #[tokio::test]
+    async fn test_rename_recipe() {
+        let store = inmem_sqlite_store().await;
+
+        let recipe = Recipe::new("pancakes");
+        let ingredients = Ingredients::from_input_string("flour, eggs");
+        store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap();
+
+        let new_name = Recipe::new("crepes");
+        store.rename_recipe(&recipe, &new_name).await.unwrap();
+
+        let StoreResponse::Recipes(recipes) = store.recipes().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(recipes, vec![new_name.clone()]);
+        let StoreResponse::RecipeIngredients(Some(renamed)) =
+            store.recipe_ingredients(&new_name).await.unwrap()
         else {
             todo!()
         };
-        assert_eq!(recipe_ingredients, ingredients);
+        assert_eq!(renamed, Ingredients::from_input_string("eggs, flour"));
     }
 
     #[tokio::test]
-    async fn test_delete_checklist_item() {
+    async fn test_rename_recipe_collision() {
         let store = inmem_sqlite_store().await;
 
-        let item_name = test_item_name();
-        store.add_checklist_item(&item_name).await.unwrap();
+        let pancakes = Recipe::new("pancakes");
+        let crepes = Recipe::new("crepes");
+        for recipe in [&pancakes, &crepes] {
+            store
+                .add_recipe(recipe, &Ingredients::from_input_string("flour"), &None)
+                .await
+                .unwrap();
+        }
 
-        let StoreResponse::Checklist(checklist) = store.checklist().await.unwrap() else {
+        assert!(matches!(
+            store.rename_recipe(&pancakes, &crepes).await,
+            Err(StoreError::RecipeExists(name)) if name == "crepes"
+        ));
+        assert!(matches!(
+            store
+                .rename_recipe(&Recipe::new("waffles"), &Recipe::new("gofres"))
+                .await,
+            Err(StoreError::RecipeNotFound(_))
+        ));
+
+        // renaming to its own name is a no-op, not a collision
+        store.rename_recipe(&crepes, &crepes).await.unwrap();
+
+        // a deleted recipe's name is free to take
+        store.delete_recipe(&crepes).await.unwrap();
+        store.rename_recipe(&pancakes, &crepes).await.unwrap();
+        let StoreResponse::Recipes(recipes) = store.recipes().await.unwrap() else {
             todo!()
         };
+        assert_eq!(recipes, vec![crepes]);
+    }
 
-        assert!(checklist.iter().any(|item| item.name() == &item_name));
+    #[tokio::test]
+    async fn test_rename_item() {
+        let store = inmem_sqlite_store().await;
 
-        store.delete_checklist_item(&item_name).await.unwrap();
+        let item = Name::from("spagheti");
+        store.add_list_item(&item).await.unwrap();
+        store.add_checklist_item(&item).await.unwrap();
 
+        let new_name = Name::from("spaghetti");
+        store.rename_item(&item, &new_name).await.unwrap();
+
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(
+            list.items()
+                .iter()
+                .map(|item| item.name().clone())
+                .collect::<Vec<_>>(),
+            vec![new_name.clone()]
+        );
         let StoreResponse::Checklist(checklist) = store.checklist().await.unwrap() else {
             todo!()
         };
-
-        assert!(checklist.iter().all(|item| item.name() != &item_name));
+        assert_eq!(
+            checklist
+                .iter()
+                .map(|item| item.name().clone())
+                .collect::<Vec<_>>(),
+            vec![new_name]
+        );
     }
 
     #[tokio::test]
-    async fn test_delete_recipe() {
+    async fn test_rename_item_merges_into_existing_item() {
         let store = inmem_sqlite_store().await;
 
-        let ingredients =
-            Ingredients::from_iter(vec![Name::from("ingredient 1"), Name::from("ingredient 2")]);
+        let item = Name::from("spagheti");
+        let existing = Name::from("spaghetti");
+        store.add_list_item(&item).await.unwrap();
+        store.add_list_item(&existing).await.unwrap();
+        store.add_checklist_item(&item).await.unwrap();
 
-        let recipe = Recipe::new("test recipe");
-        store.add_recipe(&recipe, &ingredients).await.unwrap();
+        store.rename_item(&item, &existing).await.unwrap();
 
-        let StoreResponse::Recipes(recipes) = store.recipes().await.unwrap() else {
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
             todo!()
         };
-        assert_eq!(recipes.len(), 1);
+        assert_eq!(
+            list.items()
+                .iter()
+                .map(|item| item.name().clone())
+                .collect::<Vec<_>>(),
+            vec![existing.clone()]
+        );
+        let StoreResponse::Checklist(checklist) = store.checklist().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(
+            checklist
+                .iter()
+                .map(|item| item.name().clone())
+                .collect::<Vec<_>>(),
+            vec![existing]
+        );
+        assert!(matches!(
+            store.rename_item(&item, &Name::from("pasta")).await,
+            Err(StoreError::ItemNotFound(_))
+        ));
+    }
 
-        let added_recipe = &recipes[0];
-        assert_eq!(added_recipe.as_str(), "test recipe");
+    #[tokio::test]
+    async fn test_rename_item_merge_keeps_list_and_recipe_rows() {
+        let store = inmem_sqlite_store().await;
 
-        let StoreResponse::RecipeIngredients(Some(recipe_ingredients)) =
-            store.recipe_ingredients(&recipe).await.unwrap()
-        else {
+        let item = Name::from("spagheti");
+        let existing = Name::from("spaghetti");
+        let carbonara = Recipe::new("carbonara");
+        store
+            .add_recipe(
+                &carbonara,
+                &Ingredients::from_iter(vec![item.clone()]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store
+            .add_recipe(
+                &Recipe::new("bolognese"),
+                &Ingredients::from_iter(vec![existing.clone()]),
+                &None,
+            )
+            .await
+            .unwrap();
+        store
+            .add_list_recipe(&Recipe::new("bolognese"), false)
+            .await
+            .unwrap();
+        store.add_list_item(&item).await.unwrap();
+        {
+            use schema::{items, items_recipes};
+
+            for (name, times_listed) in [("spagheti", 2), ("spaghetti", 3)] {
+                diesel::update(items::table.filter(items::name.eq(name)))
+                    .set(items::times_listed.eq(times_listed))
+                    .execute(&mut store.connection().unwrap())
+                    .unwrap();
+            }
+
+            diesel::update(
+                items_recipes::table.filter(
+                    items_recipes::item_id.eq_any(
+                        items::table
+                            .filter(items::name.eq("spagheti"))
+                            .select(items::id),
+                    ),
+                ),
+            )
+            .set((items_recipes::quantity.eq(500), items_recipes::unit.eq("g")))
+            .execute(&mut store.connection().unwrap())
+            .unwrap();
+        }
+
+        store.rename_item(&item, &existing).await.unwrap();
+
+        {
+            use schema::{items, items_recipes, recipes};
+
+            let mut connection = store.connection().unwrap();
+            let amounts: Vec<(String, i32, Option<String>)> = items_recipes::table
+                .inner_join(items::table)
+                .inner_join(recipes::table)
+                .filter(items::name.eq("spaghetti"))
+                .select((recipes::name, items_recipes::quantity, items_recipes::unit))
+                .order_by(recipes::name)
+                .load(&mut connection)
+                .unwrap();
+            assert_eq!(
+                amounts,
+                vec![
+                    ("bolognese".to_string(), 1, None),
+                    ("carbonara".to_string(), 500, Some("g".to_string())),
+                ]
+            );
+            let times_listed: i32 = items::table
+                .filter(items::name.eq("spaghetti"))
+                .select(items::times_listed)
+                .first(&mut connection)
+                .unwrap();
+            assert_eq!(times_listed, 5);
+        }
+
+        // The merged item was added by hand, so it outlives the recipe that
+        // listed the existing one.
+        store
+            .remove_list_recipe(&Recipe::new("bolognese"), true)
+            .await
+            .unwrap();
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
             todo!()
         };
-        assert_eq!(recipe_ingredients, ingredients);
+        assert_eq!(
+            list.items()
+                .iter()
+                .map(|item| item.name().clone())
+                .collect::<Vec<_>>(),
+            vec![existing]
+        );
+    }
 
-        store.delete_recipe(&recipe).await.unwrap();
+    #[tokio::test]
+    async fn test_rename_item_strict() {
+        let store = inmem_sqlite_store().await;
+        let item = Name::from("spaghetti");
+        let existing = Name::from("pasta");
+        store.add_item(&item, &None).await.unwrap();
+        store.add_item(&existing, &None).await.unwrap();
 
-        let StoreResponse::Recipes(recipes) = store.recipes().await.unwrap() else {
+        assert!(matches!(
+            store.rename_item_strict(&item, &existing).await,
+            Err(StoreError::ItemExists(name)) if name == "pasta"
+        ));
+        // nothing was merged
+        assert!(store.item_id(&item).await.unwrap().is_some());
+
+        let new_name = Name::from("linguine");
+        store.rename_item_strict(&item, &new_name).await.unwrap();
+        assert!(store.item_id(&item).await.unwrap().is_none());
+        assert!(store.item_id(&new_name).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_mark_section_as_staples() {
+        let store = inmem_sqlite_store().await;
+
+        let dairy = common::section::Section::from("dairy");
+        let milk = Name::from("milk");
+        let butter = Name::from("butter");
+        store.add_item(&milk, &Some(dairy.clone())).await.unwrap();
+        store.add_item(&butter, &Some(dairy.clone())).await.unwrap();
+        store
+            .add_item(
+                &Name::from("bread"),
+                &Some(common::section::Section::from("bakery")),
+            )
+            .await
+            .unwrap();
+
+        let StoreResponse::SubscribedSection(section, staples) =
+            store.mark_section_as_staples(&dairy).await.unwrap()
+        else {
             todo!()
         };
-        assert_eq!(recipes.len(), 0);
+        assert_eq!(section, dairy);
+        assert_eq!(staples, vec![butter.clone(), milk.clone()]);
 
-        let StoreResponse::RecipeIngredients(recipe_ingredients) =
-            store.recipe_ingredients(&recipe).await.unwrap()
+        let StoreResponse::Subscriptions(subscriptions) = store.subscriptions().await.unwrap()
         else {
             todo!()
         };
-        assert_eq!(recipe_ingredients, None);
+        let mut subscribed = subscriptions
+            .iter()
+            .map(|item| item.name().as_str())
+            .collect::<Vec<_>>();
+        subscribed.sort();
+        assert_eq!(subscribed, vec!["butter", "milk"]);
+
+        assert!(matches!(
+            store
+                .mark_section_as_staples(&common::section::Section::from("frozen"))
+                .await,
+            Err(StoreError::SectionNotFound(_))
+        ));
     }
 
     #[tokio::test]
-    async fn test_refresh_list() {
+    async fn test_list_snapshot_for_refresh() {
         let store = inmem_sqlite_store().await;
 
-        store.refresh_list().await.unwrap();
+        let bread = Name::from("bread");
+        let milk = Name::from("milk");
+        store.add_list_item(&bread).await.unwrap();
+        store.add_list_item(&milk).await.unwrap();
+        store.add_checklist_item(&milk).await.unwrap();
 
+        let preview = store.list_snapshot_for_refresh(false).await.unwrap();
         let StoreResponse::List(list) = store.list().await.unwrap() else {
             todo!()
         };
-        assert_eq!(list.items().len(), 0);
+        assert_eq!(preview, list);
+        assert_eq!(preview.items().len(), 2);
 
-        let item1 = Name::from("item 1");
-        let item2 = Name::from("item 2");
-        store.add_list_item(&item1).await.unwrap();
-        store.add_list_item(&item2).await.unwrap();
+        // Subscribed items stay through a refresh that keeps subscriptions
+        store.subscribe_item(&milk).await.unwrap();
+        let preview = store.list_snapshot_for_refresh(true).await.unwrap();
+        assert_eq!(
+            preview
+                .items()
+                .iter()
+                .map(|item| item.name())
+                .collect::<Vec<_>>(),
+            vec![&bread]
+        );
+        assert_eq!(preview.checklist(), list.checklist());
+
+        store.refresh_list(false).await.unwrap();
+        assert!(store
+            .list_snapshot_for_refresh(false)
+            .await
+            .unwrap()
+            .items()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_list_with_subscriptions() {
+        let store = inmem_sqlite_store().await;
+
+        let milk = Name::from("milk");
+        let eggs = Name::from("eggs");
+        let bread = Name::from("bread");
+        store.subscribe_item(&milk).await.unwrap();
+        store.subscribe_item(&eggs).await.unwrap();
+        store.add_list_item(&bread).await.unwrap();
+        store.add_list_item(&milk).await.unwrap();
+        store.unsubscribe_item(&eggs).await.unwrap();
+
+        let StoreResponse::Subscriptions(subscriptions) = store.subscriptions().await.unwrap()
+        else {
+            todo!()
+        };
+        assert_eq!(
+            subscriptions
+                .iter()
+                .map(|item| item.name().clone())
+                .collect::<Vec<_>>(),
+            vec![milk.clone()]
+        );
+
+        store.refresh_list(true).await.unwrap();
 
         let StoreResponse::List(list) = store.list().await.unwrap() else {
             todo!()
         };
-        assert_eq!(list.items().len(), 2);
-        assert!(list.items().iter().any(|item| item.name() == &item1));
-        assert!(list.items().iter().any(|item| item.name() == &item2));
+        assert_eq!(
+            list.items()
+                .iter()
+                .map(|item| item.name().clone())
+                .collect::<Vec<_>>(),
+            vec![milk.clone()]
+        );
 
-        store.refresh_list().await.unwrap();
+        // The subscription is back by hand, so removing a recipe that also
+        // needs it leaves it listed.
+        let cereal = Recipe::new("cereal");
+        store
+            .add_recipe(&cereal, &Ingredients::from_iter(vec![milk.clone()]), &None)
+            .await
+            .unwrap();
+        store.add_list_recipe(&cereal, false).await.unwrap();
+        store.remove_list_recipe(&cereal, true).await.unwrap();
+        let StoreResponse::List(list) = store.list().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(
+            list.items()
+                .iter()
+                .map(|item| item.name().clone())
+                .collect::<Vec<_>>(),
+            vec![milk.clone()]
+        );
+
+        store.refresh_list(false).await.unwrap();
 
         let StoreResponse::List(list) = store.list().await.unwrap() else {
             todo!()
         };
-        assert_eq!(list.items().len(), 0);
+        assert!(list.items().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_items_and_recipes_stream() {
+        let store = inmem_sqlite_store().await;
+
+        // More than one batch, so the stream has to pick up where it left off
+        let rows: Vec<_> = (0..=BATCH_SIZE)
+            .map(|i| {
+                (
+                    Name::from(format!("item {i}").as_str()),
+                    common::section::Section::from(format!("section {}", i % 3)),
+                )
+            })
+            .collect();
+        store.import_item_sections(&rows).await.unwrap();
+        for recipe in ["pancakes", "omelette", "waffles"] {
+            store
+                .add_recipe(
+                    &Recipe::new(recipe),
+                    &Ingredients::from_iter(vec![Name::from("item 0")]),
+                    &None,
+                )
+                .await
+                .unwrap();
+        }
+        store.delete_recipe(&Recipe::new("omelette")).await.unwrap();
+
+        let mut stream = store.items_stream();
+        let mut streamed = Vec::new();
+        while let Some(item) = stream.recv().await {
+            streamed.push(item.unwrap());
+        }
+        assert_eq!(streamed, store.items().await.unwrap().collection());
+
+        let mut stream = store.recipes_stream();
+        let mut streamed = Vec::new();
+        while let Some(recipe) = stream.recv().await {
+            streamed.push(recipe.unwrap());
+        }
+        let StoreResponse::Recipes(recipes) = store.recipes().await.unwrap() else {
+            todo!()
+        };
+        assert_eq!(streamed, recipes);
+        assert_eq!(streamed.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_items_stream_lets_writes_through() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.path().join("groceries.db");
+        let store = SqliteStore::new(DbUri::from(path.to_str().unwrap()))
+            .await
+            .unwrap();
+        let rows: Vec<_> = (0..BATCH_SIZE * 2)
+            .map(|i| {
+                (
+                    Name::from(format!("item {i}").as_str()),
+                    common::section::Section::from("pantry"),
+                )
+            })
+            .collect();
+        store.import_item_sections(&rows).await.unwrap();
+
+        // The stream stalls with a full channel once one row has been taken
+        let mut stream = store.items_stream();
+        stream.recv().await.unwrap().unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        store.add_item(&Name::from("eggs"), &None).await.unwrap();
+
+        let mut streamed = 1;
+        while let Some(item) = stream.recv().await {
+            item.unwrap();
+            streamed += 1;
+        }
+        // Read after the write, the last batch has the new item too
+        assert_eq!(streamed, BATCH_SIZE * 2 + 1);
     }
 
     #[tokio::test]
@@ -858,7 +7797,10 @@ mod tests {
         let ingredients = Ingredients::from_iter(vec![Name::from("item 1"), Name::from("item 2")]);
         let recipe = Recipe::new("test recipe");
 
-        store.add_recipe(&recipe, &ingredients).await.unwrap();
+        store
+            .add_recipe(&recipe, &ingredients, &None)
+            .await
+            .unwrap();
 
         let items = store.items().await.unwrap();
 
@@ -885,6 +7827,7 @@ mod tests {
                             ),
                         ],
                     ),
+                    quantity: None,
                 },
                 Item {
                     name: Name(
@@ -902,6 +7845,7 @@ mod tests {
                             ),
                         ],
                     ),
+                    quantity: None,
                 },
             ],
         )
@@ -0,0 +1,34 @@
+use std::{collections::BTreeMap, fmt};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// How much the database holds: rows per table and, for a database kept in
+/// a file, that file's size in bytes.
+#[derive(Serialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct DbStats {
+    pub(crate) rows: BTreeMap<String, i64>,
+    pub(crate) file_size: Option<u64>,
+}
+
+impl DbStats {
+    pub fn rows(&self, table: &str) -> Option<i64> {
+        self.rows.get(table).copied()
+    }
+
+    pub fn file_size(&self) -> Option<u64> {
+        self.file_size
+    }
+}
+
+impl fmt::Display for DbStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (table, rows) in &self.rows {
+            writeln!(f, "{table}: {rows} rows")?;
+        }
+        match self.file_size {
+            Some(bytes) => write!(f, "file size: {bytes} bytes"),
+            None => write!(f, "file size: in memory"),
+        }
+    }
+}
@@ -1,13 +1,15 @@
+#[cfg(feature = "fetch-limiter")]
+use common::fetcher::{FetchLimiter, DEFAULT_MAX_IN_FLIGHT};
 use common::{
-    commands::{Add, ApiCommand, Delete, Read, Update},
-    export::ExportError,
+    commands::{Add, ApiCommand, Delete, FetchOptions, Read, Update},
+    export::{ExportError, Format},
     fetcher::{FetchError, Fetcher},
-    item::{Item, Name},
+    item::{Item, ItemDetail, ItemLookup, Name},
     items::Items,
-    list::List,
-    load::LoadError,
-    recipes::{Ingredients, Recipe},
-    section::Section,
+    list::{List, ListDiff, ShareError, ShoppingOrder, Trip, TripId},
+    load::{Load, LoadError},
+    recipes::{AddRecipeReport, Ingredients, Recipe, RecipeExport, RecipeSort, RecipeStatus},
+    section::{ItemSectionsCsv, ItemsCsvReport, Section, SectionMap, SectionMapReport},
 };
 use futures::FutureExt;
 use thiserror::Error;
@@ -18,12 +20,53 @@ use tokio::sync::{
 use tracing::warn;
 use url::Url;
 
-use std::{error::Error, fmt::Debug, fmt::Display, str::FromStr};
+use std::{
+    collections::BTreeMap, error::Error, fmt::Debug, fmt::Display, future::Future, str::FromStr,
+    time::Duration,
+};
+
+use crate::{
+    dump::DatabaseDump,
+    import_store::MigrationSummary,
+    sqlite::{connection::DbUri, SqliteStore},
+    stats::DbStats,
+};
+
+/// How long a fetched recipe is reused before its URL is fetched again.
+pub const FETCH_CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Most ingredients a recipe may have by default. Anything past this is more
+/// likely a scraper gone wrong than a real recipe.
+pub const MAX_INGREDIENTS: usize = 200;
+
+/// Most near matches suggested when an item isn't found.
+pub const MAX_SUGGESTIONS: usize = 5;
+
+/// How many ingredients are shown with each recipe when browsing recipes.
+pub const PREVIEW_INGREDIENTS: usize = 3;
+
+/// How many times a write transaction is retried while another writer holds
+/// the database lock, before giving up with [`StoreError::Busy`].
+pub const BUSY_RETRIES: u32 = 5;
+
+/// Wait before the first retry of a locked transaction; it doubles each time.
+pub(crate) const BUSY_BACKOFF: Duration = Duration::from_millis(20);
 
-use crate::sqlite::{connection::DbUri, SqliteStore};
+type StoreSendWithReply = (ApiCommand, StoreReply);
+
+/// Where the store sends what a command returns: whole, or one
+/// [`StoreChunk`] at a time.
+#[derive(Debug)]
+pub enum StoreReply {
+    Response(Sender<Result<StoreResponse, StoreError>>),
+    Stream(mpsc::Sender<Result<StoreChunk, StoreError>>),
+}
 
 #[derive(Error, Debug)]
 pub enum StoreError {
+    #[error("the database stayed locked by another writer")]
+    Busy,
+
     #[error("SQLite database connection error: {0}")]
     ConnectionError(#[from] diesel::ConnectionError),
 
@@ -42,6 +85,12 @@ pub enum StoreError {
     #[error("fetch error: {0}")]
     FetchError(#[from] FetchError),
 
+    #[error("an item with this name already exists: {0}")]
+    ItemExists(String),
+
+    #[error("item not found: {0}")]
+    ItemNotFound(String),
+
     #[error("JoinError: {0}")]
     JoinError(#[from] tokio::task::JoinError),
 
@@ -60,8 +109,42 @@ pub enum StoreError {
     #[error("ingredients not found for: {0}")]
     RecipeIngredients(String),
 
-    #[error("ingredients not found for: {0}")]
-    SendError(#[from] SendError<(ApiCommand, Sender<Result<StoreResponse, StoreError>>)>),
+    #[error("a recipe with this name already exists: {0}")]
+    RecipeExists(String),
+
+    #[error("trip not found: {0}")]
+    TripNotFound(TripId),
+
+    #[error("recipe not found: {0}")]
+    RecipeNotFound(String),
+
+    /// Kept behind a `Box`: the error holds the command and its reply channel.
+    #[error("failed to send a command to the store: {0}")]
+    SendError(#[from] Box<SendError<StoreSendWithReply>>),
+
+    #[error("section not found: {0}")]
+    SectionNotFound(String),
+
+    #[error("share code error: {0}")]
+    ShareError(#[from] ShareError),
+
+    #[error("too many ingredients for {0}: {1} is over the limit of {2}")]
+    TooManyIngredients(String, usize, usize),
+
+    #[error("not supported yet: {0}")]
+    Unsupported(&'static str),
+}
+
+impl StoreError {
+    /// Whether SQLite turned the command away because another connection
+    /// held the lock, so trying again later may succeed.
+    pub fn is_locked(&self) -> bool {
+        matches!(
+            self,
+            Self::DBQuery(diesel::result::Error::DatabaseError(_, info))
+                if info.message().contains("database is locked")
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -93,6 +176,44 @@ impl FromStr for StoreType {
     }
 }
 
+/// Connection settings for a store. Queries are sent to `read_uri` when set,
+/// otherwise everything goes through `write_uri`. A replica at `read_uri` may
+/// lag behind, so a query isn't guaranteed to see a write that has just been
+/// made, though each write reads what it needs from `write_uri` inside its own
+/// transaction. With `seed_sections`, the default sections are added once the
+/// store is set up. Recipes with more than `max_ingredients` ingredients are
+/// refused, and at most `max_fetches` recipe fetches run at once.
+pub struct StoreConfig {
+    pub write_uri: DbUri,
+    pub read_uri: Option<DbUri>,
+    pub seed_sections: bool,
+    pub max_ingredients: usize,
+    #[cfg(feature = "fetch-limiter")]
+    pub max_fetches: usize,
+}
+
+impl From<DbUri> for StoreConfig {
+    fn from(write_uri: DbUri) -> Self {
+        Self {
+            write_uri,
+            read_uri: None,
+            seed_sections: false,
+            max_ingredients: MAX_INGREDIENTS,
+            #[cfg(feature = "fetch-limiter")]
+            max_fetches: DEFAULT_MAX_IN_FLIGHT,
+        }
+    }
+}
+
+impl From<StoreType> for StoreConfig {
+    fn from(store_type: StoreType) -> Self {
+        match store_type {
+            StoreType::Sqlite => Self::from(DbUri::new()),
+            StoreType::SqliteInMem => Self::from(DbUri::inmem()),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum Store {
     Sqlite(SqliteStore),
@@ -107,11 +228,16 @@ impl Store {
         }
     }
 
+    pub async fn from_config(config: StoreConfig) -> Result<Self, StoreError> {
+        Ok(Self::Sqlite(SqliteStore::from_config(config).await?))
+    }
+
+    pub fn from_sqlite_store(store: SqliteStore) -> Self {
+        Self::Sqlite(store)
+    }
+
     pub async fn init(&self) -> Result<StoreDispatch, StoreError> {
-        let (tx, mut rx) = mpsc::channel::<(
-            ApiCommand,
-            oneshot::Sender<Result<StoreResponse, StoreError>>,
-        )>(10);
+        let (tx, mut rx) = mpsc::channel::<StoreSendWithReply>(10);
 
         let store = self.clone();
 
@@ -119,17 +245,24 @@ impl Store {
             loop {
                 tokio::select! {
                     cmd = rx.recv().fuse() => {
-                        if let Some((command, reply)) = cmd {
-                            let result = store
-                                .execute_transaction(command)
-                                .await;
-
-                            reply
-                                .send(result)
-                                .map_err(|e| {
-                                    warn!(?e, "Send reply to API command executor failed");
-                                })
-                                .ok();
+                        match cmd {
+                            // Fetches spend most of their time waiting on a
+                            // recipe site, so they run alongside other
+                            // commands, as many at once as the fetch limiter
+                            // allows
+                            Some((command, StoreReply::Response(reply))) if is_fetch(&command) => {
+                                let store = store.clone();
+                                tokio::task::spawn(async move {
+                                    store.respond(command, reply).await;
+                                });
+                            }
+                            Some((command, StoreReply::Response(reply))) => {
+                                store.respond(command, reply).await;
+                            }
+                            Some((command, StoreReply::Stream(reply))) => {
+                                store.execute_stream(command, reply).await;
+                            }
+                            None => {}
                         }
                     }
                     else => break
@@ -145,70 +278,276 @@ impl Store {
             Self::Sqlite(store) => store.execute_transaction(command).await,
         }
     }
+
+    /// Runs `command`, sending what it returns to `reply`.
+    async fn respond(
+        &self,
+        command: ApiCommand,
+        reply: oneshot::Sender<Result<StoreResponse, StoreError>>,
+    ) {
+        let result = self.execute_transaction(command).await;
+
+        reply
+            .send(result)
+            .map_err(|e| {
+                warn!(?e, "Send reply to API command executor failed");
+            })
+            .ok();
+    }
+
+    /// Runs `command`, sending what it returns to `reply` one chunk at a
+    /// time. The library's items and recipes are sent as they're read;
+    /// other collections are read whole, then sent an item or recipe at a
+    /// time. Chunks are sent from a task of their own, so a slow reader
+    /// doesn't hold up other commands.
+    async fn execute_stream(
+        &self,
+        command: ApiCommand,
+        reply: mpsc::Sender<Result<StoreChunk, StoreError>>,
+    ) {
+        let Self::Sqlite(store) = self;
+        match command {
+            ApiCommand::Read(Read::All) => {
+                tokio::task::spawn(forward(store.items_stream(), reply, StoreChunk::Item));
+            }
+            ApiCommand::Read(Read::Recipes) => {
+                tokio::task::spawn(forward(store.recipes_stream(), reply, StoreChunk::Recipe));
+            }
+            command => {
+                let chunks = match store.execute_transaction(command).await {
+                    Ok(response) => StoreChunk::split(response).into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(e)],
+                };
+                tokio::task::spawn(async move {
+                    for chunk in chunks {
+                        if reply.send(chunk).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        }
+    }
+}
+
+fn is_fetch(command: &ApiCommand) -> bool {
+    matches!(
+        command,
+        ApiCommand::FetchRecipe { .. } | ApiCommand::Update(Update::RefetchRecipe { .. })
+    )
+}
+
+/// Passes rows on to `reply` as chunks, until either side hangs up.
+async fn forward<T>(
+    mut rows: mpsc::Receiver<Result<T, StoreError>>,
+    reply: mpsc::Sender<Result<StoreChunk, StoreError>>,
+    chunk: fn(T) -> StoreChunk,
+) {
+    while let Some(row) = rows.recv().await {
+        if reply.send(row.map(chunk)).await.is_err() {
+            break;
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct StoreDispatch {
-    tx: mpsc::Sender<(
-        ApiCommand,
-        oneshot::Sender<Result<StoreResponse, StoreError>>,
-    )>,
+    tx: mpsc::Sender<StoreSendWithReply>,
 }
 
 impl StoreDispatch {
-    pub fn new(
-        tx: mpsc::Sender<(
-            ApiCommand,
-            oneshot::Sender<Result<StoreResponse, StoreError>>,
-        )>,
-    ) -> Self {
+    pub fn new(tx: mpsc::Sender<StoreSendWithReply>) -> Self {
         Self { tx }
     }
 
     pub async fn send(
         &self,
-        msg: (
+        (command, reply): (
             ApiCommand,
             oneshot::Sender<Result<StoreResponse, StoreError>>,
         ),
     ) -> Result<(), StoreError> {
-        self.tx.send(msg).await?;
+        self.tx
+            .send((command, StoreReply::Response(reply)))
+            .await
+            .map_err(Box::new)?;
+        Ok(())
+    }
+
+    /// Sends `command` to the store, which replies on `reply` one chunk at
+    /// a time.
+    pub async fn send_stream(
+        &self,
+        command: ApiCommand,
+        reply: mpsc::Sender<Result<StoreChunk, StoreError>>,
+    ) -> Result<(), StoreError> {
+        self.tx
+            .send((command, StoreReply::Stream(reply)))
+            .await
+            .map_err(Box::new)?;
         Ok(())
     }
 }
 
+/// One piece of a [`StoreResponse`] sent by a streamed command.
+#[derive(Debug)]
+pub enum StoreChunk {
+    Item(Item),
+    Recipe(Recipe),
+    Response(Box<StoreResponse>),
+}
+
+impl StoreChunk {
+    /// Splits collections of items or recipes into one chunk each, leaving
+    /// any other response whole.
+    fn split(response: StoreResponse) -> Vec<Self> {
+        match response {
+            StoreResponse::Items(items) => {
+                items.collection_iter().cloned().map(Self::Item).collect()
+            }
+            StoreResponse::Recipes(recipes) => recipes.into_iter().map(Self::Recipe).collect(),
+            response => vec![Self::Response(Box::new(response))],
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum StoreResponse {
     AddedChecklistItem(Name),
     AddedItem(Name),
     AddedListItem(Name),
     AddedListRecipe(Recipe),
-    AddedRecipe(Recipe),
+    AddedRecipe(Recipe, AddRecipeReport),
+    AddedSubscription(Name),
+    AppliedSectionMap(SectionMapReport),
     Checklist(Vec<Item>),
+    ClearedChecklist,
+    ClearedList,
+    CommonListIngredients(Vec<Name>),
+    CompletedTrip(TripId),
     DeletedRecipe(Recipe),
+    DeletedSections(Vec<Section>),
+    DeletedSubscription(Name),
     DeletedChecklistItem(Name),
+    DeletedItem(Name),
+    DeletedListItem(Name),
+    DuplicateItems(Vec<Vec<Name>>),
+    Dump(DatabaseDump),
     Exported(Vec<Item>, List),
+    ExportedChecklist(String),
+    ExportedList(String),
+    ExportedRecipe(String),
     FetchedRecipe((Recipe, Ingredients)),
-    ImportToSqlite,
+    Id(Option<i32>),
+    IngredientHistogram(BTreeMap<i64, i64>),
+    ImportedChecklist(Vec<Name>),
+    ImportedItemsCsv(ItemsCsvReport),
+    ImportedRecipes(Vec<Recipe>),
+    ImportedShareCode(List),
+    ImportToSqlite(MigrationSummary),
+    FrequentlyListed(Vec<(Name, usize)>),
+    IsEmpty(bool),
     ItemAlreadyAdded(Name),
+    ItemDetail(ItemDetail),
+    ItemNotFound {
+        query: Name,
+        suggestions: Vec<Name>,
+    },
     Items(Items),
+    ItemsWithSections(Vec<(Item, Option<Section>)>),
     List(List),
+    ListDiff(ListDiff),
+    ListRecipeStatus(Vec<RecipeStatus>),
+    MissingFromLibrary(Vec<i32>),
+    MergedRecipes(Recipe, Recipe),
+    MigrationStatus {
+        applied: Vec<String>,
+        pending: Vec<String>,
+    },
+    NormalizedUnit(String, String, usize),
     NothingReturned(ApiCommand),
+    Overlap(Vec<Name>),
+    RecipePreviews(Vec<(Recipe, Vec<Name>)>),
     Recipes(Vec<Recipe>),
     RecipeIngredients(Option<Ingredients>),
+    RecipeCoverage(Vec<(Recipe, i64, i64)>),
+    RecipeIngredientCount(Recipe, i64),
+    RecipeShoppingStatus(Vec<(Name, bool)>),
     RefreshList,
+    RemovedListRecipe(Recipe),
+    RenamedItem(Name, Name),
+    RenamedRecipe(Recipe, Recipe),
+    RenamedSection(Section, Section),
+    Restored,
+    RestoredRecipe(Recipe),
     Sections(Vec<Section>),
+    ShareCode(String),
+    ShoppingOrder(ShoppingOrder),
+    Stats(DbStats),
+    SubscribedSection(Section, Vec<Name>),
+    SuggestedSection(Name, Option<Section>),
+    Subscriptions(Vec<Item>),
+    SubstitutedIngredient(Name, Name),
+    ToggledListItem(Name, bool),
+    Trips(Vec<Trip>),
+    Units(Vec<String>),
+    UpdatedIngredient(Recipe, Name),
 }
 
 pub(crate) trait Storage: Send + Sync + 'static {
     async fn execute_transaction(&self, command: ApiCommand) -> Result<StoreResponse, StoreError> {
         match command {
             ApiCommand::Add(cmd) => self.add(cmd).await,
+            ApiCommand::ApplySectionMap(path) => {
+                let map = SectionMap::from_reader(&tokio::fs::read_to_string(path).await?)?;
+                Ok(StoreResponse::AppliedSectionMap(
+                    self.apply_section_map(&map).await?,
+                ))
+            }
             ApiCommand::Delete(cmd) => self.delete(cmd).await,
+            ApiCommand::Dump => Ok(StoreResponse::Dump(self.dump().await?)),
             ApiCommand::Export => self.export().await,
-            ApiCommand::FetchRecipe(url) => self.fetch_recipe(url).await,
-            ApiCommand::ImportFromJson => self.import_from_json().await,
+            ApiCommand::ExportChecklist => Ok(StoreResponse::ExportedChecklist(
+                self.export_checklist_json().await?,
+            )),
+            ApiCommand::ExportList(format) => self.export_list(format).await,
+            ApiCommand::ExportRecipe(recipe) => self.export_recipe(recipe).await,
+            ApiCommand::ExportShareCode => self.export_share_code().await,
+            ApiCommand::FetchRecipe {
+                url,
+                default_section,
+                options,
+            } => self.fetch_recipe(url, &default_section, options).await,
+            ApiCommand::ImportChecklist(path) => Ok(StoreResponse::ImportedChecklist(
+                self.import_checklist_json(&tokio::fs::read_to_string(path).await?)
+                    .await?,
+            )),
+            ApiCommand::ImportFromJson { fresh } => self.import_from_json(fresh).await,
+            ApiCommand::ImportItemsCsv(path) => {
+                let csv = ItemSectionsCsv::parse(&tokio::fs::read_to_string(path).await?);
+                let imported = self.import_item_sections(csv.rows()).await?;
+                Ok(StoreResponse::ImportedItemsCsv(ItemsCsvReport::new(
+                    imported,
+                    csv.malformed().to_vec(),
+                )))
+            }
+            ApiCommand::ImportPaprika(path) => Ok(StoreResponse::ImportedRecipes(
+                self.import_paprika(&tokio::fs::read_to_string(path).await?)
+                    .await?,
+            )),
+            ApiCommand::ImportShareCode(code) => self.import_share_code(&code).await,
+            ApiCommand::MigrationStatus => Ok(StoreResponse::MigrationStatus {
+                applied: self.applied_migrations().await?,
+                pending: self.pending_migrations().await?,
+            }),
             ApiCommand::Read(cmd) => self.read(cmd).await,
+            ApiCommand::Restore(path) => {
+                let dump = DatabaseDump::from_json_str(&tokio::fs::read_to_string(path).await?)?;
+                self.restore(&dump).await?;
+                Ok(StoreResponse::Restored)
+            }
+            // the API describes its own responses without asking the store
+            ApiCommand::Schema => Ok(StoreResponse::NothingReturned(ApiCommand::Schema)),
             ApiCommand::Update(cmd) => self.update(cmd).await,
         }
     }
@@ -216,97 +555,805 @@ pub(crate) trait Storage: Send + Sync + 'static {
     async fn add(&self, cmd: Add) -> Result<StoreResponse, StoreError> {
         match cmd {
             Add::ChecklistItem(name) => self.add_checklist_item(&name).await,
-            Add::Item { name, section } => self.add_item(&name, &section).await,
+            Add::ExistingChecklistItem(name) => self.checklist_existing_item(&name).await,
+            Add::Item { name, section } => {
+                if self.add_item(&name, &section).await? {
+                    Ok(StoreResponse::AddedItem(name))
+                } else {
+                    Ok(StoreResponse::ItemAlreadyAdded(name))
+                }
+            }
             Add::ListItem(name) => self.add_list_item(&name).await,
-            Add::ListRecipe(name) => self.add_list_recipe(&name).await,
+            Add::ListRecipe { recipe, refresh } => self.add_list_recipe(&recipe, refresh).await,
             Add::Recipe {
                 recipe,
                 ingredients,
-            } => self.add_recipe(&recipe, &ingredients).await,
+            } => {
+                let report = self.add_recipe(&recipe, &ingredients, &None).await?;
+                Ok(StoreResponse::AddedRecipe(recipe, report))
+            }
+            Add::Subscription(name) => self.subscribe_item(&name).await,
+            Add::RecipeFromText { recipe, text } => {
+                let report = self
+                    .add_recipe(&recipe, &Ingredients::from_lines(&text), &None)
+                    .await?;
+                Ok(StoreResponse::AddedRecipe(recipe, report))
+            }
         }
     }
 
     async fn read(&self, cmd: Read) -> Result<StoreResponse, StoreError> {
         match cmd {
             Read::All => Ok(StoreResponse::Items(self.items().await?)),
+            Read::AvailableRecipes => Ok(StoreResponse::Recipes(self.recipes_not_on_list().await?)),
             Read::Checklist => self.checklist().await,
-            Read::Item(_name) => todo!(),
+            Read::CombinedIngredients(recipes) => Ok(StoreResponse::RecipeIngredients(Some(
+                self.combined_ingredients(&recipes).await?,
+            ))),
+            Read::CommonListIngredients => Ok(StoreResponse::CommonListIngredients(
+                self.common_across_list_recipes().await?,
+            )),
+            Read::DeletedRecipes => self.deleted_recipes().await,
+            Read::DuplicateItems => Ok(StoreResponse::DuplicateItems(
+                self.duplicate_item_groups().await?,
+            )),
+            Read::EmptyRecipes => Ok(StoreResponse::Recipes(self.empty_recipes().await?)),
+            Read::EmptySections => Ok(StoreResponse::Sections(self.empty_sections().await?)),
+            Read::FrequentlyListed(limit) => Ok(StoreResponse::FrequentlyListed(
+                self.frequently_listed(limit).await?,
+            )),
+            Read::IngredientHistogram => Ok(StoreResponse::IngredientHistogram(
+                self.ingredient_count_histogram().await?,
+            )),
+            Read::IsEmpty => Ok(StoreResponse::IsEmpty(self.is_empty().await?)),
+            Read::Item(name) => match self.item_or_suggestions(&name).await? {
+                ItemLookup::Found(item) => Ok(StoreResponse::Items(Items::from_iter([item]))),
+                ItemLookup::NotFound { query, suggestions } => {
+                    Ok(StoreResponse::ItemNotFound { query, suggestions })
+                }
+            },
+            Read::ItemDetail(name) => Ok(StoreResponse::ItemDetail(self.item_detail(&name).await?)),
+            Read::ItemId(name) => Ok(StoreResponse::Id(self.item_id(&name).await?)),
+            Read::ItemsByPrefix(prefix) => {
+                Ok(StoreResponse::Items(self.items_by_prefix(&prefix).await?))
+            }
+            Read::ItemsWithSections => Ok(StoreResponse::ItemsWithSections(
+                self.items_with_sections().await?,
+            )),
             Read::List => self.list().await,
-            Read::ListRecipes => todo!(),
+            Read::ListDiff(path) => {
+                let snapshot = List::from_reader(&tokio::fs::read_to_string(path).await?)?;
+                let list = self.list_snapshot_for_refresh(false).await?;
+                Ok(StoreResponse::ListDiff(list.diff(&snapshot)))
+            }
+            Read::ListItemsFromRecipe(recipe) => Ok(StoreResponse::List(
+                List::new().with_items(self.items_from_recipe_on_list(&recipe).await?),
+            )),
+            Read::ListItemsOnly => self.list_items().await,
+            Read::ListMissingFromLibrary => Ok(StoreResponse::MissingFromLibrary(
+                self.list_items_missing_from_library().await?,
+            )),
+            Read::ListRecipes => Ok(StoreResponse::Recipes(self.list_recipes().await?)),
+            Read::ListRecipeStatus => self.list_recipe_status().await,
+            Read::Overlap => Ok(StoreResponse::Overlap(self.list_checklist_overlap().await?)),
             Read::Recipe(recipe) => self.recipe_ingredients(&recipe).await,
+            Read::RefreshPreview { subscriptions } => Ok(StoreResponse::List(
+                self.list_snapshot_for_refresh(subscriptions).await?,
+            )),
+            Read::RecentFetches(limit) => {
+                Ok(StoreResponse::Recipes(self.recent_fetches(limit).await?))
+            }
+            Read::RecipeAlreadyPlanned(recipe) => Ok(StoreResponse::RecipeIngredients(Some(
+                Ingredients::from_iter(self.recipe_ingredients_on_list(&recipe).await?),
+            ))),
+            Read::RecipeBySection(recipe) => Ok(StoreResponse::ItemsWithSections(
+                self.recipe_ingredients_detailed(&recipe).await?,
+            )),
+            Read::RecipeCoverage => Ok(StoreResponse::RecipeCoverage(
+                self.recipes_by_list_coverage().await?,
+            )),
+            Read::RecipeId(recipe) => Ok(StoreResponse::Id(self.recipe_id(&recipe).await?)),
+            Read::RecipeIngredientCount(recipe) => {
+                let count = self.recipe_ingredient_count(&recipe).await?;
+                Ok(StoreResponse::RecipeIngredientCount(recipe, count))
+            }
+            Read::RecipeStatus(recipe) => Ok(StoreResponse::RecipeShoppingStatus(
+                self.recipe_shopping_status(&recipe).await?,
+            )),
             Read::Recipes => self.recipes().await,
+            Read::RecipesPage {
+                offset,
+                limit,
+                sort,
+                preview: true,
+            } => Ok(StoreResponse::RecipePreviews(
+                self.recipe_previews_paged_sorted(offset, limit, sort)
+                    .await?,
+            )),
+            Read::RecipesPage {
+                offset,
+                limit,
+                sort,
+                preview: false,
+            } => Ok(StoreResponse::Recipes(
+                self.recipes_paged_sorted(offset, limit, sort).await?,
+            )),
+            Read::RecipesUsing(item) => Ok(StoreResponse::Recipes(
+                self.recipes_using(&Name::from(item.as_str())).await?,
+            )),
+            Read::Section(section) => {
+                Ok(StoreResponse::Items(self.items_in_section(&section).await?))
+            }
             Read::Sections => self.sections().await,
+            Read::Stats => Ok(StoreResponse::Stats(self.db_stats().await?)),
+            Read::ShoppingOrder => Ok(StoreResponse::ShoppingOrder(self.shopping_order().await?)),
+            Read::SuggestSection(item) => {
+                let section = self.suggest_section(&item).await?;
+                Ok(StoreResponse::SuggestedSection(item, section))
+            }
+            Read::Subscriptions => self.subscriptions().await,
+            Read::Trip(id) => Ok(StoreResponse::Trips(vec![self.trip(id).await?])),
+            Read::Trips => Ok(StoreResponse::Trips(self.trips().await?)),
+            Read::Units => Ok(StoreResponse::Units(self.distinct_units().await?)),
         }
     }
 
     async fn update(&self, cmd: Update) -> Result<StoreResponse, StoreError> {
         match cmd {
-            Update::Item(_name) => todo!(),
-            Update::RefreshList => self.refresh_list().await,
-            Update::Recipe(_name) => todo!(),
+            Update::CompleteTrip => Ok(StoreResponse::CompletedTrip(self.complete_trip().await?)),
+            Update::IngredientAmount {
+                recipe,
+                item,
+                quantity,
+                unit,
+            } => {
+                self.set_ingredient_amount(&recipe, &item, quantity, unit.as_deref())
+                    .await?;
+                Ok(StoreResponse::UpdatedIngredient(recipe, item))
+            }
+            Update::ListAllItems => {
+                self.add_all_items_to_list().await?;
+                self.list().await
+            }
+            Update::RenameItem { item, new_name } => self.rename_item(&item, &new_name).await,
+            Update::RenameItemStrict { item, new_name } => {
+                self.rename_item_strict(&item, &new_name).await
+            }
+            Update::RefreshList { subscriptions } => self.refresh_list(subscriptions).await,
+            Update::RefreshKeeping(keep) => self.refresh_list_keeping(&keep).await,
+            Update::MergeRecipes { keep, remove } => {
+                self.merge_recipes(&keep, &remove).await?;
+                Ok(StoreResponse::MergedRecipes(keep, remove))
+            }
+            Update::NormalizeUnit { from, to } => {
+                let updated = self.normalize_unit(&from, &to).await?;
+                Ok(StoreResponse::NormalizedUnit(from, to, updated))
+            }
+            Update::Recipe(_name) => Err(StoreError::Unsupported("editing a recipe's ingredients")),
+            Update::RefetchRecipe { recipe, url } => self.refetch_recipe(&recipe, url).await,
+            Update::RenameRecipe { recipe, new_name } => {
+                self.rename_recipe(&recipe, &new_name).await
+            }
+            Update::RenameSection { section, new_name } => {
+                self.rename_section(&section, &new_name).await
+            }
+            Update::RestoreRecipe(recipe) => self.restore_recipe(&recipe).await,
+            Update::StapleSection(section) => self.mark_section_as_staples(&section).await,
+            Update::RestoreTrip(id) => {
+                self.restore_trip(id).await?;
+                self.list().await
+            }
+            Update::SetList(items) => {
+                self.set_list(&items).await?;
+                self.list().await
+            }
+            Update::Substitute { from, to } => self.substitute_ingredient(&from, &to).await,
+            Update::ToggleListItem(name) => {
+                let on_list = self.toggle_list_item(&name).await?;
+                Ok(StoreResponse::ToggledListItem(name, on_list))
+            }
         }
     }
 
     async fn delete(&self, cmd: Delete) -> Result<StoreResponse, StoreError> {
         match cmd {
             Delete::ChecklistItem(name) => self.delete_checklist_item(&name).await,
-            Delete::ClearChecklist => todo!(),
-            Delete::ClearList => todo!(),
-            Delete::Item(_name) => todo!(),
-            Delete::ListItem(_name) => todo!(),
+            Delete::ClearChecklist => self.clear_checklist().await,
+            Delete::ClearList => self.clear_list().await,
+            Delete::EmptySections => Ok(StoreResponse::DeletedSections(
+                self.delete_empty_sections().await?,
+            )),
+            Delete::Item(name) => self.delete_item(&name).await,
+            Delete::ListItem(name) => self.delete_list_item(&name).await,
+            Delete::ListRecipe { recipe, with_items } => {
+                self.remove_list_recipe(&recipe, with_items).await?;
+                Ok(StoreResponse::RemovedListRecipe(recipe))
+            }
             Delete::Recipe(recipe) => self.delete_recipe(&recipe).await,
+            Delete::Subscription(name) => self.unsubscribe_item(&name).await,
         }
     }
 
+    /// Every row of every table, ids included.
+    async fn dump(&self) -> Result<DatabaseDump, StoreError>;
+
+    /// Replaces everything in the database with the contents of `dump`,
+    /// keeping its ids.
+    async fn restore(&self, dump: &DatabaseDump) -> Result<(), StoreError>;
+
     async fn export(&self) -> Result<StoreResponse, StoreError>;
 
-    async fn fetch_recipe(&self, url: Url) -> Result<StoreResponse, StoreError> {
-        let fetcher = Fetcher::from(url);
-        let (recipe, ingredients) = fetcher.fetch_recipe().await?;
+    /// Bounds how many of the store's recipe fetches run at once.
+    #[cfg(feature = "fetch-limiter")]
+    fn fetch_limiter(&self) -> FetchLimiter;
+
+    /// Fetches a recipe and adds it to the library. Ingredients new to the
+    /// library are put in `default_section`, when one is given.
+    ///
+    /// A URL fetched within [`FETCH_CACHE_TTL`], or one a recipe in the
+    /// library was imported from, is served from the library instead, unless
+    /// `options.force` is set. A page without any ingredients is an error
+    /// unless `options.allow_empty` is set.
+    async fn fetch_recipe(
+        &self,
+        url: Url,
+        default_section: &Option<Section>,
+        options: FetchOptions,
+    ) -> Result<StoreResponse, StoreError> {
+        #[cfg(feature = "fetch-limiter")]
+        let limiter = self.fetch_limiter();
+        self.fetch_recipe_with(url, default_section, options, |url| async move {
+            let fetcher = Fetcher::from(url).allow_empty(options.allow_empty);
+            #[cfg(feature = "fetch-limiter")]
+            let fetcher = fetcher.with_limiter(limiter);
+            fetcher.fetch_recipe().await
+        })
+        .await
+    }
+
+    /// Once fetched, the recipe is added by [`Self::add_fetched_recipe`], so
+    /// a busy database retries the write rather than the fetch.
+    async fn fetch_recipe_with<F, Fut>(
+        &self,
+        url: Url,
+        default_section: &Option<Section>,
+        options: FetchOptions,
+        fetch: F,
+    ) -> Result<StoreResponse, StoreError>
+    where
+        F: FnOnce(Url) -> Fut + Send,
+        Fut: Future<Output = Result<(Recipe, Ingredients), FetchError>> + Send,
+    {
+        if !options.force {
+            if let Some(fetched) = self.cached_recipe(&url).await? {
+                return Ok(StoreResponse::FetchedRecipe(fetched));
+            }
+            if let Some(recipe) = self.recipe_by_url(&url).await? {
+                if let StoreResponse::RecipeIngredients(Some(ingredients)) =
+                    self.recipe_ingredients(&recipe).await?
+                {
+                    return Ok(StoreResponse::FetchedRecipe((recipe, ingredients)));
+                }
+            }
+        }
+
+        let (recipe, ingredients) = fetch(url.clone()).await?;
 
-        self.add_recipe(&recipe, &ingredients).await?;
+        self.add_fetched_recipe(&url, &recipe, &ingredients, default_section, options)
+            .await?;
         Ok(StoreResponse::FetchedRecipe((recipe, ingredients)))
     }
 
-    async fn import_from_json(&self) -> Result<StoreResponse, StoreError>;
+    /// Fetches `url` again and replaces the recipe's ingredients with what's
+    /// found there, recording `url` as the recipe's source.
+    async fn refetch_recipe(&self, recipe: &Recipe, url: Url) -> Result<StoreResponse, StoreError> {
+        #[cfg(feature = "fetch-limiter")]
+        let limiter = self.fetch_limiter();
+        self.refetch_recipe_with(recipe, url, |url| async move {
+            let fetcher = Fetcher::from(url);
+            #[cfg(feature = "fetch-limiter")]
+            let fetcher = fetcher.with_limiter(limiter);
+            fetcher.fetch_recipe().await
+        })
+        .await
+    }
+
+    async fn refetch_recipe_with<F, Fut>(
+        &self,
+        recipe: &Recipe,
+        url: Url,
+        fetch: F,
+    ) -> Result<StoreResponse, StoreError>
+    where
+        F: FnOnce(Url) -> Fut + Send,
+        Fut: Future<Output = Result<(Recipe, Ingredients), FetchError>> + Send,
+    {
+        let (_, ingredients) = fetch(url.clone()).await?;
+        self.replace_recipe_ingredients(recipe, &ingredients, &url)
+            .await?;
+        self.cache_fetched_recipe(&url, recipe, &ingredients)
+            .await?;
+        Ok(StoreResponse::FetchedRecipe((recipe.clone(), ingredients)))
+    }
+
+    /// Swaps all of a recipe's ingredients for `ingredients` at once, and
+    /// records `source_url` as where they came from.
+    async fn replace_recipe_ingredients(
+        &self,
+        recipe: &Recipe,
+        ingredients: &Ingredients,
+        source_url: &Url,
+    ) -> Result<(), StoreError>;
+
+    /// Up to `limit` of the recipes fetched from a URL, most recently
+    /// fetched first.
+    async fn recent_fetches(&self, limit: usize) -> Result<Vec<Recipe>, StoreError>;
+
+    /// The recipe last fetched from `url` and the ingredients found there, if
+    /// fetched within [`FETCH_CACHE_TTL`].
+    async fn cached_recipe(&self, url: &Url) -> Result<Option<(Recipe, Ingredients)>, StoreError>;
+
+    /// Records `url` as where `recipe` was fetched from, with the
+    /// `ingredients` the fetch found.
+    async fn cache_fetched_recipe(
+        &self,
+        url: &Url,
+        recipe: &Recipe,
+        ingredients: &Ingredients,
+    ) -> Result<(), StoreError>;
+
+    /// Adds a recipe fetched from `url` and caches the fetch, all in one
+    /// transaction. Ingredients new to the library go in `default_section`,
+    /// or with `options.auto_section` in their suggested section if there
+    /// is one.
+    async fn add_fetched_recipe(
+        &self,
+        url: &Url,
+        recipe: &Recipe,
+        ingredients: &Ingredients,
+        default_section: &Option<Section>,
+        options: FetchOptions,
+    ) -> Result<AddRecipeReport, StoreError>;
+
+    /// The recipe in the library that was imported from `url`, if any.
+    async fn recipe_by_url(&self, url: &Url) -> Result<Option<Recipe>, StoreError>;
+
+    async fn export_list(&self, format: Format) -> Result<StoreResponse, StoreError> {
+        let StoreResponse::List(list) = self.list().await? else {
+            todo!()
+        };
+        match format {
+            Format::Plain => Ok(StoreResponse::ExportedList(list.to_plain())),
+            Format::BySection {
+                uncategorized_label,
+            } => Ok(StoreResponse::ExportedList(
+                list.to_plain_by_section(&uncategorized_label),
+            )),
+        }
+    }
+
+    async fn export_recipe(&self, recipe: Recipe) -> Result<StoreResponse, StoreError> {
+        let StoreResponse::RecipeIngredients(Some(ingredients)) =
+            self.recipe_ingredients(&recipe).await?
+        else {
+            return Err(StoreError::RecipeNotFound(recipe.to_string()));
+        };
+        Ok(StoreResponse::ExportedRecipe(
+            RecipeExport::new(recipe, ingredients).to_json(),
+        ))
+    }
+
+    /// The checklist's item names as a JSON array, to save as a template.
+    async fn export_checklist_json(&self) -> Result<String, StoreError> {
+        let StoreResponse::Checklist(checklist) = self.checklist().await? else {
+            todo!()
+        };
+        let names: Vec<&Name> = checklist.iter().map(Item::name).collect();
+        Ok(serde_json::to_string(&names)?)
+    }
+
+    /// Adds each name in a JSON array, as written by `export_checklist_json`,
+    /// to the checklist, returning the names added. Either every name is
+    /// added or none is.
+    async fn import_checklist_json(&self, json: &str) -> Result<Vec<Name>, StoreError>;
+
+    /// Adds each recipe in a Paprika JSON export with the items, quantities
+    /// and units its ingredient lines give, returning the recipes added.
+    /// Either every recipe is added or none is.
+    async fn import_paprika(&self, json: &str) -> Result<Vec<Recipe>, StoreError>;
+
+    async fn export_share_code(&self) -> Result<StoreResponse, StoreError> {
+        let StoreResponse::List(list) = self.list().await? else {
+            todo!()
+        };
+        Ok(StoreResponse::ShareCode(list.to_share_code()))
+    }
+
+    /// Imports the JSON store. With `fresh`, existing data is cleared first so
+    /// the store ends up mirroring the JSON.
+    async fn import_from_json(&self, fresh: bool) -> Result<StoreResponse, StoreError>;
+
+    /// Versions of the schema migrations that have been run.
+    async fn applied_migrations(&self) -> Result<Vec<String>, StoreError>;
+
+    /// Versions of the schema migrations that have yet to be run.
+    async fn pending_migrations(&self) -> Result<Vec<String>, StoreError>;
+
+    /// Adds a shared list's recipes, items and checklist to this one, all at
+    /// once. Recipes unknown to this library are skipped. Items one of the
+    /// shared recipes calls for come with that recipe; only the rest are
+    /// added by hand.
+    async fn import_share_code(&self, code: &str) -> Result<StoreResponse, StoreError>;
 
     // Create
-    async fn add_item(
+    /// Adds an item to the library, returning whether it was newly created.
+    async fn add_item(&self, item: &Name, section: &Option<Section>) -> Result<bool, StoreError>;
+
+    async fn add_checklist_item(&self, item: &Name) -> Result<StoreResponse, StoreError>;
+
+    /// Renames an item. Renaming into an existing item's name merges the two,
+    /// moving list, checklist, subscription, recipe, and section rows over to
+    /// the existing item.
+    async fn rename_item(&self, item: &Name, new_name: &Name) -> Result<StoreResponse, StoreError>;
+
+    /// Renames an item without ever merging. Fails with
+    /// [`StoreError::ItemExists`] if another item already has the new name.
+    async fn rename_item_strict(
         &self,
         item: &Name,
-        section: &Option<Section>,
+        new_name: &Name,
     ) -> Result<StoreResponse, StoreError>;
 
-    async fn add_checklist_item(&self, item: &Name) -> Result<StoreResponse, StoreError>;
+    /// Marks an item as always needed, so a refresh can put it back on the list.
+    async fn subscribe_item(&self, item: &Name) -> Result<StoreResponse, StoreError>;
+
+    async fn unsubscribe_item(&self, item: &Name) -> Result<StoreResponse, StoreError>;
+
+    async fn subscriptions(&self) -> Result<StoreResponse, StoreError>;
+
+    /// Adds an item to the checklist only if it's already in the library.
+    async fn checklist_existing_item(&self, item: &Name) -> Result<StoreResponse, StoreError>;
 
     async fn add_list_item(&self, item: &Name) -> Result<StoreResponse, StoreError>;
 
-    async fn add_list_recipe(&self, recipe: &Recipe) -> Result<StoreResponse, StoreError>;
+    /// Adds a recipe and its ingredients to the list. With `refresh`, the
+    /// recipe's items already on the list are removed first, so the list ends
+    /// up holding the recipe's current ingredients.
+    async fn add_list_recipe(
+        &self,
+        recipe: &Recipe,
+        refresh: bool,
+    ) -> Result<StoreResponse, StoreError>;
 
+    /// Adds a recipe with its ingredients, reporting the recipe's id and
+    /// which ingredients were new to the library. Ingredients created by
+    /// this call are put in `default_section`, if any.
     async fn add_recipe(
         &self,
         recipe: &Recipe,
         ingredients: &Ingredients,
-    ) -> Result<StoreResponse, StoreError>;
+        default_section: &Option<Section>,
+    ) -> Result<AddRecipeReport, StoreError>;
+
+    /// Adds each item to the library in its section, creating either as
+    /// needed, returning how many rows were imported.
+    async fn import_item_sections(&self, rows: &[(Name, Section)]) -> Result<usize, StoreError>;
+
+    /// Moves each item into its paired section, all in one go. Items or
+    /// sections that don't exist yet are skipped and reported rather than
+    /// created.
+    async fn set_item_sections(
+        &self,
+        pairs: &[(Name, Section)],
+    ) -> Result<SectionMapReport, StoreError>;
+
+    /// Moves each mapped item into its section, as [`Self::set_item_sections`].
+    async fn apply_section_map(&self, map: &SectionMap) -> Result<SectionMapReport, StoreError> {
+        self.set_item_sections(&map.iter().collect::<Vec<_>>())
+            .await
+    }
 
     // Read
     async fn checklist(&self) -> Result<StoreResponse, StoreError>;
 
+    async fn deleted_recipes(&self) -> Result<StoreResponse, StoreError>;
+
     async fn list(&self) -> Result<StoreResponse, StoreError>;
 
+    /// The list as it stands, so what a refresh would clear can be confirmed
+    /// before running it. With `subscriptions`, subscribed items are left
+    /// out, since that refresh puts them back.
+    async fn list_snapshot_for_refresh(&self, subscriptions: bool) -> Result<List, StoreError> {
+        let StoreResponse::List(list) = self.list().await? else {
+            unreachable!("list() returns a list")
+        };
+        if !subscriptions {
+            return Ok(list);
+        }
+        let StoreResponse::Subscriptions(subscribed) = self.subscriptions().await? else {
+            unreachable!("subscriptions() returns subscriptions")
+        };
+        Ok(List::new()
+            .with_items(
+                list.items()
+                    .iter()
+                    .filter(|item| {
+                        !subscribed
+                            .iter()
+                            .any(|subscribed| subscribed.name() == item.name())
+                    })
+                    .cloned(),
+            )
+            .with_checklist(list.checklist().iter().cloned())
+            .with_recipes(list.recipes().iter().cloned()))
+    }
+
+    /// The list's items grouped by section in the order they're walked past,
+    /// each with its quantity and the list recipes calling for it.
+    async fn shopping_order(&self) -> Result<ShoppingOrder, StoreError>;
+
+    /// The list's items and their quantities, leaving its recipes and
+    /// checklist empty.
+    async fn list_items(&self) -> Result<StoreResponse, StoreError>;
+
+    /// Ids on the list that don't belong to any item in the library, as an
+    /// integrity check.
+    async fn list_items_missing_from_library(&self) -> Result<Vec<i32>, StoreError>;
+
+    /// The list's items called for by `recipe`, if it's one of the list's
+    /// recipes.
+    async fn items_from_recipe_on_list(&self, recipe: &Recipe) -> Result<Vec<Item>, StoreError>;
+
+    /// The recipes on the list.
+    async fn list_recipes(&self) -> Result<Vec<Recipe>, StoreError>;
+
+    async fn list_recipe_status(&self) -> Result<StoreResponse, StoreError>;
+
+    /// The section most common among the items that share a recipe with
+    /// `item`, if any of them have one.
+    async fn suggest_section(&self, item: &Name) -> Result<Option<Section>, StoreError>;
+
+    /// Archives the list's items as a completed trip, then clears the list.
+    async fn complete_trip(&self) -> Result<TripId, StoreError>;
+
+    /// Completed trips, most recent first.
+    async fn trips(&self) -> Result<Vec<Trip>, StoreError>;
+
+    async fn trip(&self, id: TripId) -> Result<Trip, StoreError>;
+
+    /// Replaces the list with a completed trip's items.
+    async fn restore_trip(&self, id: TripId) -> Result<(), StoreError> {
+        let trip = self.trip(id).await?;
+        self.set_list(trip.items()).await
+    }
+
+    /// Ingredients that every recipe on the list calls for, in name order.
+    /// Empty when the list has no recipes.
+    async fn common_across_list_recipes(&self) -> Result<Vec<Name>, StoreError>;
+
+    /// Items that are on both the list and the checklist.
+    async fn list_checklist_overlap(&self) -> Result<Vec<Name>, StoreError>;
+
     async fn items(&self) -> Result<Items, StoreError>;
 
+    /// Every item in the library, as [`Self::items`], sent as it's read
+    /// rather than collected first.
+    fn items_stream(&self) -> mpsc::Receiver<Result<Item, StoreError>>;
+
+    /// Items whose names start with `prefix`, ignoring ASCII case, in name
+    /// order.
+    async fn items_by_prefix(&self, prefix: &str) -> Result<Items, StoreError>;
+
+    /// The item called `name` or, failing that, up to [`MAX_SUGGESTIONS`]
+    /// names of items that look like what was meant.
+    async fn item_or_suggestions(&self, name: &Name) -> Result<ItemLookup, StoreError>;
+
+    /// The item with its section and recipes, and whether it's on the list
+    /// and the checklist.
+    async fn item_detail(&self, name: &Name) -> Result<ItemDetail, StoreError>;
+
+    /// Groups of items stored under names that differ, but only by
+    /// surrounding whitespace or case, e.g. "Milk" and "milk ".
+    async fn duplicate_item_groups(&self) -> Result<Vec<Vec<Name>>, StoreError>;
+
+    /// Whether the library has no items yet, e.g. on first run.
+    async fn is_empty(&self) -> Result<bool, StoreError>;
+
+    /// Every item in the library paired with its section, if any.
+    async fn items_with_sections(&self) -> Result<Vec<(Item, Option<Section>)>, StoreError>;
+
+    /// The items in `section`, in the order they were added to the library.
+    async fn items_in_section(&self, section: &Section) -> Result<Items, StoreError>;
+
     async fn recipes(&self) -> Result<StoreResponse, StoreError>;
 
+    /// The library's recipes, sent as they're read rather than collected
+    /// first.
+    fn recipes_stream(&self) -> mpsc::Receiver<Result<Recipe, StoreError>>;
+
+    /// Up to `limit` recipes, skipping the first `offset` in `sort` order.
+    async fn recipes_paged_sorted(
+        &self,
+        offset: usize,
+        limit: usize,
+        sort: RecipeSort,
+    ) -> Result<Vec<Recipe>, StoreError>;
+
+    /// Up to `limit` items that have been on the list, most often first,
+    /// with how many times each was put there.
+    async fn frequently_listed(&self, limit: usize) -> Result<Vec<(Name, usize)>, StoreError>;
+
+    /// The page [`Self::recipes_paged_sorted`] reads, each recipe with its
+    /// first [`PREVIEW_INGREDIENTS`] ingredients by name.
+    async fn recipe_previews_paged_sorted(
+        &self,
+        offset: usize,
+        limit: usize,
+        sort: RecipeSort,
+    ) -> Result<Vec<(Recipe, Vec<Name>)>, StoreError>;
+
+    /// Recipes with an ingredient whose name contains `item`.
+    async fn recipes_using(&self, item: &Name) -> Result<Vec<Recipe>, StoreError>;
+
     async fn recipe_ingredients(&self, recipe: &Recipe) -> Result<StoreResponse, StoreError>;
 
+    /// A recipe's ingredients paired with their sections, for grouping a
+    /// recipe by aisle.
+    async fn recipe_ingredients_detailed(
+        &self,
+        recipe: &Recipe,
+    ) -> Result<Vec<(Item, Option<Section>)>, StoreError>;
+
+    /// The ingredients needed across all of `recipes`, each listed once.
+    async fn combined_ingredients(&self, recipes: &[Recipe]) -> Result<Ingredients, StoreError>;
+
+    /// Recipes that haven't been added to the list.
+    async fn recipes_not_on_list(&self) -> Result<Vec<Recipe>, StoreError>;
+
+    /// Recipes without any ingredients, e.g. left behind by a broken import.
+    async fn empty_recipes(&self) -> Result<Vec<Recipe>, StoreError>;
+
+    /// Sections no item belongs to.
+    async fn empty_sections(&self) -> Result<Vec<Section>, StoreError>;
+
+    /// Deletes the sections no item belongs to, returning them.
+    async fn delete_empty_sections(&self) -> Result<Vec<Section>, StoreError>;
+
+    /// The id the store gives an item, for integrations that need a stable
+    /// key.
+    async fn item_id(&self, name: &Name) -> Result<Option<i32>, StoreError>;
+
+    /// The id the store gives a recipe that hasn't been deleted.
+    async fn recipe_id(&self, recipe: &Recipe) -> Result<Option<i32>, StoreError>;
+
+    /// Each of a recipe's ingredients, and whether it's already on the list.
+    async fn recipe_shopping_status(
+        &self,
+        recipe: &Recipe,
+    ) -> Result<Vec<(Name, bool)>, StoreError>;
+
+    /// A recipe's ingredients that are already on the list.
+    async fn recipe_ingredients_on_list(&self, recipe: &Recipe) -> Result<Vec<Name>, StoreError> {
+        Ok(self
+            .recipe_shopping_status(recipe)
+            .await?
+            .into_iter()
+            .filter_map(|(ingredient, on_list)| on_list.then_some(ingredient))
+            .collect())
+    }
+
+    /// Number of ingredients in the recipe, without loading them.
+    async fn recipe_ingredient_count(&self, recipe: &Recipe) -> Result<i64, StoreError>;
+
+    /// Each recipe with how many of its ingredients are on the list and how
+    /// many it has, best covered first. Recipes without ingredients are left
+    /// out.
+    async fn recipes_by_list_coverage(&self) -> Result<Vec<(Recipe, i64, i64)>, StoreError>;
+
+    /// Row counts for every table, and the database file's size if it has
+    /// one.
+    async fn db_stats(&self) -> Result<DbStats, StoreError>;
+
+    /// Every unit recipes measure their ingredients in, alphabetically.
+    async fn distinct_units(&self) -> Result<Vec<String>, StoreError>;
+
+    /// Renames unit `from` to `to` in every recipe, returning how many
+    /// ingredients changed.
+    async fn normalize_unit(&self, from: &str, to: &str) -> Result<usize, StoreError>;
+
+    /// Sets how much of `item` `recipe` calls for. A `None` unit counts the
+    /// item itself, as in "2 eggs".
+    async fn set_ingredient_amount(
+        &self,
+        recipe: &Recipe,
+        item: &Name,
+        quantity: i32,
+        unit: Option<&str>,
+    ) -> Result<(), StoreError>;
+
+    /// How many recipes have each number of ingredients, keyed by that
+    /// number. Recipes without ingredients aren't counted.
+    async fn ingredient_count_histogram(&self) -> Result<BTreeMap<i64, i64>, StoreError>;
+
     async fn sections(&self) -> Result<StoreResponse, StoreError>;
 
+    /// Adds the default sections, in the order they're walked past, returning
+    /// how many were new.
+    async fn seed_default_sections(&self) -> Result<usize, StoreError>;
+
     // Update
-    async fn refresh_list(&self) -> Result<StoreResponse, StoreError>;
+    /// Deletes the list's items and recipes. The checklist is left alone.
+    async fn clear_list(&self) -> Result<StoreResponse, StoreError>;
+
+    /// Puts every item in the library on the list, returning how many weren't
+    /// on it already.
+    async fn add_all_items_to_list(&self) -> Result<usize, StoreError>;
+
+    /// Replaces the list's items with `items`, all at once.
+    async fn set_list(&self, items: &[Name]) -> Result<(), StoreError>;
+
+    /// Puts `name` on the list if it isn't there, and takes it off if it is.
+    /// Returns whether the item is on the list afterwards.
+    async fn toggle_list_item(&self, name: &Name) -> Result<bool, StoreError>;
+
+    /// Empties the list. With `subscriptions`, subscribed items are put back.
+    async fn refresh_list(&self, subscriptions: bool) -> Result<StoreResponse, StoreError>;
+
+    /// Empties the list except for the items named in `keep`.
+    async fn refresh_list_keeping(&self, keep: &[Name]) -> Result<StoreResponse, StoreError>;
+
+    /// Renames a recipe. Fails with [`StoreError::RecipeExists`] if the new
+    /// name is taken, including by a deleted recipe.
+    async fn rename_recipe(
+        &self,
+        recipe: &Recipe,
+        new_name: &Recipe,
+    ) -> Result<StoreResponse, StoreError>;
+
+    /// Folds `remove` into `keep`: `keep` gains any ingredients only `remove`
+    /// had, and takes its place on the list, then `remove` is deleted.
+    async fn merge_recipes(&self, keep: &Recipe, remove: &Recipe) -> Result<(), StoreError>;
+
+    /// Swaps ingredient `from` for `to` in every recipe that uses it, adding
+    /// `to` to the library if needed.
+    async fn substitute_ingredient(
+        &self,
+        from: &Name,
+        to: &Name,
+    ) -> Result<StoreResponse, StoreError>;
+
+    /// Renames a section. If the new name is already a section, the items in
+    /// `section` are moved into it instead.
+    async fn rename_section(
+        &self,
+        section: &Section,
+        new_name: &Section,
+    ) -> Result<StoreResponse, StoreError>;
+
+    async fn restore_recipe(&self, recipe: &Recipe) -> Result<StoreResponse, StoreError>;
+
+    /// Subscribes to every item currently in a section, so they all come back
+    /// when the list is refreshed.
+    async fn mark_section_as_staples(&self, section: &Section)
+        -> Result<StoreResponse, StoreError>;
 
     // Delete
+    /// Takes everything off the checklist.
+    async fn clear_checklist(&self) -> Result<StoreResponse, StoreError>;
+
     async fn delete_checklist_item(&self, item: &Name) -> Result<StoreResponse, StoreError>;
 
+    /// Deletes an item from the library, taking it off the list, the
+    /// checklist, its subscription, its section and its recipes.
+    async fn delete_item(&self, item: &Name) -> Result<StoreResponse, StoreError>;
+
+    /// Takes an item off the list, leaving it in the library.
+    async fn delete_list_item(&self, item: &Name) -> Result<StoreResponse, StoreError>;
+
     async fn delete_recipe(&self, recipe: &Recipe) -> Result<StoreResponse, StoreError>;
+
+    /// Takes a recipe off the list. With `with_items`, its items go too,
+    /// unless they were added by hand or another recipe on the list needs them.
+    async fn remove_list_recipe(&self, recipe: &Recipe, with_items: bool)
+        -> Result<(), StoreError>;
 }